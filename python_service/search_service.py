@@ -3,6 +3,7 @@ SAVVY Python Search Service
 Fast semantic search using sentence-transformers
 """
 import json
+import os
 import sys
 import numpy as np
 from pathlib import Path
@@ -10,10 +11,14 @@ from sentence_transformers import SentenceTransformer
 from typing import List, Dict, Optional
 import gzip
 
-# Cache directory for embeddings and indexes
-CACHE_DIR = Path.home() / '.savvy' / 'cache'
+# Cache directory for embeddings and indexes. The app passes the directory it
+# downloads to in SAVVY_CACHE_DIR; the default is where older versions kept it.
+CACHE_DIR = Path(os.environ.get('SAVVY_CACHE_DIR') or Path.home() / '.savvy' / 'cache')
 CACHE_DIR.mkdir(parents=True, exist_ok=True)
 
+# Cache files for chocolatey use the short "choco" prefix
+CACHE_PREFIXES = {'chocolatey': 'choco'}
+
 # Model
 MODEL_NAME = 'sentence-transformers/all-MiniLM-L6-v2'
 model = None
@@ -32,8 +37,9 @@ def load_model():
 def load_embeddings(source: str) -> Optional[Dict]:
     """Load embeddings for a given source (chocolatey or winget)"""
     # Try .gz file first, then fall back to .json
-    embedding_file_gz = CACHE_DIR / f'{source}-embeddings.json.gz'
-    embedding_file = CACHE_DIR / f'{source}-embeddings.json'
+    prefix = CACHE_PREFIXES.get(source, source)
+    embedding_file_gz = CACHE_DIR / f'{prefix}-embeddings.json.gz'
+    embedding_file = CACHE_DIR / f'{prefix}-embeddings.json'
 
     if embedding_file_gz.exists():
         with gzip.open(embedding_file_gz, 'rt', encoding='utf-8') as f:
@@ -55,8 +61,9 @@ def load_embeddings(source: str) -> Optional[Dict]:
 def load_index(source: str) -> Optional[Dict]:
     """Load package index for a given source"""
     # Try .gz file first, then fall back to .json
-    index_file_gz = CACHE_DIR / f'{source}-index.json.gz'
-    index_file = CACHE_DIR / f'{source}-index.json'
+    prefix = CACHE_PREFIXES.get(source, source)
+    index_file_gz = CACHE_DIR / f'{prefix}-index.json.gz'
+    index_file = CACHE_DIR / f'{prefix}-index.json'
 
     if index_file_gz.exists():
         with gzip.open(index_file_gz, 'rt', encoding='utf-8') as f:
@@ -81,7 +88,17 @@ def cosine_similarity(a: np.ndarray, b: np.ndarray) -> float:
     return float(dot_product / (norm_a * norm_b))
 
 
-def search(query: str, source: str = 'both', limit: int = 20, threshold: float = 0.3) -> List[Dict]:
+def parse_tags(tags) -> List[str]:
+    """Tags are a list in winget indexes, a space/comma separated string in choco ones"""
+    if isinstance(tags, list):
+        return [str(tag) for tag in tags]
+    if isinstance(tags, str):
+        return [tag for tag in tags.replace(',', ' ').split() if tag]
+    return []
+
+
+def search(query: str, source: str = 'both', limit: int = 20, threshold: float = 0.3,
+           category: Optional[str] = None) -> Dict:
     """
     Perform semantic search across package embeddings
 
@@ -90,9 +107,11 @@ def search(query: str, source: str = 'both', limit: int = 20, threshold: float =
         source: 'chocolatey', 'winget', or 'both'
         limit: Maximum number of results
         threshold: Minimum similarity score (0-1)
+        category: Only include packages in this category (case-insensitive)
 
     Returns:
-        List of search results with scores
+        {'results': [...], 'total_matches': n}, where results holds the best
+        `limit` matches and total_matches counts every match above threshold
     """
     # Load model
     embed_model = load_model()
@@ -128,21 +147,59 @@ def search(query: str, source: str = 'both', limit: int = 20, threshold: float =
                 # Get full package metadata from index
                 if pkg_id in index_data:
                     pkg_info = index_data[pkg_id]
+                    pkg_category = pkg_info.get('category') or None
+                    if category and (pkg_category or '').lower() != category.lower():
+                        continue
                     results.append({
                         'id': pkg_id,
                         'title': pkg_info.get('title', pkg_id),
                         'summary': pkg_info.get('summary', ''),
                         'score': similarity,
-                        'source': src
+                        'source': src,
+                        'category': pkg_category,
+                        'tags': parse_tags(pkg_info.get('tags'))
                     })
 
     # Sort by score and limit
     results.sort(key=lambda x: x['score'], reverse=True)
-    return results[:limit]
+    return {'results': results[:limit], 'total_matches': len(results)}
+
+
+def serve():
+    """
+    Serve newline-delimited JSON search requests from stdin until it closes.
+    Each request gets exactly one JSON line back on stdout: an object with
+    "results" and "total_matches", or an object with an "error" key.
+    """
+    load_model()
+
+    for line in sys.stdin:
+        line = line.strip()
+        if not line:
+            continue
+
+        try:
+            args = json.loads(line)
+            response = search(
+                query=args['query'],
+                source=args.get('source') or 'both',
+                limit=args.get('limit') or 20,
+                threshold=args.get('threshold') if args.get('threshold') is not None else 0.3,
+                category=args.get('category')
+            )
+        except Exception as e:
+            response = {'error': str(e)}
+
+        sys.stdout.write(json.dumps(response) + '\n')
+        sys.stdout.flush()
 
 
 def main():
     """Main CLI entry point"""
+    if len(sys.argv) == 2 and sys.argv[1] == '--serve':
+        serve()
+        return
+
     if len(sys.argv) < 3:
         print("Usage: python search_service.py <command> <json_args>", file=sys.stderr)
         sys.exit(1)
@@ -152,13 +209,14 @@ def main():
 
     if command == 'search':
         args = json.loads(args_json)
-        results = search(
+        response = search(
             query=args['query'],
             source=args.get('source', 'both'),
             limit=args.get('limit', 20),
-            threshold=args.get('threshold', 0.3)
+            threshold=args.get('threshold', 0.3),
+            category=args.get('category')
         )
-        print(json.dumps(results))
+        print(json.dumps(response))
     else:
         print(f"Unknown command: {command}", file=sys.stderr)
         sys.exit(1)
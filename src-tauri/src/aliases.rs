@@ -0,0 +1,75 @@
+// Aliases - links the different ids one app has in Chocolatey and winget
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::data_cache::ALIASES_FILE;
+use crate::search_service::read_cache_json;
+
+/// One app's id in each package manager, either of which may be unknown
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct AliasEntry {
+    #[serde(default)]
+    pub choco_id: Option<String>,
+    #[serde(default)]
+    pub winget_id: Option<String>,
+}
+
+impl AliasEntry {
+    fn id(&self, source: &str) -> Option<&str> {
+        match source {
+            "chocolatey" => self.choco_id.as_deref(),
+            "winget" => self.winget_id.as_deref(),
+            _ => None,
+        }
+    }
+}
+
+/// `aliases.json`: canonical app keys (e.g. `google-chrome`) mapped to the
+/// app's ids, e.g. `{ "choco_id": "googlechrome", "winget_id": "Google.Chrome" }`.
+///
+/// An app without an entry, or a source without an id in its entry, simply
+/// has no alias; callers fall back to whatever matching they did before.
+#[derive(Debug, Default)]
+pub struct AliasTable {
+    by_key: HashMap<String, AliasEntry>,
+    /// (source, lowercase id) to canonical key
+    by_id: HashMap<(String, String), String>,
+}
+
+impl AliasTable {
+    fn from_entries(by_key: HashMap<String, AliasEntry>) -> Self {
+        let mut by_id = HashMap::new();
+        for (key, entry) in &by_key {
+            for source in ["chocolatey", "winget"] {
+                if let Some(id) = entry.id(source) {
+                    by_id.insert((source.to_string(), id.to_lowercase()), key.clone());
+                }
+            }
+        }
+        Self { by_key, by_id }
+    }
+
+    /// Read the cached alias table; a missing or unreadable file gives an
+    /// empty table, since aliases only improve matching
+    pub fn load(cache_dir: &Path) -> Self {
+        match read_cache_json::<HashMap<String, AliasEntry>>(cache_dir, ALIASES_FILE) {
+            Ok(Some(entries)) => Self::from_entries(entries),
+            Ok(None) => Self::default(),
+            Err(e) => {
+                tracing::warn!("Ignoring alias table: {}", e);
+                Self::default()
+            }
+        }
+    }
+
+    /// Canonical key of the app `id` belongs to in `source`, ignoring case
+    pub fn canonical_key(&self, id: &str, source: &str) -> Option<String> {
+        self.by_id.get(&(source.to_string(), id.to_lowercase())).cloned()
+    }
+
+    /// The app's id in `source`, if the table knows it
+    pub fn resolve(&self, canonical: &str, source: &str) -> Option<String> {
+        self.by_key.get(canonical)?.id(source).map(str::to_string)
+    }
+}
@@ -0,0 +1,79 @@
+// Health Check - one diagnostic report of the environment, for bug reports
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+use crate::data_cache::{self, CacheStatus};
+use crate::logging;
+use crate::package_manager::{util, ManagerStatus, PackageManager};
+use crate::search_service;
+
+/// The Python interpreter used for semantic search
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PythonStatus {
+    pub available: bool,
+    pub interpreter: Option<String>,
+    pub version: Option<String>,
+    pub error: Option<String>,
+}
+
+/// Everything support needs to know about this machine's setup.
+///
+/// Each check runs on its own: one that fails leaves its field empty and
+/// adds a line to `errors` instead of failing the whole report.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealthReport {
+    pub app_version: String,
+    pub os: String,
+    pub arch: String,
+    pub managers: Vec<ManagerStatus>,
+    pub python: PythonStatus,
+    pub cache: CacheStatus,
+    pub elevated: bool,
+    pub cache_dir: Option<PathBuf>,
+    pub log_dir: Option<PathBuf>,
+    pub errors: Vec<String>,
+}
+
+/// Run every check and gather the results
+pub async fn health_check(package_manager: &PackageManager) -> HealthReport {
+    let (managers, python) = tokio::join!(package_manager.manager_statuses(), python_status());
+
+    let mut errors = Vec::new();
+    if let Some(error) = &python.error {
+        errors.push(format!("Python: {}", error));
+    }
+    let cache_dir = data_cache::get_cache_dir()
+        .map_err(|e| errors.push(format!("Cache directory: {}", e)))
+        .ok();
+    let log_dir = logging::get_log_dir()
+        .map_err(|e| errors.push(format!("Log directory: {}", e)))
+        .ok();
+
+    HealthReport {
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        os: std::env::consts::OS.to_string(),
+        arch: std::env::consts::ARCH.to_string(),
+        managers,
+        python,
+        cache: data_cache::cache_status(data_cache::cache_ttl()),
+        elevated: util::is_elevated(),
+        cache_dir,
+        log_dir,
+        errors,
+    }
+}
+
+async fn python_status() -> PythonStatus {
+    match search_service::python_version().await {
+        Ok((interpreter, version)) => PythonStatus {
+            available: true,
+            interpreter: Some(interpreter.to_string()),
+            version: Some(version).filter(|v| !v.is_empty()),
+            error: None,
+        },
+        Err(e) => PythonStatus {
+            error: Some(e.to_string()),
+            ..PythonStatus::default()
+        },
+    }
+}
@@ -0,0 +1,200 @@
+// Operation History - Append-only log of installs, uninstalls, upgrades, downgrades and repairs
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use crate::package_manager::{InstallResult, PackageError, PackageSource, RepairResult, UninstallResult, UpgradeResult};
+
+/// Serializes appends so concurrent operations never interleave lines
+static HISTORY_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HistoryOperation {
+    Install,
+    Uninstall,
+    Upgrade,
+    Downgrade,
+    Repair,
+}
+
+/// One completed package operation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub timestamp: DateTime<Utc>,
+    pub operation: HistoryOperation,
+    pub package_id: String,
+    pub source: PackageSource,
+    pub from_version: Option<String>,
+    pub to_version: Option<String>,
+    pub success: bool,
+    pub error: Option<String>,
+}
+
+/// Get the history file path (~/.savvy/history.jsonl)
+pub fn history_path() -> Result<PathBuf, String> {
+    let home_dir = dirs::home_dir()
+        .ok_or_else(|| "Could not determine home directory".to_string())?;
+
+    let savvy_dir = home_dir.join(".savvy");
+    fs::create_dir_all(&savvy_dir)
+        .map_err(|e| format!("Failed to create history directory: {}", e))?;
+
+    Ok(savvy_dir.join("history.jsonl"))
+}
+
+/// Append an entry to the history file as a single JSON line
+pub fn append(entry: &HistoryEntry) -> Result<(), String> {
+    let mut line = serde_json::to_string(entry)
+        .map_err(|e| format!("Failed to serialize history entry: {}", e))?;
+    line.push('\n');
+
+    let path = history_path()?;
+    let _guard = HISTORY_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .map_err(|e| format!("Failed to open history file: {}", e))?;
+    file.write_all(line.as_bytes())
+        .map_err(|e| format!("Failed to write history entry: {}", e))
+}
+
+/// Read history newest-first, up to `limit` entries. Unreadable lines are skipped.
+pub fn read(limit: Option<usize>) -> Result<Vec<HistoryEntry>, String> {
+    let path = history_path()?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let contents = fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read history file: {}", e))?;
+
+    Ok(contents
+        .lines()
+        .rev()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .take(limit.unwrap_or(usize::MAX))
+        .collect())
+}
+
+/// The version a package had before its most recent successful upgrade.
+///
+/// Returns `None` if no upgrade is recorded, the prior version wasn't known,
+/// or the package has been downgraded since (the upgrade was already undone).
+pub fn version_before_last_upgrade(package_id: &str, source: PackageSource) -> Result<Option<String>, String> {
+    let last_change = read(None)?.into_iter().find(|entry| {
+        entry.success
+            && entry.source == source
+            && entry.package_id.eq_ignore_ascii_case(package_id)
+            && matches!(entry.operation, HistoryOperation::Upgrade | HistoryOperation::Downgrade)
+            // No-op upgrades of an already up-to-date package changed nothing
+            && entry.from_version != entry.to_version
+    });
+
+    Ok(last_change
+        .filter(|entry| entry.operation == HistoryOperation::Upgrade)
+        .and_then(|entry| entry.from_version))
+}
+
+/// Delete the history file
+pub fn clear() -> Result<(), String> {
+    let path = history_path()?;
+    let _guard = HISTORY_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+    match fs::remove_file(&path) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(format!("Failed to clear history: {}", e)),
+    }
+}
+
+/// Record an install outcome
+pub fn record_install(package_id: &str, source: PackageSource, result: &Result<InstallResult, PackageError>) {
+    let entry = match result {
+        Ok(r) => entry(HistoryOperation::Install, &r.package_id, source, None, r.version.clone(), r.success, r.error.clone()),
+        Err(e) => entry(HistoryOperation::Install, package_id, source, None, None, false, Some(e.to_string())),
+    };
+    record(entry);
+}
+
+/// Record an uninstall outcome
+pub fn record_uninstall(package_id: &str, source: PackageSource, result: &Result<UninstallResult, PackageError>) {
+    let entry = match result {
+        Ok(r) => entry(HistoryOperation::Uninstall, &r.package_id, source, None, None, r.success, r.error.clone()),
+        Err(e) => entry(HistoryOperation::Uninstall, package_id, source, None, None, false, Some(e.to_string())),
+    };
+    record(entry);
+}
+
+/// Record an upgrade outcome
+pub fn record_upgrade(package_id: &str, source: PackageSource, result: &Result<UpgradeResult, PackageError>) {
+    record_version_change(HistoryOperation::Upgrade, package_id, source, result);
+}
+
+/// Record a downgrade outcome
+pub fn record_downgrade(package_id: &str, source: PackageSource, result: &Result<UpgradeResult, PackageError>) {
+    record_version_change(HistoryOperation::Downgrade, package_id, source, result);
+}
+
+/// Record a repair outcome
+pub fn record_repair(package_id: &str, source: PackageSource, result: &Result<RepairResult, PackageError>) {
+    let entry = match result {
+        Ok(r) => entry(HistoryOperation::Repair, &r.package_id, source, r.version.clone(), r.version.clone(), r.success, r.error.clone()),
+        Err(e) => entry(HistoryOperation::Repair, package_id, source, None, None, false, Some(e.to_string())),
+    };
+    record(entry);
+}
+
+fn record_version_change(
+    operation: HistoryOperation,
+    package_id: &str,
+    source: PackageSource,
+    result: &Result<UpgradeResult, PackageError>,
+) {
+    let entry = match result {
+        Ok(r) => entry(
+            operation,
+            &r.package_id,
+            source,
+            r.old_version.clone(),
+            r.new_version.clone(),
+            r.success,
+            r.error.clone(),
+        ),
+        Err(e) => entry(operation, package_id, source, None, None, false, Some(e.to_string())),
+    };
+    record(entry);
+}
+
+fn entry(
+    operation: HistoryOperation,
+    package_id: &str,
+    source: PackageSource,
+    from_version: Option<String>,
+    to_version: Option<String>,
+    success: bool,
+    error: Option<String>,
+) -> HistoryEntry {
+    HistoryEntry {
+        timestamp: Utc::now(),
+        operation,
+        package_id: package_id.to_string(),
+        source,
+        from_version,
+        to_version,
+        success,
+        error,
+    }
+}
+
+/// History is best-effort: a failed write shouldn't fail the operation itself
+fn record(entry: HistoryEntry) {
+    if let Err(e) = append(&entry) {
+        tracing::warn!("Failed to record history: {}", e);
+    }
+}
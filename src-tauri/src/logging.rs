@@ -0,0 +1,96 @@
+// Logging - tracing subscriber writing to a daily log file (and stderr in debug builds)
+use std::fs;
+use std::path::PathBuf;
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{fmt, EnvFilter};
+
+const LOG_FILE_PREFIX: &str = "savvy";
+const LOG_FILE_SUFFIX: &str = "log";
+
+/// Number of daily log files kept before the oldest is deleted
+const MAX_LOG_FILES: usize = 7;
+
+/// Filter used when `SAVVY_LOG` isn't set
+const DEFAULT_FILTER: &str = "info";
+
+/// Get the log directory path (~/.savvy/logs)
+pub fn get_log_dir() -> Result<PathBuf, String> {
+    let home_dir = dirs::home_dir()
+        .ok_or_else(|| "Could not determine home directory".to_string())?;
+
+    let log_dir = home_dir.join(".savvy").join("logs");
+
+    fs::create_dir_all(&log_dir)
+        .map_err(|e| format!("Failed to create log directory: {}", e))?;
+
+    Ok(log_dir)
+}
+
+/// Path of the most recently written log file, or the log directory itself
+/// if nothing has been logged yet
+pub fn get_log_path() -> Result<PathBuf, String> {
+    let log_dir = get_log_dir()?;
+    let entries = fs::read_dir(&log_dir)
+        .map_err(|e| format!("Failed to read log directory: {}", e))?;
+
+    let newest = entries
+        .flatten()
+        .filter(|entry| entry.file_name().to_string_lossy().starts_with(LOG_FILE_PREFIX))
+        .filter_map(|entry| {
+            let modified = entry.metadata().ok()?.modified().ok()?;
+            Some((modified, entry.path()))
+        })
+        .max_by_key(|(modified, _)| *modified)
+        .map(|(_, path)| path);
+
+    Ok(newest.unwrap_or(log_dir))
+}
+
+/// Install the global tracing subscriber.
+///
+/// Logs go to a daily-rotated file in `~/.savvy/logs`, plus stderr in debug
+/// builds. The filter defaults to `info` and can be changed with `SAVVY_LOG`
+/// (e.g. `SAVVY_LOG=savvy=debug`). The returned guard flushes the file writer
+/// and must be held until the app exits. If the log file can't be opened,
+/// logging still goes to stderr.
+pub fn init() -> Option<WorkerGuard> {
+    let filter = EnvFilter::try_from_env("SAVVY_LOG").unwrap_or_else(|_| EnvFilter::new(DEFAULT_FILTER));
+
+    let file_writer = get_log_dir().and_then(|log_dir| {
+        RollingFileAppender::builder()
+            .rotation(Rotation::DAILY)
+            .filename_prefix(LOG_FILE_PREFIX)
+            .filename_suffix(LOG_FILE_SUFFIX)
+            .max_log_files(MAX_LOG_FILES)
+            .build(log_dir)
+            .map_err(|e| format!("Failed to open log file: {}", e))
+    });
+
+    let (file_layer, guard, file_error) = match file_writer {
+        Ok(appender) => {
+            let (writer, guard) = tracing_appender::non_blocking(appender);
+            let layer = fmt::layer().with_writer(writer).with_ansi(false);
+            (Some(layer), Some(guard), None)
+        }
+        Err(e) => (None, None, Some(e)),
+    };
+
+    // Without a log file stderr is the only place left to report to
+    let stderr_layer = (cfg!(debug_assertions) || file_error.is_some())
+        .then(|| fmt::layer().with_writer(std::io::stderr));
+
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(file_layer)
+        .with(stderr_layer)
+        .init();
+
+    if let Some(e) = file_error {
+        tracing::warn!("{}", e);
+    }
+
+    guard
+}
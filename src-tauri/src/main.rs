@@ -3,17 +3,35 @@
 
 mod package_manager;
 mod search_service;
+mod aliases;
 mod data_cache;
+mod health;
+mod history;
+mod logging;
 
-use package_manager::{PackageManager, PackageSource, InstallResult, UninstallResult, UpgradeResult, InstalledPackage, PackageError};
-use search_service::{SearchRequest, SearchResult};
-use tauri::State;
+use package_manager::{PackageManager, PackageSource, InstallResult, UninstallResult, UpgradeResult, InstalledPackage, AvailablePackage, PackageDetails, UpgradablePackage, PackageError, PackagePreview, PinnedPackage, InstallOptions, Source, RepairResult, VerifyResult, UpdateSummary, PagedPackages};
+use search_service::{EmbeddingStore, PythonSearchDaemon, SearchRequest, SearchResponse, SearchResult, SearchSessions, Suggestion};
+use data_cache::{CacheInfo, CacheStatus, DownloadReport};
+use health::HealthReport;
+use history::HistoryEntry;
+use tauri::{Emitter, State};
+use tauri_plugin_opener::OpenerExt;
 use std::sync::Arc;
 use std::path::PathBuf;
+use std::time::Duration;
+
+/// Event carrying an `OperationStatus` while a package operation runs
+const OPERATION_STATUS_EVENT: &str = "operation-status";
+
+/// Event carrying the `DownloadReport` of a background refresh that updated the cache
+const CACHE_REFRESHED_EVENT: &str = "cache-refreshed";
 
 /// Tauri state for package manager
 struct AppState {
     package_manager: Arc<PackageManager>,
+    embedding_store: Arc<EmbeddingStore>,
+    search_daemon: Arc<PythonSearchDaemon>,
+    search_sessions: Arc<SearchSessions>,
 }
 
 /// Install a package
@@ -22,26 +40,188 @@ async fn install_package(
     package_id: String,
     source: PackageSource,
     state: State<'_, AppState>,
-) -> Result<InstallResult, String> {
-    state
+) -> Result<InstallResult, PackageError> {
+    let result = state
         .package_manager
         .install(&package_id, source)
+        .await;
+    history::record_install(&package_id, source, &result);
+    result
+}
+
+/// Install a specific version of a package (latest when `version` is omitted)
+#[tauri::command]
+async fn install_version(
+    package_id: String,
+    source: PackageSource,
+    version: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<InstallResult, PackageError> {
+    let result = state
+        .package_manager
+        .install_version(&package_id, source, version.as_deref())
+        .await;
+    history::record_install(&package_id, source, &result);
+    result
+}
+
+/// Install a package with extra arguments passed through to the package manager
+#[tauri::command]
+async fn install_with_args(
+    package_id: String,
+    source: PackageSource,
+    extra_args: Vec<String>,
+    state: State<'_, AppState>,
+) -> Result<InstallResult, PackageError> {
+    let result = state
+        .package_manager
+        .install_with_options(&package_id, source, &InstallOptions { extra_args, ..Default::default() })
+        .await;
+    history::record_install(&package_id, source, &result);
+    result
+}
+
+/// Install a package with any combination of version, source and extra arguments
+#[tauri::command]
+async fn install_with_options(
+    package_id: String,
+    source: PackageSource,
+    options: InstallOptions,
+    state: State<'_, AppState>,
+) -> Result<InstallResult, PackageError> {
+    let result = state
+        .package_manager
+        .install_with_options(&package_id, source, &options)
+        .await;
+    history::record_install(&package_id, source, &result);
+    result
+}
+
+/// Reinstall a package over its current installation
+#[tauri::command]
+async fn reinstall_package(
+    package_id: String,
+    source: PackageSource,
+    state: State<'_, AppState>,
+) -> Result<InstallResult, PackageError> {
+    let result = state
+        .package_manager
+        .reinstall(&package_id, source)
+        .await;
+    history::record_install(&package_id, source, &result);
+    result
+}
+
+/// Install several packages, returning one result per package
+#[tauri::command]
+async fn install_batch(
+    package_ids: Vec<String>,
+    source: PackageSource,
+    state: State<'_, AppState>,
+) -> Result<Vec<InstallResult>, PackageError> {
+    let results = state.package_manager.install_batch(package_ids, source).await;
+    for result in &results {
+        history::record_install(&result.package_id, source, &Ok(result.clone()));
+    }
+    Ok(results)
+}
+
+/// Install from a local manifest or package file
+#[tauri::command]
+async fn install_local(
+    path: PathBuf,
+    source: PackageSource,
+    state: State<'_, AppState>,
+) -> Result<InstallResult, PackageError> {
+    let result = state
+        .package_manager
+        .install_local(&path, source)
+        .await;
+    history::record_install(&path.to_string_lossy(), source, &result);
+    result
+}
+
+/// Dry run: what installing a package would do
+#[tauri::command]
+async fn preview_install(
+    package_id: String,
+    source: PackageSource,
+    state: State<'_, AppState>,
+) -> Result<PackagePreview, PackageError> {
+    state
+        .package_manager
+        .preview_install(&package_id, source)
         .await
-        .map_err(|e| e.to_string())
 }
 
-/// Uninstall a package
+/// Dry run: what upgrading a package would do
 #[tauri::command]
-async fn uninstall_package(
+async fn preview_upgrade(
     package_id: String,
     source: PackageSource,
     state: State<'_, AppState>,
-) -> Result<UninstallResult, String> {
+) -> Result<PackagePreview, PackageError> {
     state
         .package_manager
-        .uninstall(&package_id, source)
+        .preview_upgrade(&package_id, source)
         .await
-        .map_err(|e| e.to_string())
+}
+
+/// Pin a package so bulk upgrades skip it
+#[tauri::command]
+async fn pin_package(
+    package_id: String,
+    source: PackageSource,
+    state: State<'_, AppState>,
+) -> Result<(), PackageError> {
+    state.package_manager.pin(&package_id, source).await
+}
+
+/// Remove a package's pin
+#[tauri::command]
+async fn unpin_package(
+    package_id: String,
+    source: PackageSource,
+    state: State<'_, AppState>,
+) -> Result<(), PackageError> {
+    state.package_manager.unpin(&package_id, source).await
+}
+
+/// List pinned packages
+#[tauri::command]
+async fn list_pinned(source: PackageSource, state: State<'_, AppState>) -> Result<Vec<PinnedPackage>, PackageError> {
+    state.package_manager.list_pinned(source).await
+}
+
+/// Uninstall a package
+#[tauri::command]
+async fn uninstall_package(
+    package_id: String,
+    source: PackageSource,
+    state: State<'_, AppState>,
+) -> Result<UninstallResult, PackageError> {
+    let result = state
+        .package_manager
+        .uninstall(&package_id, source, false)
+        .await;
+    history::record_uninstall(&package_id, source, &result);
+    result
+}
+
+/// Uninstall a package and also remove what it leaves behind; the result
+/// lists what was removed
+#[tauri::command]
+async fn uninstall_purge(
+    package_id: String,
+    source: PackageSource,
+    state: State<'_, AppState>,
+) -> Result<UninstallResult, PackageError> {
+    let result = state
+        .package_manager
+        .uninstall(&package_id, source, true)
+        .await;
+    history::record_uninstall(&package_id, source, &result);
+    result
 }
 
 /// Upgrade a package
@@ -50,12 +230,155 @@ async fn upgrade_package(
     package_id: String,
     source: PackageSource,
     state: State<'_, AppState>,
-) -> Result<UpgradeResult, String> {
-    state
+) -> Result<UpgradeResult, PackageError> {
+    let result = state
         .package_manager
         .upgrade(&package_id, source)
+        .await;
+    history::record_upgrade(&package_id, source, &result);
+    result
+}
+
+/// Install an older version of a package; `force` skips the "must be older" check
+#[tauri::command]
+async fn downgrade_package(
+    package_id: String,
+    source: PackageSource,
+    target_version: String,
+    force: bool,
+    state: State<'_, AppState>,
+) -> Result<UpgradeResult, PackageError> {
+    let result = state
+        .package_manager
+        .downgrade(&package_id, source, &target_version, force)
+        .await;
+    history::record_downgrade(&package_id, source, &result);
+    result
+}
+
+/// Reinstall the version a package had before its last upgrade, as recorded
+/// in the history log
+#[tauri::command]
+async fn rollback_last_upgrade(
+    package_id: String,
+    source: PackageSource,
+    state: State<'_, AppState>,
+) -> Result<UpgradeResult, PackageError> {
+    let previous = history::version_before_last_upgrade(&package_id, source)
+        .map_err(PackageError::Unknown)?
+        .ok_or_else(|| {
+            PackageError::NotFound(format!("{}: no version before its last upgrade is recorded", package_id))
+        })?;
+
+    // The history already says this is the older version, so skip the comparison
+    let result = state
+        .package_manager
+        .downgrade(&package_id, source, &previous, true)
+        .await;
+    history::record_downgrade(&package_id, source, &result);
+    result
+}
+
+/// Repair a damaged install of a package
+#[tauri::command]
+async fn repair_package(
+    package_id: String,
+    source: PackageSource,
+    state: State<'_, AppState>,
+) -> Result<RepairResult, PackageError> {
+    let result = state
+        .package_manager
+        .repair(&package_id, source)
+        .await;
+    history::record_repair(&package_id, source, &result);
+    result
+}
+
+/// Download a winget package's installer and manifest without installing it
+#[cfg(not(target_os = "macos"))]
+#[tauri::command]
+async fn download_package(
+    package_id: String,
+    dest_dir: PathBuf,
+    state: State<'_, AppState>,
+) -> Result<package_manager::DownloadResult, PackageError> {
+    state
+        .package_manager
+        .download(&package_id, &dest_dir)
         .await
-        .map_err(|e| e.to_string())
+}
+
+/// Find a package among everything installed, under any source. Ids the same
+/// app has in other sources' indexes count too, so `googlechrome` also finds
+/// an installed `Google.Chrome`.
+#[tauri::command]
+async fn is_installed(
+    package_id: String,
+    state: State<'_, AppState>,
+) -> Result<Option<InstalledPackage>, PackageError> {
+    let aliases: Vec<(PackageSource, String)> =
+        search_service::equivalent_ids(&state.embedding_store, &package_id)
+            .await
+            .unwrap_or_else(|e| {
+                tracing::warn!("Failed to look up aliases for {}: {}", package_id, e);
+                Vec::new()
+            })
+            .into_iter()
+            .filter_map(|(source, id)| match source.as_str() {
+                "chocolatey" => Some((PackageSource::Chocolatey, id)),
+                "winget" => Some((PackageSource::Winget, id)),
+                _ => None,
+            })
+            .collect();
+
+    Ok(state.package_manager.find_installed(&package_id, &aliases).await)
+}
+
+/// Check a package is installed and, where supported, that its files are intact
+#[tauri::command]
+async fn verify_package(
+    package_id: String,
+    source: PackageSource,
+    state: State<'_, AppState>,
+) -> Result<VerifyResult, PackageError> {
+    state
+        .package_manager
+        .verify(&package_id, source)
+        .await
+}
+
+/// Upgrade every package with an available update
+#[tauri::command]
+async fn upgrade_all(
+    source: PackageSource,
+    state: State<'_, AppState>,
+) -> Result<Vec<UpgradeResult>, PackageError> {
+    let results = state
+        .package_manager
+        .upgrade_all(source)
+        .await?;
+    for result in &results {
+        history::record_upgrade(&result.package_id, source, &Ok(result.clone()));
+    }
+    Ok(results)
+}
+
+/// List installed packages with an update available
+#[tauri::command]
+async fn list_upgradable(
+    source: PackageSource,
+    state: State<'_, AppState>,
+) -> Result<Vec<UpgradablePackage>, PackageError> {
+    state
+        .package_manager
+        .list_upgradable(source)
+        .await
+}
+
+/// Count available updates across every source, noting any that couldn't be checked
+#[tauri::command]
+async fn check_for_updates(state: State<'_, AppState>) -> Result<UpdateSummary, PackageError> {
+    state.package_manager.check_for_updates().await
 }
 
 /// List installed packages
@@ -63,30 +386,431 @@ async fn upgrade_package(
 async fn list_installed_packages(
     source: PackageSource,
     state: State<'_, AppState>,
-) -> Result<Vec<InstalledPackage>, String> {
+) -> Result<Vec<InstalledPackage>, PackageError> {
     state
         .package_manager
         .list_installed(source)
         .await
-        .map_err(|e| e.to_string())
 }
 
-/// Semantic search using Python backend
+/// List one page of installed packages
+#[tauri::command]
+async fn list_installed_paged(
+    source: PackageSource,
+    offset: Option<usize>,
+    limit: Option<usize>,
+    state: State<'_, AppState>,
+) -> Result<PagedPackages, PackageError> {
+    state
+        .package_manager
+        .list_installed_paged(
+            source,
+            offset.unwrap_or(0),
+            limit.unwrap_or(package_manager::DEFAULT_PAGE_SIZE),
+        )
+        .await
+}
+
+/// Count installed packages for a source without sending the list
 #[tauri::command]
-async fn semantic_search(request: SearchRequest) -> Result<Vec<SearchResult>, String> {
-    search_service::semantic_search(request)
+async fn count_installed(source: PackageSource, state: State<'_, AppState>) -> Result<usize, PackageError> {
+    state.package_manager.count_installed(source).await
 }
 
-/// Download and cache all data files
+/// Count installed packages across every available source
 #[tauri::command]
-async fn download_cache_data() -> Result<Vec<PathBuf>, String> {
-    data_cache::download_all_data().await
+async fn count_installed_all(state: State<'_, AppState>) -> Result<usize, PackageError> {
+    state.package_manager.count_installed_all().await
 }
 
-/// Check if cache is valid
+/// Re-read the installed packages for a source, replacing the cached list
 #[tauri::command]
-fn is_cache_valid() -> bool {
-    data_cache::is_cache_valid()
+async fn refresh_installed(
+    source: PackageSource,
+    state: State<'_, AppState>,
+) -> Result<Vec<InstalledPackage>, PackageError> {
+    state.package_manager.refresh_installed(source).await
+}
+
+/// List installed packages matching a name or id filter
+#[tauri::command]
+async fn list_installed_filtered(
+    source: PackageSource,
+    filter: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<InstalledPackage>, PackageError> {
+    state
+        .package_manager
+        .list_installed_filtered(source, filter.as_deref())
+        .await
+}
+
+/// List the package sources usable on this machine
+#[tauri::command]
+async fn available_sources(state: State<'_, AppState>) -> Result<Vec<PackageSource>, PackageError> {
+    Ok(state.package_manager.available_sources().await)
+}
+
+/// Search the Winget catalog
+#[tauri::command]
+async fn search_winget(
+    query: String,
+    catalog: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<AvailablePackage>, PackageError> {
+    state
+        .package_manager
+        .search(&query, PackageSource::Winget, catalog.as_deref())
+        .await
+}
+
+/// Search the Chocolatey repository, or only the named source when `catalog` is given
+#[tauri::command]
+async fn search_chocolatey(
+    query: String,
+    catalog: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<AvailablePackage>, PackageError> {
+    state
+        .package_manager
+        .search(&query, PackageSource::Chocolatey, catalog.as_deref())
+        .await
+}
+
+/// Search Homebrew formulae and casks
+#[tauri::command]
+async fn search_homebrew(
+    query: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<AvailablePackage>, PackageError> {
+    state
+        .package_manager
+        .search(&query, PackageSource::Homebrew, None)
+        .await
+}
+
+/// List the versions a package offers, newest first
+#[tauri::command]
+async fn list_versions(
+    package_id: String,
+    source: PackageSource,
+    state: State<'_, AppState>,
+) -> Result<Vec<String>, PackageError> {
+    state
+        .package_manager
+        .available_versions(&package_id, source)
+        .await
+}
+
+/// List the repositories a package manager installs from
+#[tauri::command]
+async fn list_sources(
+    source: PackageSource,
+    state: State<'_, AppState>,
+) -> Result<Vec<Source>, PackageError> {
+    state.package_manager.list_sources(source).await
+}
+
+/// Register a repository, e.g. an internal winget REST feed
+#[tauri::command]
+async fn add_source(
+    source: PackageSource,
+    name: String,
+    url: String,
+    source_type: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<(), PackageError> {
+    state
+        .package_manager
+        .add_source(source, &name, &url, source_type.as_deref())
+        .await
+}
+
+/// Remove a repository by name
+#[tauri::command]
+async fn remove_source(
+    source: PackageSource,
+    name: String,
+    state: State<'_, AppState>,
+) -> Result<(), PackageError> {
+    state.package_manager.remove_source(source, &name).await
+}
+
+/// Re-enable a disabled repository (Chocolatey only)
+#[tauri::command]
+async fn enable_source(
+    source: PackageSource,
+    name: String,
+    state: State<'_, AppState>,
+) -> Result<(), PackageError> {
+    state.package_manager.set_source_enabled(source, &name, true).await
+}
+
+/// Stop using a repository without removing it (Chocolatey only)
+#[tauri::command]
+async fn disable_source(
+    source: PackageSource,
+    name: String,
+    state: State<'_, AppState>,
+) -> Result<(), PackageError> {
+    state.package_manager.set_source_enabled(source, &name, false).await
+}
+
+/// Get description, publisher, homepage and license for a package
+#[tauri::command]
+async fn get_package_details(
+    package_id: String,
+    source: PackageSource,
+    state: State<'_, AppState>,
+) -> Result<PackageDetails, PackageError> {
+    state
+        .package_manager
+        .details(&package_id, source)
+        .await
+}
+
+/// Export installed packages to a JSON manifest file
+#[tauri::command]
+async fn export_packages(path: PathBuf, state: State<'_, AppState>) -> Result<(), PackageError> {
+    let manifest = state.package_manager.export_installed().await?;
+
+    std::fs::write(&path, manifest)
+        .map_err(|e| PackageError::Unknown(format!("Failed to write manifest: {}", e)))
+}
+
+/// Export installed packages as a PowerShell script that reinstalls them
+#[tauri::command]
+async fn export_script(path: PathBuf, state: State<'_, AppState>) -> Result<(), PackageError> {
+    let script = state.package_manager.export_script().await?;
+
+    std::fs::write(&path, script)
+        .map_err(|e| PackageError::Unknown(format!("Failed to write script: {}", e)))
+}
+
+/// Export installed winget packages as a winget configuration (`.dsc.yaml`) file
+#[cfg(not(target_os = "macos"))]
+#[tauri::command]
+async fn export_winget_configuration(path: PathBuf, state: State<'_, AppState>) -> Result<(), PackageError> {
+    let configuration = state.package_manager.export_winget_configuration().await?;
+
+    std::fs::write(&path, configuration)
+        .map_err(|e| PackageError::Unknown(format!("Failed to write configuration: {}", e)))
+}
+
+/// Apply a winget configuration file, reporting each unit's outcome
+#[cfg(not(target_os = "macos"))]
+#[tauri::command]
+async fn apply_winget_configuration(
+    path: PathBuf,
+    state: State<'_, AppState>,
+) -> Result<package_manager::ConfigurationResult, PackageError> {
+    state
+        .package_manager
+        .apply_winget_configuration(&path)
+        .await
+}
+
+/// Export installed winget packages with `winget export`, for `winget import`
+#[cfg(not(target_os = "macos"))]
+#[tauri::command]
+async fn export_winget_native(path: PathBuf, state: State<'_, AppState>) -> Result<(), PackageError> {
+    state.package_manager.export_winget_native(&path).await
+}
+
+/// Install the packages in a `winget export` file, reporting each one
+#[cfg(not(target_os = "macos"))]
+#[tauri::command]
+async fn import_winget_native(path: PathBuf, state: State<'_, AppState>) -> Result<Vec<InstallResult>, PackageError> {
+    state.package_manager.import_winget_native(&path).await
+}
+
+/// Reinstall every package listed in a JSON manifest file
+#[tauri::command]
+async fn import_packages(path: PathBuf, state: State<'_, AppState>) -> Result<Vec<InstallResult>, PackageError> {
+    let manifest = std::fs::read_to_string(&path)
+        .map_err(|e| PackageError::Unknown(format!("Failed to read manifest: {}", e)))?;
+
+    state
+        .package_manager
+        .import_manifest(&manifest)
+        .await
+}
+
+/// Whether SAVVY is running with administrator rights
+#[tauri::command]
+fn is_elevated() -> bool {
+    package_manager::util::is_elevated()
+}
+
+/// Limit how many batch installs and downloads run at once; 1 makes them serial
+#[tauri::command]
+fn set_max_concurrency(
+    max_operations: Option<usize>,
+    max_downloads: Option<usize>,
+    state: State<'_, AppState>,
+) {
+    if let Some(max) = max_operations {
+        state.package_manager.set_max_concurrency(max);
+    }
+    if let Some(max) = max_downloads {
+        data_cache::set_download_concurrency(max);
+    }
+}
+
+/// Whether Windows needs a restart to finish a previous install
+#[tauri::command]
+fn reboot_pending() -> bool {
+    package_manager::util::reboot_pending()
+}
+
+/// Completed package operations, newest first
+#[tauri::command]
+fn get_history(limit: Option<usize>) -> Result<Vec<HistoryEntry>, String> {
+    history::read(limit)
+}
+
+/// Delete the operation history
+#[tauri::command]
+fn clear_history() -> Result<(), String> {
+    history::clear()
+}
+
+/// Re-detect installed package managers (e.g. after installing winget or choco)
+#[tauri::command]
+async fn refresh_availability(state: State<'_, AppState>) -> Result<(), PackageError> {
+    state.package_manager.refresh_availability().await;
+    Ok(())
+}
+
+/// Install Chocolatey via the official script in an elevated PowerShell.
+///
+/// Runs `Set-ExecutionPolicy Bypass -Scope Process -Force; iex ((New-Object
+/// System.Net.WebClient).DownloadString('https://community.chocolatey.org/install.ps1'))`
+/// after a UAC prompt. `confirmed` must be set by an explicit user action.
+#[tauri::command]
+async fn bootstrap_chocolatey(confirmed: bool, state: State<'_, AppState>) -> Result<InstallResult, PackageError> {
+    require_confirmation(confirmed)?;
+    state.package_manager.bootstrap(PackageSource::Chocolatey).await
+}
+
+/// Install winget by registering App Installer, downloading it from
+/// https://aka.ms/getwinget if needed. `confirmed` must be set by an explicit
+/// user action.
+#[tauri::command]
+async fn bootstrap_winget(confirmed: bool, state: State<'_, AppState>) -> Result<InstallResult, PackageError> {
+    require_confirmation(confirmed)?;
+    state.package_manager.bootstrap(PackageSource::Winget).await
+}
+
+/// Refuse to bootstrap a package manager unless the user asked for it
+fn require_confirmation(confirmed: bool) -> Result<(), PackageError> {
+    if confirmed {
+        Ok(())
+    } else {
+        Err(PackageError::InvalidInput(
+            "Installing a package manager requires explicit confirmation".to_string(),
+        ))
+    }
+}
+
+/// Semantic search using the Python daemon, falling back to a one-shot Python
+/// process and then the offline Rust search. The response carries the total
+/// match count so the UI can show "20 of 340", and is marked `superseded` when
+/// a newer search in the same `session` replaced it.
+#[tauri::command]
+async fn semantic_search(
+    request: SearchRequest,
+    state: State<'_, AppState>,
+) -> Result<SearchResponse, String> {
+    search_service::search(&state.search_daemon, &state.embedding_store, &state.search_sessions, request).await
+}
+
+/// Instant fuzzy name search over the cached package indexes
+#[tauri::command]
+async fn fuzzy_search(
+    query: String,
+    source: Option<String>,
+    limit: Option<usize>,
+    state: State<'_, AppState>,
+) -> Result<Vec<SearchResult>, String> {
+    search_service::fuzzy_search(
+        &state.embedding_store,
+        &query,
+        source.as_deref(),
+        limit.unwrap_or(search_service::DEFAULT_LIMIT),
+    )
+    .await
+}
+
+/// Package id and title completions for what's been typed so far
+#[tauri::command]
+async fn suggest(
+    prefix: String,
+    source: Option<String>,
+    limit: Option<usize>,
+    state: State<'_, AppState>,
+) -> Result<Vec<Suggestion>, String> {
+    search_service::suggest(
+        &state.embedding_store,
+        &prefix,
+        source.as_deref(),
+        limit.unwrap_or(search_service::DEFAULT_SUGGEST_LIMIT),
+    )
+    .await
+}
+
+/// Download missing or stale data files, or every file when `force` is set
+#[tauri::command]
+async fn download_cache_data(
+    app: tauri::AppHandle,
+    force: bool,
+    state: State<'_, AppState>,
+) -> Result<DownloadReport, String> {
+    let report = data_cache::download_all_data(&app, force).await?;
+    state.embedding_store.reload();
+    Ok(report)
+}
+
+/// Check if cache is valid, optionally with a custom max age in seconds
+#[tauri::command]
+fn is_cache_valid(ttl_secs: Option<u64>) -> bool {
+    data_cache::is_cache_valid(resolve_cache_ttl(ttl_secs))
+}
+
+/// Report which cache files are missing or stale
+#[tauri::command]
+fn cache_status(ttl_secs: Option<u64>) -> CacheStatus {
+    data_cache::cache_status(resolve_cache_ttl(ttl_secs))
+}
+
+/// Use the caller's TTL if given, otherwise the configured default
+fn resolve_cache_ttl(ttl_secs: Option<u64>) -> Duration {
+    ttl_secs.map(Duration::from_secs).unwrap_or_else(data_cache::cache_ttl)
+}
+
+/// Delete all cached index and embedding files, returning the removed names
+#[tauri::command]
+fn clear_cache(state: State<'_, AppState>) -> Result<Vec<String>, String> {
+    let removed = data_cache::clear_cache()?;
+    state.embedding_store.reload();
+    Ok(removed)
+}
+
+/// Delete one cached index or embedding file, returning the removed names
+#[tauri::command]
+fn delete_cache_file(
+    state: State<'_, AppState>,
+    file_name: String,
+) -> Result<Vec<String>, String> {
+    let removed = data_cache::delete_cache_file(&file_name)?;
+    state.embedding_store.reload();
+    Ok(removed)
+}
+
+/// Report cache disk usage and file ages
+#[tauri::command]
+fn cache_info() -> Result<CacheInfo, String> {
+    data_cache::cache_info()
 }
 
 /// Get cache directory path
@@ -95,23 +819,167 @@ fn get_cache_dir() -> Result<PathBuf, String> {
     data_cache::get_cache_dir()
 }
 
+/// Open the cache directory in the system file manager (Explorer on Windows),
+/// creating it first if it doesn't exist
+#[tauri::command]
+fn reveal_cache_dir(app: tauri::AppHandle) -> Result<PathBuf, String> {
+    let cache_dir = data_cache::get_cache_dir()?;
+    app.opener()
+        .open_path(cache_dir.display().to_string(), None::<&str>)
+        .map_err(|e| format!("Failed to open {} in a file manager: {}", cache_dir.display(), e))?;
+    Ok(cache_dir)
+}
+
+/// Get the current log file path, for attaching to bug reports
+#[tauri::command]
+fn get_log_path() -> Result<PathBuf, String> {
+    logging::get_log_path()
+}
+
+/// Report managers, Python, cache and elevation status in one payload for bug reports
+#[tauri::command]
+async fn health_check(state: State<'_, AppState>) -> Result<HealthReport, String> {
+    Ok(health::health_check(&state.package_manager).await)
+}
+
+/// Download missing or stale cache files now and then every `interval`,
+/// telling the frontend whenever the index was updated
+async fn refresh_cache_periodically(app: tauri::AppHandle, embedding_store: Arc<EmbeddingStore>, interval: Duration) {
+    let mut ticks = tokio::time::interval(interval);
+    ticks.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+    loop {
+        ticks.tick().await;
+        match data_cache::download_all_data(&app, false).await {
+            Ok(report) if report.succeeded.is_empty() => {}
+            Ok(report) => {
+                tracing::info!("Background refresh updated {} cache files", report.succeeded.len());
+                embedding_store.reload();
+                if let Err(e) = app.emit(CACHE_REFRESHED_EVENT, &report) {
+                    tracing::warn!("Failed to emit cache refresh: {}", e);
+                }
+            }
+            Err(e) => tracing::warn!("Background cache refresh failed: {}", e),
+        }
+    }
+}
+
 fn main() {
+    let _log_guard = logging::init();
+
     let package_manager = Arc::new(PackageManager::new());
+    let embedding_store = Arc::new(EmbeddingStore::new());
+    let search_daemon = Arc::new(PythonSearchDaemon::new());
+    let search_sessions = Arc::new(SearchSessions::new());
 
     tauri::Builder::default()
         .plugin(tauri_plugin_http::init())
+        .plugin(tauri_plugin_opener::init())
+        .setup({
+            let package_manager = package_manager.clone();
+            let embedding_store = embedding_store.clone();
+            move |app| {
+                let handle = app.handle().clone();
+                package_manager.set_status_callback(Arc::new(move |status| {
+                    if let Err(e) = handle.emit(OPERATION_STATUS_EVENT, status) {
+                        tracing::warn!("Failed to emit operation status: {}", e);
+                    }
+                }));
+
+                match data_cache::refresh_interval() {
+                    Some(interval) => {
+                        tauri::async_runtime::spawn(refresh_cache_periodically(
+                            app.handle().clone(),
+                            embedding_store,
+                            interval,
+                        ));
+                    }
+                    None => tracing::info!("Background cache refresh disabled"),
+                }
+                Ok(())
+            }
+        })
         .manage(AppState {
             package_manager,
+            embedding_store,
+            search_daemon,
+            search_sessions,
         })
         .invoke_handler(tauri::generate_handler![
             install_package,
+            install_version,
+            install_with_args,
+            install_with_options,
+            install_batch,
+            reinstall_package,
+            install_local,
+            get_history,
+            clear_history,
+            preview_install,
+            preview_upgrade,
+            pin_package,
+            unpin_package,
+            list_pinned,
             uninstall_package,
+            uninstall_purge,
             upgrade_package,
+            downgrade_package,
+            rollback_last_upgrade,
+            repair_package,
+            verify_package,
+            is_installed,
+            #[cfg(not(target_os = "macos"))]
+            download_package,
+            upgrade_all,
             list_installed_packages,
+            list_installed_filtered,
+            list_installed_paged,
+            refresh_installed,
+            count_installed,
+            count_installed_all,
+            list_upgradable,
+            check_for_updates,
+            search_winget,
+            search_chocolatey,
+            search_homebrew,
+            list_sources,
+            add_source,
+            remove_source,
+            enable_source,
+            disable_source,
+            get_package_details,
+            list_versions,
+            export_packages,
+            export_script,
+            #[cfg(not(target_os = "macos"))]
+            export_winget_configuration,
+            #[cfg(not(target_os = "macos"))]
+            apply_winget_configuration,
+            #[cfg(not(target_os = "macos"))]
+            export_winget_native,
+            #[cfg(not(target_os = "macos"))]
+            import_winget_native,
+            import_packages,
+            refresh_availability,
+            available_sources,
+            is_elevated,
+            reboot_pending,
+            set_max_concurrency,
+            bootstrap_chocolatey,
+            bootstrap_winget,
             semantic_search,
+            fuzzy_search,
+            suggest,
             download_cache_data,
             is_cache_valid,
+            cache_status,
+            clear_cache,
+            delete_cache_file,
+            cache_info,
             get_cache_dir,
+            reveal_cache_dir,
+            get_log_path,
+            health_check,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
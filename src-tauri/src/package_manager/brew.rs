@@ -0,0 +1,958 @@
+use super::types::*;
+use super::{exit_code_error, validate_package_id, validate_search_query};
+use super::status::StatusSink;
+use super::util::{clean_output, elapsed_ms, output_with_lines};
+use serde_json::Value;
+use std::path::Path;
+use std::time::Instant;
+use tokio::sync::OnceCell;
+use tokio::process::Command as TokioCommand;
+
+/// Where the Homebrew installer puts `brew` (Apple Silicon, then Intel).
+/// Apps launched from Finder don't inherit the shell's PATH, so these are
+/// checked before falling back to a bare `brew`.
+const DEFAULT_BREW_PATHS: &[&str] = &["/opt/homebrew/bin/brew", "/usr/local/bin/brew"];
+
+/// Homebrew package manager wrapper (macOS)
+pub struct HomebrewManager {
+    exe_path: String,
+    /// Detected Homebrew version, probed once on first use (`None` if not installed)
+    availability: OnceCell<Option<String>>,
+    status: StatusSink,
+}
+
+impl HomebrewManager {
+    pub fn new() -> Self {
+        let path = DEFAULT_BREW_PATHS
+            .iter()
+            .find(|path| Path::new(path).is_file())
+            .copied()
+            .unwrap_or("brew");
+        Self::with_path(path)
+    }
+
+    /// Use a specific `brew` executable instead of the default locations
+    pub fn with_path(path: impl Into<String>) -> Self {
+        Self {
+            exe_path: path.into(),
+            availability: OnceCell::new(),
+            status: StatusSink::default(),
+        }
+    }
+
+    /// Report operation progress through `status`
+    pub fn with_status(mut self, status: StatusSink) -> Self {
+        self.status = status;
+        self
+    }
+
+    /// Check if Homebrew is installed
+    pub async fn is_installed(&self) -> bool {
+        self.version().await.is_some()
+    }
+
+    /// Get the installed Homebrew version, probing only on first call
+    pub async fn version(&self) -> Option<String> {
+        self.availability
+            .get_or_init(|| async {
+                let output = TokioCommand::new(&self.exe_path)
+                    .arg("--version")
+                    .output()
+                    .await
+                    .ok()?;
+
+                // First line is "Homebrew 4.2.0"
+                let stdout = clean_output(&output.stdout);
+                let version = stdout
+                    .lines()
+                    .next()
+                    .map(|line| line.trim().trim_start_matches("Homebrew").trim().to_string())
+                    .filter(|version| !version.is_empty())
+                    .unwrap_or_else(|| "unknown".to_string());
+                Some(version)
+            })
+            .await
+            .clone()
+    }
+
+    /// Forget the cached availability, e.g. after Homebrew was installed mid-session
+    pub fn refresh_availability(&mut self) {
+        self.availability = OnceCell::new();
+    }
+
+    /// Homebrew's installer needs an interactive terminal and sudo, so it
+    /// can't be bootstrapped from the app; this only reports whether it's there.
+    pub async fn bootstrap(&mut self) -> Result<InstallResult, PackageError> {
+        if !self.is_installed().await {
+            return Err(PackageError::InvalidInput(
+                "Homebrew must be installed from a terminal; see https://brew.sh".to_string(),
+            ));
+        }
+
+        Ok(InstallResult {
+            success: true,
+            package_id: "homebrew".to_string(),
+            version: self.version().await,
+            output: "Homebrew is already installed".to_string(),
+            error: None,
+            extra_args: Vec::new(),
+            duration_ms: 0,
+            forced: false,
+        })
+    }
+
+    /// Install a formula or cask, with `extra_args` appended. Homebrew only
+    /// offers the current version of each formula, so a specific version
+    /// can't be requested.
+    pub async fn install(&self, package_id: &str, options: &InstallOptions) -> Result<InstallResult, PackageError> {
+        validate_package_id(package_id)?;
+
+        if !self.is_installed().await {
+            return Err(PackageError::NotFound(
+                "Homebrew is not installed".to_string(),
+            ));
+        }
+
+        if options.catalog.is_some() {
+            return Err(PackageError::InvalidInput(
+                "Homebrew installs from taps by name; use a tap-qualified name like user/tap/formula".to_string(),
+            ));
+        }
+
+        if options.version.is_some() {
+            return Err(PackageError::InvalidInput(format!(
+                "Homebrew can't install a specific version of {}; install a versioned formula (e.g. {}@<version>) instead",
+                package_id, package_id
+            )));
+        }
+
+        let started = Instant::now();
+        let mut tracker = self.status.track("install", package_id);
+        let output = output_with_lines(
+            TokioCommand::new(&self.exe_path)
+                .args([if options.force { "reinstall" } else { "install" }, package_id])
+                .args(&options.extra_args),
+            |line| tracker.line(line),
+        )
+        .await
+        .map_err(|e| PackageError::CommandFailed(e.to_string()))?;
+        tracker.finish(output.status.success());
+
+        let stdout = clean_output(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+        let success = output.status.success();
+
+        // brew exits 0 and only warns when the package is already there
+        if stderr.contains("is already installed and up-to-date") {
+            return Err(PackageError::AlreadyInstalled(package_id.to_string()));
+        }
+        if stderr.contains("No available formula or cask") {
+            return Err(PackageError::NotFound(package_id.to_string()));
+        }
+
+        if let Some(error) = exit_code_error(&output.status, &stderr, package_id) {
+            return Err(error);
+        }
+
+        Ok(InstallResult {
+            success,
+            package_id: package_id.to_string(),
+            version: self.installed_version(package_id).await.ok().flatten(),
+            output: stdout,
+            error: if success { None } else { Some(stderr) },
+            extra_args: options.extra_args.clone(),
+            duration_ms: elapsed_ms(started),
+            forced: options.force,
+        })
+    }
+
+    /// Homebrew formulae are installed by name from a tap, not from local files
+    pub async fn install_from_file(&self, path: &Path) -> Result<InstallResult, PackageError> {
+        Err(PackageError::InvalidInput(format!(
+            "Homebrew can't install from a local file ({}); add a tap instead",
+            path.display()
+        )))
+    }
+
+    /// Preview an install from `brew info`
+    pub async fn preview_install(&self, package_id: &str) -> Result<PackagePreview, PackageError> {
+        validate_package_id(package_id)?;
+
+        if let Some(current) = self.installed_version(package_id).await? {
+            return Ok(PackagePreview {
+                package_id: package_id.to_string(),
+                action: PreviewAction::AlreadyInstalled,
+                from_version: Some(current.clone()),
+                to_version: Some(current),
+            });
+        }
+
+        let details = self.info(package_id).await?;
+
+        Ok(PackagePreview {
+            package_id: package_id.to_string(),
+            action: PreviewAction::Install,
+            from_version: None,
+            to_version: details.version,
+        })
+    }
+
+    /// Preview an upgrade from the versions `brew outdated` reports
+    pub async fn preview_upgrade(&self, package_id: &str) -> Result<PackagePreview, PackageError> {
+        validate_package_id(package_id)?;
+
+        let current = self
+            .installed_version(package_id)
+            .await?
+            .ok_or_else(|| PackageError::NotInstalled(package_id.to_string()))?;
+
+        let available = self
+            .list_upgradable()
+            .await?
+            .into_iter()
+            .find(|p| p.id.eq_ignore_ascii_case(package_id))
+            .map(|p| p.available_version);
+
+        Ok(PackagePreview {
+            package_id: package_id.to_string(),
+            action: if available.is_some() { PreviewAction::Upgrade } else { PreviewAction::UpToDate },
+            to_version: available.or_else(|| Some(current.clone())),
+            from_version: Some(current),
+        })
+    }
+
+    /// Installed version of a package, if it's installed
+    async fn installed_version(&self, package_id: &str) -> Result<Option<String>, PackageError> {
+        Ok(self
+            .list_installed()
+            .await?
+            .into_iter()
+            .find(|p| p.id.eq_ignore_ascii_case(package_id))
+            .map(|p| p.version))
+    }
+
+    /// Uninstall a formula or cask
+    ///
+    /// With `purge`, casks are uninstalled with `--zap`, which also deletes the
+    /// app's preferences, caches and support files. Formulae have no equivalent.
+    pub async fn uninstall(&self, package_id: &str, purge: bool) -> Result<UninstallResult, PackageError> {
+        validate_package_id(package_id)?;
+
+        if !self.is_installed().await {
+            return Err(PackageError::NotFound(
+                "Homebrew is not installed".to_string(),
+            ));
+        }
+
+        let mut args = vec!["uninstall", package_id];
+        if purge {
+            args.push("--zap");
+        }
+
+        let started = Instant::now();
+        let mut tracker = self.status.track("uninstall", package_id);
+        let output = output_with_lines(TokioCommand::new(&self.exe_path).args(&args), |line| {
+            tracker.line(line)
+        })
+        .await
+        .map_err(|e| PackageError::CommandFailed(e.to_string()))?;
+        tracker.finish(output.status.success());
+
+        let stdout = clean_output(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+        let success = output.status.success();
+
+        if stderr.contains("No such keg") || stderr.contains("is not installed") {
+            return Err(PackageError::NotInstalled(package_id.to_string()));
+        }
+
+        if let Some(error) = exit_code_error(&output.status, &stderr, package_id) {
+            return Err(error);
+        }
+
+        let removed = if purge { Self::parse_zapped(&stdout) } else { Vec::new() };
+
+        Ok(UninstallResult {
+            success,
+            package_id: package_id.to_string(),
+            output: stdout,
+            error: if success { None } else { Some(stderr) },
+            removed,
+            duration_ms: elapsed_ms(started),
+        })
+    }
+
+    /// Paths listed under `--zap`'s "==> Removing files:" and
+    /// "==> Trashing files:" headings
+    fn parse_zapped(output: &str) -> Vec<String> {
+        let mut in_file_list = false;
+        let mut removed = Vec::new();
+
+        for line in output.lines() {
+            let line = line.trim();
+            if line.starts_with("==>") {
+                in_file_list = line.contains("Removing files") || line.contains("Trashing files");
+            } else if in_file_list && (line.starts_with('/') || line.starts_with('~')) {
+                removed.push(line.to_string());
+            }
+        }
+
+        removed
+    }
+
+    /// Repair a formula or cask with `brew reinstall`
+    pub async fn repair(&self, package_id: &str) -> Result<RepairResult, PackageError> {
+        validate_package_id(package_id)?;
+
+        if !self.is_installed().await {
+            return Err(PackageError::NotFound(
+                "Homebrew is not installed".to_string(),
+            ));
+        }
+
+        let mut tracker = self.status.track("repair", package_id);
+        let output = output_with_lines(TokioCommand::new(&self.exe_path).args(["reinstall", package_id]), |line| {
+            tracker.line(line)
+        })
+        .await
+        .map_err(|e| PackageError::CommandFailed(e.to_string()))?;
+        tracker.finish(output.status.success());
+
+        let stdout = clean_output(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+        let success = output.status.success();
+
+        if stderr.contains("is not installed") {
+            return Err(PackageError::NotInstalled(package_id.to_string()));
+        }
+
+        if let Some(error) = exit_code_error(&output.status, &stderr, package_id) {
+            return Err(error);
+        }
+
+        Ok(RepairResult {
+            success,
+            package_id: package_id.to_string(),
+            version: self.installed_version(package_id).await.ok().flatten(),
+            output: stdout,
+            error: if success { None } else { Some(stderr) },
+        })
+    }
+
+    /// List installed formulae and casks
+    pub async fn list_installed(&self) -> Result<Vec<InstalledPackage>, PackageError> {
+        if !self.is_installed().await {
+            return Err(PackageError::NotFound(
+                "Homebrew is not installed".to_string(),
+            ));
+        }
+
+        let output = TokioCommand::new(&self.exe_path)
+            .args(["list", "--versions"])
+            .output()
+            .await
+            .map_err(|e| PackageError::CommandFailed(e.to_string()))?;
+
+        if !output.status.success() {
+            return Err(PackageError::CommandFailed(
+                String::from_utf8_lossy(&output.stderr).to_string(),
+            ));
+        }
+
+        let stdout = clean_output(&output.stdout);
+        Ok(Self::parse_versions_output(&stdout)
+            .into_iter()
+            .map(|(id, version)| InstalledPackage {
+                name: Some(id.clone()),
+                id,
+                version: version.unwrap_or_else(|| "unknown".to_string()),
+                source: PackageSource::Homebrew,
+            })
+            .collect())
+    }
+
+    /// Parse `brew list --versions` lines ("name 1.0 1.1"). When several
+    /// versions are kept side by side the last one listed is the newest.
+    fn parse_versions_output(output: &str) -> Vec<(String, Option<String>)> {
+        output
+            .lines()
+            .filter_map(|line| {
+                let mut fields = line.split_whitespace();
+                let id = fields.next()?.to_string();
+                Some((id, fields.last().map(str::to_string)))
+            })
+            .collect()
+    }
+
+    /// Upgrade a formula or cask
+    pub async fn upgrade(&self, package_id: &str) -> Result<UpgradeResult, PackageError> {
+        validate_package_id(package_id)?;
+
+        if !self.is_installed().await {
+            return Err(PackageError::NotFound(
+                "Homebrew is not installed".to_string(),
+            ));
+        }
+
+        let old_version = self.installed_version(package_id).await?;
+
+        let started = Instant::now();
+        let mut tracker = self.status.track("upgrade", package_id);
+        let output = output_with_lines(TokioCommand::new(&self.exe_path).args(["upgrade", package_id]), |line| {
+            tracker.line(line)
+        })
+        .await
+        .map_err(|e| PackageError::CommandFailed(e.to_string()))?;
+        tracker.finish(output.status.success());
+
+        let stdout = clean_output(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+        let success = output.status.success();
+
+        if stderr.contains("not installed") {
+            return Err(PackageError::NotInstalled(package_id.to_string()));
+        }
+
+        if let Some(error) = exit_code_error(&output.status, &stderr, package_id) {
+            return Err(error);
+        }
+
+        Ok(UpgradeResult {
+            success,
+            package_id: package_id.to_string(),
+            new_version: self.installed_version(package_id).await.ok().flatten(),
+            old_version,
+            output: stdout,
+            error: if success { None } else { Some(stderr) },
+            duration_ms: elapsed_ms(started),
+        })
+    }
+
+    /// Homebrew can't install an older version of a formula
+    pub async fn downgrade(&self, package_id: &str, version: &str) -> Result<UpgradeResult, PackageError> {
+        validate_package_id(package_id)?;
+
+        Err(PackageError::InvalidInput(format!(
+            "Homebrew can't downgrade {} to {}; only the current version is available",
+            package_id, version
+        )))
+    }
+
+    /// Upgrade every outdated formula and cask.
+    ///
+    /// Returns one result per package whose installed version changed.
+    /// Pinned formulae are skipped by `brew upgrade` itself.
+    pub async fn upgrade_all(&self) -> Result<Vec<UpgradeResult>, PackageError> {
+        if !self.is_installed().await {
+            return Err(PackageError::NotFound(
+                "Homebrew is not installed".to_string(),
+            ));
+        }
+
+        let started = Instant::now();
+        let before = self.list_installed().await?;
+
+        let mut tracker = self.status.track("upgrade", "all");
+        let output = output_with_lines(TokioCommand::new(&self.exe_path).arg("upgrade"), |line| tracker.line(line))
+            .await
+            .map_err(|e| PackageError::CommandFailed(e.to_string()))?;
+        tracker.finish(output.status.success());
+
+        let stdout = clean_output(&output.stdout);
+        let after = self.list_installed().await?;
+        let results = super::collect_version_changes(&before, &after, &stdout, elapsed_ms(started));
+
+        if results.is_empty() && !output.status.success() {
+            return Err(PackageError::CommandFailed(
+                String::from_utf8_lossy(&output.stderr).to_string(),
+            ));
+        }
+
+        Ok(results)
+    }
+
+    /// List installed packages that have an update available
+    pub async fn list_upgradable(&self) -> Result<Vec<UpgradablePackage>, PackageError> {
+        if !self.is_installed().await {
+            return Err(PackageError::NotFound(
+                "Homebrew is not installed".to_string(),
+            ));
+        }
+
+        let output = TokioCommand::new(&self.exe_path)
+            .args(["outdated", "--json=v2"])
+            .output()
+            .await
+            .map_err(|e| PackageError::CommandFailed(e.to_string()))?;
+
+        if !output.status.success() {
+            return Err(PackageError::CommandFailed(
+                String::from_utf8_lossy(&output.stderr).to_string(),
+            ));
+        }
+
+        let json: Value = serde_json::from_slice(&output.stdout)
+            .map_err(|e| PackageError::CommandFailed(format!("Unexpected brew outdated output: {}", e)))?;
+        Ok(Self::parse_outdated_json(&json))
+    }
+
+    /// Parse `brew outdated --json=v2`, which lists formulae and casks separately
+    fn parse_outdated_json(json: &Value) -> Vec<UpgradablePackage> {
+        ["formulae", "casks"]
+            .iter()
+            .filter_map(|kind| json[kind].as_array())
+            .flatten()
+            .filter_map(|entry| {
+                let id = entry["name"].as_str()?;
+                let current_version = entry["installed_versions"]
+                    .as_array()
+                    .and_then(|versions| versions.last())
+                    .and_then(Value::as_str)
+                    .or_else(|| entry["installed_versions"].as_str())?;
+                let available_version = entry["current_version"].as_str()?;
+
+                Some(UpgradablePackage {
+                    id: id.to_string(),
+                    current_version: current_version.to_string(),
+                    available_version: available_version.to_string(),
+                    source: PackageSource::Homebrew,
+                })
+            })
+            .collect()
+    }
+
+    /// Search formulae and casks by name
+    pub async fn search(&self, query: &str, catalog: Option<&str>) -> Result<Vec<AvailablePackage>, PackageError> {
+        validate_search_query(query)?;
+
+        if !self.is_installed().await {
+            return Err(PackageError::NotFound(
+                "Homebrew is not installed".to_string(),
+            ));
+        }
+
+        if catalog.is_some() {
+            return Err(PackageError::InvalidInput(
+                "Homebrew searches every tap at once; it can't be limited to one".to_string(),
+            ));
+        }
+
+        let output = TokioCommand::new(&self.exe_path)
+            .args(["search", query])
+            .output()
+            .await
+            .map_err(|e| PackageError::CommandFailed(e.to_string()))?;
+
+        let stdout = clean_output(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+
+        // brew exits non-zero when nothing matches; that's an empty result, not an error
+        if stderr.contains("No formulae or casks found") {
+            return Ok(Vec::new());
+        }
+
+        if !output.status.success() {
+            return Err(PackageError::CommandFailed(stderr));
+        }
+
+        Ok(Self::parse_search_output(&stdout))
+    }
+
+    /// Parse `brew search` output: names under "==> Formulae" and "==> Casks"
+    /// headings. Search doesn't report versions.
+    fn parse_search_output(output: &str) -> Vec<AvailablePackage> {
+        let mut catalog = None;
+        let mut packages = Vec::new();
+
+        for line in output.lines().map(str::trim).filter(|line| !line.is_empty()) {
+            if let Some(heading) = line.strip_prefix("==>") {
+                catalog = Some(heading.trim().to_lowercase());
+                continue;
+            }
+
+            // Installed entries are marked with a trailing check mark
+            let id = line.trim_end_matches('✔').trim();
+            packages.push(AvailablePackage {
+                id: id.to_string(),
+                name: None,
+                version: String::new(),
+                source: PackageSource::Homebrew,
+                catalog: catalog.clone(),
+            });
+        }
+
+        packages
+    }
+
+    /// Show details for a formula or cask
+    pub async fn info(&self, package_id: &str) -> Result<PackageDetails, PackageError> {
+        validate_package_id(package_id)?;
+
+        if !self.is_installed().await {
+            return Err(PackageError::NotFound(
+                "Homebrew is not installed".to_string(),
+            ));
+        }
+
+        let output = TokioCommand::new(&self.exe_path)
+            .args(["info", "--json=v2", package_id])
+            .output()
+            .await
+            .map_err(|e| PackageError::CommandFailed(e.to_string()))?;
+
+        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+
+        if stderr.contains("No available formula or cask") {
+            return Err(PackageError::NotFound(package_id.to_string()));
+        }
+
+        if !output.status.success() {
+            return Err(PackageError::CommandFailed(stderr));
+        }
+
+        let json: Value = serde_json::from_slice(&output.stdout)
+            .map_err(|e| PackageError::CommandFailed(format!("Unexpected brew info output: {}", e)))?;
+        Self::parse_info_json(package_id, &json).ok_or_else(|| PackageError::NotFound(package_id.to_string()))
+    }
+
+    /// Homebrew only offers the current version of a formula or cask
+    pub async fn available_versions(&self, package_id: &str) -> Result<Vec<String>, PackageError> {
+        validate_package_id(package_id)?;
+
+        let details = self.info(package_id).await?;
+        Ok(details.version.into_iter().collect())
+    }
+
+    /// Parse `brew info --json=v2`. Formulae and casks name their fields differently.
+    fn parse_info_json(package_id: &str, json: &Value) -> Option<PackageDetails> {
+        let text = |value: &Value| value.as_str().filter(|s| !s.is_empty()).map(str::to_string);
+
+        if let Some(formula) = json["formulae"].as_array().and_then(|f| f.first()) {
+            return Some(PackageDetails {
+                id: package_id.to_string(),
+                name: text(&formula["full_name"]).or_else(|| text(&formula["name"])),
+                version: text(&formula["versions"]["stable"]),
+                description: text(&formula["desc"]),
+                publisher: text(&formula["tap"]),
+                homepage: text(&formula["homepage"]),
+                license: text(&formula["license"]),
+            });
+        }
+
+        let cask = json["casks"].as_array()?.first()?;
+        Some(PackageDetails {
+            id: package_id.to_string(),
+            name: cask["name"].as_array().and_then(|names| names.first()).and_then(text),
+            version: text(&cask["version"]),
+            description: text(&cask["desc"]),
+            publisher: text(&cask["tap"]),
+            homepage: text(&cask["homepage"]),
+            license: None,
+        })
+    }
+
+    /// List tapped repositories, Homebrew's equivalent of sources
+    pub async fn list_sources(&self) -> Result<Vec<Source>, PackageError> {
+        if !self.is_installed().await {
+            return Err(PackageError::NotFound(
+                "Homebrew is not installed".to_string(),
+            ));
+        }
+
+        let output = TokioCommand::new(&self.exe_path)
+            .arg("tap")
+            .output()
+            .await
+            .map_err(|e| PackageError::CommandFailed(e.to_string()))?;
+
+        if !output.status.success() {
+            return Err(PackageError::CommandFailed(
+                String::from_utf8_lossy(&output.stderr).to_string(),
+            ));
+        }
+
+        // `brew tap` only prints "user/repo" names
+        let stdout = clean_output(&output.stdout);
+        Ok(stdout
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(|name| Source {
+                name: name.to_string(),
+                url: String::new(),
+                source_type: Some("tap".to_string()),
+                disabled: false,
+            })
+            .collect())
+    }
+
+    /// Tap a repository (`brew tap <user/repo> <url>`)
+    pub async fn add_source(&self, name: &str, url: &str) -> Result<(), PackageError> {
+        self.run_tap_command(&["tap", name, url], name).await
+    }
+
+    /// Untap a repository
+    pub async fn remove_source(&self, name: &str) -> Result<(), PackageError> {
+        self.run_tap_command(&["untap", name], name).await
+    }
+
+    async fn run_tap_command(&self, args: &[&str], name: &str) -> Result<(), PackageError> {
+        if !self.is_installed().await {
+            return Err(PackageError::NotFound(
+                "Homebrew is not installed".to_string(),
+            ));
+        }
+
+        let output = TokioCommand::new(&self.exe_path)
+            .args(args)
+            .output()
+            .await
+            .map_err(|e| PackageError::CommandFailed(e.to_string()))?;
+
+        let stdout = clean_output(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+
+        if stderr.contains("No available tap") {
+            return Err(PackageError::NotFound(format!("tap {}", name)));
+        }
+        if let Some(error) = exit_code_error(&output.status, &stderr, name) {
+            return Err(error);
+        }
+        if !output.status.success() {
+            return Err(PackageError::CommandFailed(if stderr.trim().is_empty() { stdout } else { stderr }));
+        }
+
+        Ok(())
+    }
+
+    /// Pin a formula so `brew upgrade` leaves it alone
+    pub async fn pin(&self, package_id: &str) -> Result<(), PackageError> {
+        self.run_pin("pin", package_id).await
+    }
+
+    /// Remove a formula's pin
+    pub async fn unpin(&self, package_id: &str) -> Result<(), PackageError> {
+        self.run_pin("unpin", package_id).await
+    }
+
+    async fn run_pin(&self, command: &str, package_id: &str) -> Result<(), PackageError> {
+        validate_package_id(package_id)?;
+
+        if !self.is_installed().await {
+            return Err(PackageError::NotFound(
+                "Homebrew is not installed".to_string(),
+            ));
+        }
+
+        let output = TokioCommand::new(&self.exe_path)
+            .args([command, package_id])
+            .output()
+            .await
+            .map_err(|e| PackageError::CommandFailed(e.to_string()))?;
+
+        let stdout = clean_output(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+
+        if stderr.contains("not installed") {
+            return Err(PackageError::NotInstalled(package_id.to_string()));
+        }
+        if let Some(error) = exit_code_error(&output.status, &stderr, package_id) {
+            return Err(error);
+        }
+        if !output.status.success() {
+            return Err(PackageError::CommandFailed(if stderr.trim().is_empty() { stdout } else { stderr }));
+        }
+
+        Ok(())
+    }
+
+    /// List pinned formulae (casks can't be pinned)
+    pub async fn list_pinned(&self) -> Result<Vec<PinnedPackage>, PackageError> {
+        if !self.is_installed().await {
+            return Err(PackageError::NotFound(
+                "Homebrew is not installed".to_string(),
+            ));
+        }
+
+        let output = TokioCommand::new(&self.exe_path)
+            .args(["list", "--pinned", "--versions"])
+            .output()
+            .await
+            .map_err(|e| PackageError::CommandFailed(e.to_string()))?;
+
+        if !output.status.success() {
+            return Err(PackageError::CommandFailed(
+                String::from_utf8_lossy(&output.stderr).to_string(),
+            ));
+        }
+
+        let stdout = clean_output(&output.stdout);
+        Ok(Self::parse_versions_output(&stdout)
+            .into_iter()
+            .map(|(id, version)| PinnedPackage {
+                id,
+                version,
+                source: PackageSource::Homebrew,
+                pin_type: None,
+            })
+            .collect())
+    }
+}
+
+impl Default for HomebrewManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn outdated_lists_formulae_and_casks() {
+        let json = json!({
+            "formulae": [
+                {
+                    "name": "git",
+                    "installed_versions": ["2.42.0", "2.43.0"],
+                    "current_version": "2.44.0",
+                    "pinned": false
+                },
+                { "name": "broken", "installed_versions": [], "current_version": "1.0" }
+            ],
+            "casks": [
+                { "name": "firefox", "installed_versions": "121.0", "current_version": "122.0" }
+            ]
+        });
+
+        let outdated = HomebrewManager::parse_outdated_json(&json);
+        let summary: Vec<(&str, &str, &str)> = outdated
+            .iter()
+            .map(|p| (p.id.as_str(), p.current_version.as_str(), p.available_version.as_str()))
+            .collect();
+        assert_eq!(summary, vec![("git", "2.43.0", "2.44.0"), ("firefox", "121.0", "122.0")]);
+        assert!(HomebrewManager::parse_outdated_json(&json!({})).is_empty());
+    }
+
+    #[test]
+    fn info_reads_formula_fields() {
+        let json = json!({
+            "formulae": [{
+                "name": "wget",
+                "full_name": "wget",
+                "tap": "homebrew/core",
+                "desc": "Internet file retriever",
+                "license": "GPL-3.0-or-later",
+                "homepage": "https://www.gnu.org/software/wget/",
+                "versions": { "stable": "1.21.4", "head": "HEAD" }
+            }],
+            "casks": []
+        });
+
+        let details = HomebrewManager::parse_info_json("wget", &json).unwrap();
+        assert_eq!(details.name.as_deref(), Some("wget"));
+        assert_eq!(details.version.as_deref(), Some("1.21.4"));
+        assert_eq!(details.description.as_deref(), Some("Internet file retriever"));
+        assert_eq!(details.publisher.as_deref(), Some("homebrew/core"));
+        assert_eq!(details.license.as_deref(), Some("GPL-3.0-or-later"));
+    }
+
+    #[test]
+    fn info_reads_cask_fields() {
+        let json = json!({
+            "formulae": [],
+            "casks": [{
+                "token": "visual-studio-code",
+                "name": ["Microsoft Visual Studio Code", "VS Code"],
+                "desc": "Open-source code editor",
+                "homepage": "https://code.visualstudio.com/",
+                "version": "1.85.1",
+                "tap": "homebrew/cask"
+            }]
+        });
+
+        let details = HomebrewManager::parse_info_json("visual-studio-code", &json).unwrap();
+        assert_eq!(details.name.as_deref(), Some("Microsoft Visual Studio Code"));
+        assert_eq!(details.version.as_deref(), Some("1.85.1"));
+        assert_eq!(details.license, None);
+        assert!(HomebrewManager::parse_info_json("nope", &json!({ "formulae": [], "casks": [] })).is_none());
+    }
+
+    #[test]
+    fn search_groups_results_by_heading() {
+        let output = "\
+==> Formulae
+git ✔
+git-lfs
+legit
+
+==> Casks
+gitkraken
+";
+        let results = HomebrewManager::parse_search_output(output);
+        let summary: Vec<(&str, Option<&str>)> = results.iter().map(|p| (p.id.as_str(), p.catalog.as_deref())).collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("git", Some("formulae")),
+                ("git-lfs", Some("formulae")),
+                ("legit", Some("formulae")),
+                ("gitkraken", Some("casks")),
+            ]
+        );
+    }
+
+    #[test]
+    fn zap_reports_removed_and_trashed_paths() {
+        let output = "\
+==> Uninstalling Cask firefox
+==> Removing App '/Applications/Firefox.app'
+==> Removing files:
+/Library/Logs/DiagnosticReports/firefox_*.crash
+~/Library/Application Support/Firefox
+==> Trashing files:
+~/Library/Caches/Firefox
+==> Purging files for version 121.0 of Cask firefox
+";
+        assert_eq!(
+            HomebrewManager::parse_zapped(output),
+            vec![
+                "/Library/Logs/DiagnosticReports/firefox_*.crash",
+                "~/Library/Application Support/Firefox",
+                "~/Library/Caches/Firefox",
+            ]
+        );
+    }
+
+    #[test]
+    fn versions_output_takes_the_newest_kept_version() {
+        let output = "git 2.42.0 2.43.0\nwget 1.21.4\nodd\n\n";
+        assert_eq!(
+            HomebrewManager::parse_versions_output(output),
+            vec![
+                ("git".to_string(), Some("2.43.0".to_string())),
+                ("wget".to_string(), Some("1.21.4".to_string())),
+                ("odd".to_string(), None),
+            ]
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn failing_pinned_list_is_an_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let exe = crate::package_manager::test_support::fake_exe(
+            dir.path(),
+            "brew",
+            r#"case "$1" in
+  --version) echo "Homebrew 4.2.0" ;;
+  list) echo "Error: Permission denied @ dir_s_mkdir - /opt/homebrew/var" >&2; exit 1 ;;
+esac"#,
+        );
+
+        let result = HomebrewManager::with_path(exe).list_pinned().await;
+        assert!(matches!(result, Err(PackageError::CommandFailed(e)) if e.contains("Permission denied")));
+    }
+}
@@ -1,96 +1,446 @@
 use super::types::*;
-use std::process::Command;
+use super::{exit_code_error, validate_local_file, validate_package_id, validate_search_query, version};
+use super::status::{line_fraction, LineProgress, Phase, StatusSink, StatusTracker};
+use super::util::{clean_output, elapsed_ms, output_with_lines, parse_version, probe_version, run_powershell};
+use std::path::Path;
+use std::time::Instant;
+use tokio::sync::OnceCell;
 use tokio::process::Command as TokioCommand;
 
+/// The official Chocolatey install one-liner, from https://chocolatey.org/install
+const INSTALL_SCRIPT: &str = "Set-ExecutionPolicy Bypass -Scope Process -Force; \
+    [System.Net.ServicePointManager]::SecurityProtocol = [System.Net.ServicePointManager]::SecurityProtocol -bor 3072; \
+    iex ((New-Object System.Net.WebClient).DownloadString('https://community.chocolatey.org/install.ps1'))";
+
 /// Chocolatey package manager wrapper
 pub struct ChocolateyManager {
     exe_path: String,
+    /// Detected Chocolatey version, probed once on first use (`None` if not installed)
+    availability: OnceCell<Option<String>>,
+    status: StatusSink,
 }
 
 impl ChocolateyManager {
     pub fn new() -> Self {
+        Self::with_path("choco")
+    }
+
+    /// Use a specific Chocolatey executable instead of the one on PATH
+    pub fn with_path(path: impl Into<String>) -> Self {
         Self {
-            exe_path: "choco".to_string(),
+            exe_path: path.into(),
+            availability: OnceCell::new(),
+            status: StatusSink::default(),
         }
     }
 
+    /// Report operation progress through `status`
+    pub fn with_status(mut self, status: StatusSink) -> Self {
+        self.status = status;
+        self
+    }
+
+    /// Start reporting an operation's progress from choco's output
+    fn track(&self, operation: &str, package_id: &str) -> StatusTracker {
+        self.status.track(operation, package_id).with_parser(parse_progress)
+    }
+
     /// Check if Chocolatey is installed
-    pub fn is_installed(&self) -> bool {
-        Command::new(&self.exe_path)
-            .arg("--version")
-            .output()
-            .is_ok()
+    pub async fn is_installed(&self) -> bool {
+        self.version().await.is_some()
+    }
+
+    /// Get the installed Chocolatey version, probing only on first call
+    pub async fn version(&self) -> Option<String> {
+        self.availability
+            .get_or_init(|| probe_version(&self.exe_path))
+            .await
+            .clone()
+    }
+
+    /// Forget the cached availability, e.g. after Chocolatey was installed mid-session
+    pub fn refresh_availability(&mut self) {
+        self.availability = OnceCell::new();
+    }
+
+    /// Install Chocolatey itself with the official script in an elevated
+    /// PowerShell. Does nothing if it's already installed.
+    ///
+    /// This prompts for elevation and downloads and runs a remote script, so
+    /// it must only be triggered by an explicit user action.
+    pub async fn bootstrap(&mut self) -> Result<InstallResult, PackageError> {
+        if self.is_installed().await {
+            return Ok(InstallResult {
+                success: true,
+                package_id: "chocolatey".to_string(),
+                version: self.version().await,
+                output: "Chocolatey is already installed".to_string(),
+                error: None,
+                extra_args: Vec::new(),
+                duration_ms: 0,
+                forced: false,
+            });
+        }
+
+        let started = Instant::now();
+        let output = run_powershell(INSTALL_SCRIPT, true).await?;
+        let stdout = clean_output(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+
+        // Our PATH predates the install, so look in the default location too
+        if self.exe_path == "choco" {
+            if let Some(exe) = std::env::var_os("ProgramData")
+                .map(|dir| Path::new(&dir).join("chocolatey").join("bin").join("choco.exe"))
+                .filter(|exe| exe.is_file())
+            {
+                self.exe_path = exe.to_string_lossy().to_string();
+            }
+        }
+        self.refresh_availability();
+
+        let version = self.version().await;
+        let success = output.status.success() && version.is_some();
+
+        Ok(InstallResult {
+            success,
+            package_id: "chocolatey".to_string(),
+            version,
+            output: stdout,
+            error: if success { None } else { Some(stderr) },
+            extra_args: Vec::new(),
+            duration_ms: elapsed_ms(started),
+            forced: false,
+        })
     }
 
-    /// Install a package
-    pub async fn install(&self, package_id: &str) -> Result<InstallResult, PackageError> {
-        if !self.is_installed() {
+    /// Install a package, optionally pinned to a specific version, with
+    /// `extra_args` appended after the standard arguments
+    pub async fn install(&self, package_id: &str, options: &InstallOptions) -> Result<InstallResult, PackageError> {
+        validate_package_id(package_id)?;
+
+        if !self.is_installed().await {
             return Err(PackageError::NotFound(
                 "Chocolatey is not installed".to_string(),
             ));
         }
 
-        let output = TokioCommand::new(&self.exe_path)
-            .args(&["install", package_id, "-y", "--no-progress"])
-            .output()
+        let version = options.version.as_deref();
+        let mut args = vec!["install", package_id, "-y"];
+        if let Some(version) = version {
+            args.extend(["--version", version]);
+        }
+        if let Some(catalog) = options.catalog.as_deref() {
+            args.extend(["--source", catalog]);
+        }
+        if options.force {
+            args.push("--force");
+        }
+        args.extend(options.extra_args.iter().map(String::as_str));
+
+        let started = Instant::now();
+        let mut tracker = self.track("install", package_id);
+        let output = output_with_lines(TokioCommand::new(&self.exe_path).args(&args), |line| tracker.line(line))
             .await
             .map_err(|e| PackageError::CommandFailed(e.to_string()))?;
+        tracker.finish(output.status.success());
 
-        let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+        let stdout = clean_output(&output.stdout);
         let stderr = String::from_utf8_lossy(&output.stderr).to_string();
         let success = output.status.success();
 
-        // Parse version from output
-        let version = Self::parse_version_from_output(&stdout);
+        if Self::reports_already_installed(&stdout) {
+            return Err(PackageError::AlreadyInstalled(package_id.to_string()));
+        }
+
+        if let Some(error) = exit_code_error(&output.status, &stderr, package_id) {
+            return Err(error);
+        }
+        if Self::reports_reboot_required(&stdout) {
+            return Err(PackageError::RebootRequired(package_id.to_string()));
+        }
+
+        // Parse version from output, falling back to the one requested
+        let installed_version = parse_version(&stdout)
+            .or_else(|| version.map(str::to_string));
 
         Ok(InstallResult {
             success,
             package_id: package_id.to_string(),
-            version,
+            version: installed_version,
             output: stdout,
             error: if success { None } else { Some(stderr) },
+            extra_args: options.extra_args.clone(),
+            duration_ms: elapsed_ms(started),
+            forced: options.force,
         })
     }
 
-    /// Uninstall a package
-    pub async fn uninstall(&self, package_id: &str) -> Result<UninstallResult, PackageError> {
-        if !self.is_installed() {
+    /// Install from a local `.nupkg` or a `packages.config` file.
+    ///
+    /// A `.nupkg` is installed by id with its folder as the only source, since
+    /// Chocolatey 2 no longer accepts a package file path directly.
+    pub async fn install_from_file(&self, path: &Path) -> Result<InstallResult, PackageError> {
+        if !self.is_installed().await {
             return Err(PackageError::NotFound(
                 "Chocolatey is not installed".to_string(),
             ));
         }
 
+        validate_local_file(path, &["nupkg", "config"])?;
+        let file = path.to_string_lossy().to_string();
+
+        let is_nupkg = path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("nupkg"));
+        let (package_id, source_dir) = if is_nupkg {
+            let package_id = Self::nupkg_package_id(path).ok_or_else(|| {
+                PackageError::InvalidInput(format!("Can't determine package id from {}", file))
+            })?;
+            let folder = path
+                .parent()
+                .map(|dir| dir.to_string_lossy().to_string())
+                .filter(|dir| !dir.is_empty())
+                .unwrap_or_else(|| ".".to_string());
+            (package_id, Some(folder))
+        } else {
+            (file, None)
+        };
+
+        let mut args = vec!["install", package_id.as_str(), "-y", "--no-progress"];
+        if let Some(folder) = &source_dir {
+            args.extend(["--source", folder.as_str()]);
+        }
+
+        let started = Instant::now();
+        let output = TokioCommand::new(&self.exe_path)
+            .args(&args)
+            .output()
+            .await
+            .map_err(|e| PackageError::CommandFailed(e.to_string()))?;
+
+        let stdout = clean_output(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+        let success = output.status.success();
+
+        if Self::reports_already_installed(&stdout) {
+            return Err(PackageError::AlreadyInstalled(package_id));
+        }
+
+        if let Some(error) = exit_code_error(&output.status, &stderr, &package_id) {
+            return Err(error);
+        }
+
+        Ok(InstallResult {
+            success,
+            package_id,
+            version: parse_version(&stdout),
+            output: stdout,
+            error: if success { None } else { Some(stderr) },
+            extra_args: Vec::new(),
+            duration_ms: elapsed_ms(started),
+            forced: false,
+        })
+    }
+
+    /// Package id from a `<id>.<version>.nupkg` file name, e.g.
+    /// "git.install.2.43.0.nupkg" -> "git.install"
+    fn nupkg_package_id(path: &Path) -> Option<String> {
+        let stem = path.file_stem()?.to_str()?;
+        let id = stem
+            .split('.')
+            .take_while(|part| !part.starts_with(|c: char| c.is_ascii_digit()))
+            .collect::<Vec<_>>()
+            .join(".");
+
+        if id.is_empty() { None } else { Some(id) }
+    }
+
+    /// Preview an install with `--noop`, which resolves the package without installing it
+    pub async fn preview_install(&self, package_id: &str) -> Result<PackagePreview, PackageError> {
+        validate_package_id(package_id)?;
+
+        if let Some(current) = self.installed_version(package_id).await? {
+            return Ok(PackagePreview {
+                package_id: package_id.to_string(),
+                action: PreviewAction::AlreadyInstalled,
+                from_version: Some(current.clone()),
+                to_version: Some(current),
+            });
+        }
+
+        let stdout = self.run_noop("install", package_id).await?;
+
+        Ok(PackagePreview {
+            package_id: package_id.to_string(),
+            action: PreviewAction::Install,
+            from_version: None,
+            to_version: parse_version(&stdout),
+        })
+    }
+
+    /// Preview an upgrade with `--noop`, e.g. "You have git v2.42.0 installed.
+    /// Version 2.43.0 is available based on your source(s)."
+    pub async fn preview_upgrade(&self, package_id: &str) -> Result<PackagePreview, PackageError> {
+        validate_package_id(package_id)?;
+
+        let current = self
+            .installed_version(package_id)
+            .await?
+            .ok_or_else(|| PackageError::NotInstalled(package_id.to_string()))?;
+
+        let stdout = self.run_noop("upgrade", package_id).await?;
+        let available = parse_version(&stdout).filter(|version| *version != current);
+
+        Ok(PackagePreview {
+            package_id: package_id.to_string(),
+            action: if available.is_some() { PreviewAction::Upgrade } else { PreviewAction::UpToDate },
+            to_version: available.or_else(|| Some(current.clone())),
+            from_version: Some(current),
+        })
+    }
+
+    /// Run `choco <command> <id> --noop` and return its output
+    async fn run_noop(&self, command: &str, package_id: &str) -> Result<String, PackageError> {
         let output = TokioCommand::new(&self.exe_path)
-            .args(&["uninstall", package_id, "-y"])
+            .args([command, package_id, "--noop", "-y", "--no-progress"])
             .output()
             .await
             .map_err(|e| PackageError::CommandFailed(e.to_string()))?;
 
-        let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+        let stdout = clean_output(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+
+        if let Some(error) = exit_code_error(&output.status, &stderr, package_id) {
+            return Err(error);
+        }
+        if !output.status.success() {
+            return Err(PackageError::CommandFailed(if stderr.trim().is_empty() { stdout } else { stderr }));
+        }
+
+        Ok(stdout)
+    }
+
+    /// Installed version of a package, if it's installed
+    async fn installed_version(&self, package_id: &str) -> Result<Option<String>, PackageError> {
+        Ok(self
+            .list_installed()
+            .await?
+            .into_iter()
+            .find(|p| p.id.eq_ignore_ascii_case(package_id))
+            .map(|p| p.version))
+    }
+
+    /// Whether install output shows the package was already present and nothing changed,
+    /// e.g. "git v2.43.0 already installed. Use --force to reinstall..."
+    fn reports_already_installed(output: &str) -> bool {
+        output.lines().any(|line| {
+            let line = line.trim();
+            line.ends_with("already installed.") || line.contains("already installed. Use --force")
+        })
+    }
+
+    /// Whether choco asked for a reboot. It also exits with 3010 in that case,
+    /// unless the `usePackageExitCodes` feature has been turned off.
+    fn reports_reboot_required(output: &str) -> bool {
+        output.contains("indicate a reboot is necessary") || output.contains("Packages requiring reboot:")
+    }
+
+    /// Uninstall a package
+    ///
+    /// With `purge`, dependencies nothing else needs are removed as well.
+    pub async fn uninstall(&self, package_id: &str, purge: bool) -> Result<UninstallResult, PackageError> {
+        validate_package_id(package_id)?;
+
+        if !self.is_installed().await {
+            return Err(PackageError::NotFound(
+                "Chocolatey is not installed".to_string(),
+            ));
+        }
+
+        let mut args = vec!["uninstall", package_id, "-y"];
+        if purge {
+            args.push("--remove-dependencies");
+        }
+
+        let started = Instant::now();
+        let mut tracker = self.track("uninstall", package_id);
+        let output = output_with_lines(
+            TokioCommand::new(&self.exe_path).args(&args),
+            |line| tracker.line(line),
+        )
+        .await
+        .map_err(|e| PackageError::CommandFailed(e.to_string()))?;
+        tracker.finish(output.status.success());
+
+        let stdout = clean_output(&output.stdout);
         let stderr = String::from_utf8_lossy(&output.stderr).to_string();
         let success = output.status.success();
 
+        if Self::reports_not_installed(&stdout) || Self::reports_not_installed(&stderr) {
+            return Err(PackageError::NotInstalled(package_id.to_string()));
+        }
+
+        if let Some(error) = exit_code_error(&output.status, &stderr, package_id) {
+            return Err(error);
+        }
+
+        let removed = if purge {
+            Self::parse_uninstalled(&stdout)
+                .into_iter()
+                .filter(|id| !id.eq_ignore_ascii_case(package_id))
+                .collect()
+        } else {
+            Vec::new()
+        };
+
         Ok(UninstallResult {
             success,
             package_id: package_id.to_string(),
             output: stdout,
             error: if success { None } else { Some(stderr) },
+            removed,
+            duration_ms: elapsed_ms(started),
         })
     }
 
+    /// Ids from " git.install has been successfully uninstalled." lines
+    fn parse_uninstalled(output: &str) -> Vec<String> {
+        output
+            .lines()
+            .filter(|line| line.trim_end().ends_with("has been successfully uninstalled."))
+            .filter_map(|line| line.split_whitespace().next())
+            .map(str::to_string)
+            .collect()
+    }
+
+    /// Whether uninstall output shows there was nothing to remove,
+    /// e.g. "git is not installed. Cannot uninstall a non-existent package."
+    fn reports_not_installed(output: &str) -> bool {
+        output.contains("is not installed. Cannot uninstall")
+            || output.contains("Cannot uninstall a non-existent package")
+    }
+
     /// List installed packages
     pub async fn list_installed(&self) -> Result<Vec<InstalledPackage>, PackageError> {
-        if !self.is_installed() {
+        if !self.is_installed().await {
             return Err(PackageError::NotFound(
                 "Chocolatey is not installed".to_string(),
             ));
         }
 
-        let output = TokioCommand::new(&self.exe_path)
-            .args(&["list", "--local-only", "--limit-output"])
-            .output()
-            .await
-            .map_err(|e| PackageError::CommandFailed(e.to_string()))?;
+        // Chocolatey 2.0 removed --local-only (list is always local there)
+        let major = Self::major_version(self.version().await.as_deref());
+        let mut output = self.run_list(major).await?;
+
+        // If the version couldn't be read and we guessed 1.x wrongly, choco
+        // rejects the flag; retry with 2.x args
+        if !output.status.success() && major.is_none() {
+            let stdout = clean_output(&output.stdout);
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            if Self::rejects_local_only(&stdout) || Self::rejects_local_only(&stderr) {
+                output = self.run_list(Some(2)).await?;
+            }
+        }
 
         if !output.status.success() {
             return Err(PackageError::CommandFailed(
@@ -98,30 +448,75 @@ impl ChocolateyManager {
             ));
         }
 
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let packages = stdout
+        let stdout = clean_output(&output.stdout);
+        Ok(Self::parse_list_output(&stdout))
+    }
+
+    /// Run `choco list` with the local-only arguments appropriate to `major`
+    async fn run_list(&self, major: Option<u32>) -> Result<std::process::Output, PackageError> {
+        TokioCommand::new(&self.exe_path)
+            .args(Self::list_args(major))
+            .output()
+            .await
+            .map_err(|e| PackageError::CommandFailed(e.to_string()))
+    }
+
+    /// Major version from `choco --version` output such as "2.2.2"
+    fn major_version(version: Option<&str>) -> Option<u32> {
+        version
+            .and_then(|v| v.trim().split('.').next())
+            .and_then(|major| major.parse().ok())
+    }
+
+    /// Arguments for listing local packages on a given Chocolatey major version.
+    ///
+    /// Unknown versions are treated as 1.x, since on 1.x a bare `list` would
+    /// query the remote repository instead of installed packages.
+    fn list_args(major: Option<u32>) -> &'static [&'static str] {
+        match major {
+            Some(major) if major >= 2 => &["list", "--limit-output"],
+            _ => &["list", "--local-only", "--limit-output"],
+        }
+    }
+
+    /// Whether choco's output is its complaint about the removed `--local-only` option
+    fn rejects_local_only(output: &str) -> bool {
+        let lower = output.to_lowercase();
+        lower.contains("--local-only")
+            && ["invalid argument", "unknown option", "has been removed"]
+                .iter()
+                .any(|complaint| lower.contains(complaint))
+    }
+
+    /// Parse `id|version` lines from `choco list --limit-output`.
+    ///
+    /// 1.x and 2.x print the same pipe format; anything else (warnings, the
+    /// "N packages installed." footer without --limit-output) is skipped.
+    fn parse_list_output(output: &str) -> Vec<InstalledPackage> {
+        output
             .lines()
             .filter_map(|line| {
-                let parts: Vec<&str> = line.split('|').collect();
-                if parts.len() >= 2 {
-                    Some(InstalledPackage {
-                        id: parts[0].trim().to_string(),
-                        version: parts[1].trim().to_string(),
-                        source: PackageSource::Chocolatey,
-                        name: Some(parts[0].trim().to_string()),
-                    })
-                } else {
-                    None
+                let (id, version) = line.trim().split_once('|')?;
+                let id = id.trim();
+                if id.is_empty() || id.contains(char::is_whitespace) {
+                    return None;
                 }
-            })
-            .collect();
 
-        Ok(packages)
+                Some(InstalledPackage {
+                    id: id.to_string(),
+                    version: version.split('|').next().unwrap_or(version).trim().to_string(),
+                    source: PackageSource::Chocolatey,
+                    name: Some(id.to_string()),
+                })
+            })
+            .collect()
     }
 
     /// Upgrade a package
     pub async fn upgrade(&self, package_id: &str) -> Result<UpgradeResult, PackageError> {
-        if !self.is_installed() {
+        validate_package_id(package_id)?;
+
+        if !self.is_installed().await {
             return Err(PackageError::NotFound(
                 "Chocolatey is not installed".to_string(),
             ));
@@ -134,18 +529,29 @@ impl ChocolateyManager {
             .find(|p| p.id == package_id)
             .map(|p| p.version.clone());
 
-        let output = TokioCommand::new(&self.exe_path)
-            .args(&["upgrade", package_id, "-y", "--no-progress"])
-            .output()
-            .await
-            .map_err(|e| PackageError::CommandFailed(e.to_string()))?;
+        let started = Instant::now();
+        let mut tracker = self.track("upgrade", package_id);
+        let output = output_with_lines(
+            TokioCommand::new(&self.exe_path).args(["upgrade", package_id, "-y"]),
+            |line| tracker.line(line),
+        )
+        .await
+        .map_err(|e| PackageError::CommandFailed(e.to_string()))?;
+        tracker.finish(output.status.success());
 
-        let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+        let stdout = clean_output(&output.stdout);
         let stderr = String::from_utf8_lossy(&output.stderr).to_string();
         let success = output.status.success();
 
+        if let Some(error) = exit_code_error(&output.status, &stderr, package_id) {
+            return Err(error);
+        }
+        if Self::reports_reboot_required(&stdout) {
+            return Err(PackageError::RebootRequired(package_id.to_string()));
+        }
+
         // Parse new version from output
-        let new_version = Self::parse_version_from_output(&stdout);
+        let new_version = parse_version(&stdout);
 
         Ok(UpgradeResult {
             success,
@@ -154,24 +560,564 @@ impl ChocolateyManager {
             new_version,
             output: stdout,
             error: if success { None } else { Some(stderr) },
+            duration_ms: elapsed_ms(started),
+        })
+    }
+
+    /// Install an older version over the current one.
+    ///
+    /// `--allow-downgrade` is what permits going backwards; `--force` makes
+    /// choco reinstall even though the package id is already present.
+    pub async fn downgrade(&self, package_id: &str, version: &str) -> Result<UpgradeResult, PackageError> {
+        validate_package_id(package_id)?;
+
+        if !self.is_installed().await {
+            return Err(PackageError::NotFound(
+                "Chocolatey is not installed".to_string(),
+            ));
+        }
+
+        let old_version = self.installed_version(package_id).await?;
+
+        let started = Instant::now();
+        let mut tracker = self.track("downgrade", package_id);
+        let output = output_with_lines(
+            TokioCommand::new(&self.exe_path).args([
+                "install",
+                package_id,
+                "--version",
+                version,
+                "--force",
+                "--allow-downgrade",
+                "-y",
+            ]),
+            |line| tracker.line(line),
+        )
+        .await
+        .map_err(|e| PackageError::CommandFailed(e.to_string()))?;
+        tracker.finish(output.status.success());
+
+        let stdout = clean_output(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+        let success = output.status.success();
+
+        if let Some(error) = exit_code_error(&output.status, &stderr, package_id) {
+            return Err(error);
+        }
+
+        Ok(UpgradeResult {
+            success,
+            package_id: package_id.to_string(),
+            old_version,
+            new_version: parse_version(&stdout).or_else(|| Some(version.to_string())),
+            output: stdout,
+            error: if success { None } else { Some(stderr) },
+            duration_ms: elapsed_ms(started),
+        })
+    }
+
+    /// Repair a package by reinstalling its current version with `--force`,
+    /// which reruns the package's install script over the existing files
+    pub async fn repair(&self, package_id: &str) -> Result<RepairResult, PackageError> {
+        validate_package_id(package_id)?;
+
+        if !self.is_installed().await {
+            return Err(PackageError::NotFound(
+                "Chocolatey is not installed".to_string(),
+            ));
+        }
+
+        let version = self
+            .installed_version(package_id)
+            .await?
+            .ok_or_else(|| PackageError::NotInstalled(package_id.to_string()))?;
+
+        let mut tracker = self.track("repair", package_id);
+        let output = output_with_lines(
+            TokioCommand::new(&self.exe_path).args([
+                "install",
+                package_id,
+                "--version",
+                &version,
+                "--force",
+                "-y",
+            ]),
+            |line| tracker.line(line),
+        )
+        .await
+        .map_err(|e| PackageError::CommandFailed(e.to_string()))?;
+        tracker.finish(output.status.success());
+
+        let stdout = clean_output(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+        let success = output.status.success();
+
+        if let Some(error) = exit_code_error(&output.status, &stderr, package_id) {
+            return Err(error);
+        }
+        if Self::reports_reboot_required(&stdout) {
+            return Err(PackageError::RebootRequired(package_id.to_string()));
+        }
+
+        Ok(RepairResult {
+            success,
+            package_id: package_id.to_string(),
+            version: Some(version),
+            output: stdout,
+            error: if success { None } else { Some(stderr) },
         })
     }
 
-    /// Parse version number from command output
-    fn parse_version_from_output(output: &str) -> Option<String> {
-        // Look for patterns like "v1.2.3" or "version 1.2.3"
+    /// Problems with an installed package's files. Chocolatey keeps every
+    /// package's `.nupkg` under `lib\<id>`, so a missing one means the install
+    /// was damaged or removed outside choco.
+    pub fn verify_files(&self, package_id: &str) -> Vec<String> {
+        let install_dir = std::env::var("ChocolateyInstall")
+            .unwrap_or_else(|_| r"C:\ProgramData\chocolatey".to_string());
+        let package_dir = Path::new(&install_dir).join("lib").join(package_id);
+        let nupkg = package_dir.join(format!("{}.nupkg", package_id));
+
+        if !package_dir.is_dir() {
+            vec![format!("Package folder is missing: {}", package_dir.display())]
+        } else if !nupkg.is_file() {
+            vec![format!("Package file is missing: {}", nupkg.display())]
+        } else {
+            Vec::new()
+        }
+    }
+
+    /// Search the enabled Chocolatey sources, or only the named one when
+    /// `catalog` is given
+    pub async fn search(&self, query: &str, catalog: Option<&str>) -> Result<Vec<AvailablePackage>, PackageError> {
+        validate_search_query(query)?;
+
+        if !self.is_installed().await {
+            return Err(PackageError::NotFound(
+                "Chocolatey is not installed".to_string(),
+            ));
+        }
+
+        // `choco search` is an alias of the remote `list` on 1.x and the only
+        // remote query on 2.x (where `list` became local-only), so it works on both
+        let mut args = vec!["search", query, "--limit-output"];
+        if let Some(catalog) = catalog {
+            args.extend(["--source", catalog]);
+        }
+
+        let output = TokioCommand::new(&self.exe_path)
+            .args(&args)
+            .output()
+            .await
+            .map_err(|e| PackageError::CommandFailed(e.to_string()))?;
+
+        if !output.status.success() {
+            return Err(PackageError::CommandFailed(
+                String::from_utf8_lossy(&output.stderr).to_string(),
+            ));
+        }
+
+        let stdout = clean_output(&output.stdout);
+        Ok(Self::parse_search_output(&stdout))
+    }
+
+    /// Every version of a package the configured sources offer, newest first
+    pub async fn available_versions(&self, package_id: &str) -> Result<Vec<String>, PackageError> {
+        validate_package_id(package_id)?;
+
+        if !self.is_installed().await {
+            return Err(PackageError::NotFound(
+                "Chocolatey is not installed".to_string(),
+            ));
+        }
+
+        let output = TokioCommand::new(&self.exe_path)
+            .args(["search", package_id, "--exact", "--all-versions", "--limit-output"])
+            .output()
+            .await
+            .map_err(|e| PackageError::CommandFailed(e.to_string()))?;
+
+        if !output.status.success() {
+            return Err(PackageError::CommandFailed(
+                String::from_utf8_lossy(&output.stderr).to_string(),
+            ));
+        }
+
+        let stdout = clean_output(&output.stdout);
+        let mut versions: Vec<String> = Self::parse_search_output(&stdout)
+            .into_iter()
+            .filter(|p| p.id.eq_ignore_ascii_case(package_id) && !p.version.is_empty())
+            .map(|p| p.version)
+            .collect();
+
+        if versions.is_empty() {
+            return Err(PackageError::NotFound(package_id.to_string()));
+        }
+
+        version::sort_newest_first(&mut versions);
+        Ok(versions)
+    }
+
+    /// Parse `id|version` lines from `--limit-output` search results
+    fn parse_search_output(output: &str) -> Vec<AvailablePackage> {
+        output
+            .lines()
+            .filter_map(|line| {
+                // Deprecation notices and other chatter don't follow the pipe format
+                let (id, version) = line.trim().split_once('|')?;
+                let id = id.trim();
+                if id.is_empty() || id.contains(char::is_whitespace) {
+                    return None;
+                }
+
+                Some(AvailablePackage {
+                    id: id.to_string(),
+                    name: None,
+                    version: version.trim().to_string(),
+                    source: PackageSource::Chocolatey,
+                    catalog: None,
+                })
+            })
+            .collect()
+    }
+
+    /// Show repository details for a package
+    pub async fn info(&self, package_id: &str) -> Result<PackageDetails, PackageError> {
+        validate_package_id(package_id)?;
+
+        if !self.is_installed().await {
+            return Err(PackageError::NotFound(
+                "Chocolatey is not installed".to_string(),
+            ));
+        }
+
+        let output = TokioCommand::new(&self.exe_path)
+            .args(["info", package_id])
+            .output()
+            .await
+            .map_err(|e| PackageError::CommandFailed(e.to_string()))?;
+
+        if !output.status.success() {
+            return Err(PackageError::CommandFailed(
+                String::from_utf8_lossy(&output.stderr).to_string(),
+            ));
+        }
+
+        let stdout = clean_output(&output.stdout);
+        Self::parse_info_output(package_id, &stdout)
+            .ok_or_else(|| PackageError::NotFound(package_id.to_string()))
+    }
+
+    /// Parse `choco info` output into package details.
+    ///
+    /// Fields are printed as ` Key: value`, sometimes several per line joined
+    /// by ` | `. Returns `None` if the package header line isn't present.
+    fn parse_info_output(package_id: &str, output: &str) -> Option<PackageDetails> {
+        let mut details = PackageDetails {
+            id: package_id.to_string(),
+            ..Default::default()
+        };
+        let mut found = false;
+        let mut in_description = false;
+
         for line in output.lines() {
-            if line.contains("successfully installed") || line.contains("upgraded") {
-                // Try to extract version using regex-like pattern
-                let words: Vec<&str> = line.split_whitespace().collect();
-                for (i, word) in words.iter().enumerate() {
-                    if word.contains("v") || (i > 0 && words[i - 1].contains("version")) {
-                        return Some(word.trim_start_matches('v').to_string());
+            // Package header: "<id> <version> [Approved]"
+            if !line.starts_with(' ') {
+                let mut words = line.split_whitespace();
+                if words.next().is_some_and(|id| id.eq_ignore_ascii_case(package_id)) {
+                    details.version = words.next().map(str::to_string);
+                    found = true;
+                }
+                in_description = false;
+                continue;
+            }
+
+            let trimmed = line.trim();
+            let mut matched_field = false;
+            for field in trimmed.split(" | ") {
+                let Some((key, value)) = field.split_once(": ") else {
+                    continue;
+                };
+                let value = value.trim().to_string();
+                matched_field = true;
+                in_description = false;
+
+                match key.trim() {
+                    "Title" => details.name = Some(value),
+                    "Author" | "Authors" => details.publisher = Some(value),
+                    "Software Site" => details.homepage = Some(value),
+                    "Software License" => details.license = Some(value),
+                    "Description" => {
+                        details.description = Some(value);
+                        in_description = true;
                     }
+                    _ => {}
+                }
+            }
+
+            // Multi-line descriptions continue on lines without a "Key: " prefix
+            if !matched_field && in_description && !trimmed.is_empty() {
+                if let Some(description) = details.description.as_mut() {
+                    description.push('\n');
+                    description.push_str(trimmed);
                 }
             }
         }
-        None
+
+        found.then_some(details)
+    }
+
+    /// List configured sources, including disabled ones
+    pub async fn list_sources(&self) -> Result<Vec<Source>, PackageError> {
+        if !self.is_installed().await {
+            return Err(PackageError::NotFound(
+                "Chocolatey is not installed".to_string(),
+            ));
+        }
+
+        let output = TokioCommand::new(&self.exe_path)
+            .args(["source", "list", "--limit-output"])
+            .output()
+            .await
+            .map_err(|e| PackageError::CommandFailed(e.to_string()))?;
+
+        if !output.status.success() {
+            return Err(PackageError::CommandFailed(
+                String::from_utf8_lossy(&output.stderr).to_string(),
+            ));
+        }
+
+        let stdout = clean_output(&output.stdout);
+        Ok(Self::parse_source_list_output(&stdout))
+    }
+
+    /// Parse `choco source list --limit-output` lines of the form
+    /// "name|url|disabled|user|certificate|priority|..."
+    fn parse_source_list_output(output: &str) -> Vec<Source> {
+        output
+            .lines()
+            .filter_map(|line| {
+                let mut parts = line.trim().split('|').map(str::trim);
+                let name = parts.next().filter(|name| !name.is_empty())?;
+                let url = parts.next()?;
+                let disabled = parts.next().is_some_and(|d| d.eq_ignore_ascii_case("true"));
+
+                Some(Source {
+                    name: name.to_string(),
+                    url: url.to_string(),
+                    source_type: None,
+                    disabled,
+                })
+            })
+            .collect()
+    }
+
+    /// Add a source, e.g. an internal Artifactory or Nexus NuGet feed.
+    /// Requires administrator rights.
+    pub async fn add_source(&self, name: &str, url: &str) -> Result<(), PackageError> {
+        self.run_source(&["add", "--name", name, "--source", url], name).await
+    }
+
+    /// Remove a source. Requires administrator rights.
+    pub async fn remove_source(&self, name: &str) -> Result<(), PackageError> {
+        self.run_source(&["remove", "--name", name], name).await
+    }
+
+    /// Re-enable a disabled source. Requires administrator rights.
+    pub async fn enable_source(&self, name: &str) -> Result<(), PackageError> {
+        self.run_source(&["enable", "--name", name], name).await
+    }
+
+    /// Stop querying a source without removing it. Requires administrator rights.
+    pub async fn disable_source(&self, name: &str) -> Result<(), PackageError> {
+        self.run_source(&["disable", "--name", name], name).await
+    }
+
+    async fn run_source(&self, args: &[&str], name: &str) -> Result<(), PackageError> {
+        if !self.is_installed().await {
+            return Err(PackageError::NotFound(
+                "Chocolatey is not installed".to_string(),
+            ));
+        }
+
+        let output = TokioCommand::new(&self.exe_path)
+            .arg("source")
+            .args(args)
+            .output()
+            .await
+            .map_err(|e| PackageError::CommandFailed(e.to_string()))?;
+
+        let stdout = clean_output(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+
+        if let Some(error) = exit_code_error(&output.status, &stderr, name) {
+            return Err(error);
+        }
+        if !output.status.success() {
+            return Err(PackageError::CommandFailed(if stderr.trim().is_empty() { stdout } else { stderr }));
+        }
+
+        Ok(())
+    }
+
+    /// Pin a package at its installed version so `upgrade all` skips it
+    pub async fn pin(&self, package_id: &str) -> Result<(), PackageError> {
+        self.run_pin("add", package_id).await
+    }
+
+    /// Remove a package's pin
+    pub async fn unpin(&self, package_id: &str) -> Result<(), PackageError> {
+        self.run_pin("remove", package_id).await
+    }
+
+    async fn run_pin(&self, command: &str, package_id: &str) -> Result<(), PackageError> {
+        validate_package_id(package_id)?;
+
+        if !self.is_installed().await {
+            return Err(PackageError::NotFound(
+                "Chocolatey is not installed".to_string(),
+            ));
+        }
+
+        let output = TokioCommand::new(&self.exe_path)
+            .args(["pin", command, "--name", package_id])
+            .output()
+            .await
+            .map_err(|e| PackageError::CommandFailed(e.to_string()))?;
+
+        let stdout = clean_output(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+
+        if let Some(error) = exit_code_error(&output.status, &stderr, package_id) {
+            return Err(error);
+        }
+        if !output.status.success() {
+            return Err(PackageError::CommandFailed(if stderr.trim().is_empty() { stdout } else { stderr }));
+        }
+
+        Ok(())
+    }
+
+    /// List pinned packages
+    pub async fn list_pinned(&self) -> Result<Vec<PinnedPackage>, PackageError> {
+        if !self.is_installed().await {
+            return Err(PackageError::NotFound(
+                "Chocolatey is not installed".to_string(),
+            ));
+        }
+
+        let output = TokioCommand::new(&self.exe_path)
+            .args(["pin", "list", "--limit-output"])
+            .output()
+            .await
+            .map_err(|e| PackageError::CommandFailed(e.to_string()))?;
+
+        if !output.status.success() {
+            return Err(PackageError::CommandFailed(
+                String::from_utf8_lossy(&output.stderr).to_string(),
+            ));
+        }
+
+        let stdout = clean_output(&output.stdout);
+        Ok(Self::parse_pin_list_output(&stdout))
+    }
+
+    /// Parse `choco pin list --limit-output` lines of the form "id|version"
+    fn parse_pin_list_output(output: &str) -> Vec<PinnedPackage> {
+        output
+            .lines()
+            .filter_map(|line| {
+                let mut parts = line.trim().split('|');
+                let id = parts.next().map(str::trim).filter(|id| !id.is_empty())?;
+                let version = parts.next().map(str::trim).filter(|v| !v.is_empty());
+
+                Some(PinnedPackage {
+                    id: id.to_string(),
+                    version: version.map(str::to_string),
+                    source: PackageSource::Chocolatey,
+                    pin_type: None,
+                })
+            })
+            .collect()
+    }
+
+    /// Upgrade every installed package with an update available.
+    ///
+    /// Returns one result per package whose installed version changed.
+    /// `choco upgrade all` skips pinned packages by default.
+    pub async fn upgrade_all(&self) -> Result<Vec<UpgradeResult>, PackageError> {
+        if !self.is_installed().await {
+            return Err(PackageError::NotFound(
+                "Chocolatey is not installed".to_string(),
+            ));
+        }
+
+        let started = Instant::now();
+        let before = self.list_installed().await?;
+
+        let mut tracker = self.track("upgrade", "all");
+        let output = output_with_lines(
+            TokioCommand::new(&self.exe_path).args(["upgrade", "all", "-y"]),
+            |line| tracker.line(line),
+        )
+        .await
+        .map_err(|e| PackageError::CommandFailed(e.to_string()))?;
+        tracker.finish(output.status.success());
+
+        let stdout = clean_output(&output.stdout);
+        let after = self.list_installed().await?;
+        let results = super::collect_version_changes(&before, &after, &stdout, elapsed_ms(started));
+
+        if results.is_empty() && !output.status.success() {
+            return Err(PackageError::CommandFailed(
+                String::from_utf8_lossy(&output.stderr).to_string(),
+            ));
+        }
+
+        Ok(results)
+    }
+
+    /// List installed packages that have an update available
+    pub async fn list_outdated(&self) -> Result<Vec<UpgradablePackage>, PackageError> {
+        if !self.is_installed().await {
+            return Err(PackageError::NotFound(
+                "Chocolatey is not installed".to_string(),
+            ));
+        }
+
+        let output = TokioCommand::new(&self.exe_path)
+            .args(["outdated", "--limit-output"])
+            .output()
+            .await
+            .map_err(|e| PackageError::CommandFailed(e.to_string()))?;
+
+        if !output.status.success() {
+            return Err(PackageError::CommandFailed(
+                String::from_utf8_lossy(&output.stderr).to_string(),
+            ));
+        }
+
+        let stdout = clean_output(&output.stdout);
+        Ok(Self::parse_outdated_output(&stdout))
+    }
+
+    /// Parse `id|current|available|pinned` lines from `choco outdated --limit-output`
+    fn parse_outdated_output(output: &str) -> Vec<UpgradablePackage> {
+        output
+            .lines()
+            .filter_map(|line| {
+                let parts: Vec<&str> = line.trim().split('|').collect();
+                if parts.len() < 3 || parts[0].trim().is_empty() {
+                    return None;
+                }
+
+                Some(UpgradablePackage {
+                    id: parts[0].trim().to_string(),
+                    current_version: parts[1].trim().to_string(),
+                    available_version: parts[2].trim().to_string(),
+                    source: PackageSource::Chocolatey,
+                })
+            })
+            .collect()
     }
 }
 
@@ -180,3 +1126,238 @@ impl Default for ChocolateyManager {
         Self::new()
     }
 }
+
+/// Progress from one line of choco output.
+///
+/// Only choco's own markers count: `Progress:` lines (the package download,
+/// e.g. "Progress: Downloading git 2.43.0... 45%", and installer downloads,
+/// e.g. "Progress: 45% - Saving 12.3 MB of 27.1 MB"), and lines starting a
+/// step such as "Downloading git 64 bit" or "Installing git...". The
+/// "Installing the following packages:" header is ignored so the download
+/// that follows it isn't mistaken for the install.
+fn parse_progress(line: &str) -> LineProgress {
+    let line = line.trim();
+    if let Some(rest) = line.strip_prefix("Progress:") {
+        return LineProgress {
+            phase: Some(Phase::Downloading),
+            fraction: line_fraction(rest),
+        };
+    }
+
+    let lower = line.to_lowercase();
+    if lower.ends_with("the following packages:") {
+        return LineProgress::default();
+    }
+
+    let phase = if lower.starts_with("downloading ") {
+        Some(Phase::Downloading)
+    } else if ["installing ", "upgrading ", "uninstalling ", "extracting ", "removing "]
+        .iter()
+        .any(|step| lower.starts_with(step))
+    {
+        Some(Phase::Installing)
+    } else {
+        None
+    };
+
+    LineProgress { phase, fraction: None }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Captured from `choco install git -y`, trimmed
+    const INSTALL_OUTPUT: &str = "\
+Chocolatey v2.2.2
+Installing the following packages:
+git
+By installing, you accept licenses for the packages.
+Progress: Downloading git.install 2.43.0... 12%
+Progress: Downloading git.install 2.43.0... 45%
+Progress: Downloading git.install 2.43.0... 100%
+
+git.install v2.43.0 [Approved]
+git.install package files install completed. Performing other installation steps.
+Downloading git 64 bit
+  from 'https://github.com/git-for-windows/git/releases/download/v2.43.0.windows.1/Git-2.43.0-64-bit.exe'
+Installing git.install...
+git.install has been installed.
+ The install of git.install was successful.
+";
+
+    #[test]
+    fn list_args_follow_major_version() {
+        assert_eq!(ChocolateyManager::major_version(Some("1.4.0")), Some(1));
+        assert_eq!(ChocolateyManager::major_version(Some("2.2.2")), Some(2));
+        assert_eq!(ChocolateyManager::major_version(Some("unknown")), None);
+
+        assert_eq!(ChocolateyManager::list_args(Some(1)), ["list", "--local-only", "--limit-output"]);
+        assert_eq!(ChocolateyManager::list_args(Some(2)), ["list", "--limit-output"]);
+        assert_eq!(ChocolateyManager::list_args(Some(3)), ["list", "--limit-output"]);
+        assert_eq!(ChocolateyManager::list_args(None), ["list", "--local-only", "--limit-output"]);
+    }
+
+    #[test]
+    fn list_output_parses_on_1x_and_2x() {
+        // 1.x prints a count footer; 2.x dropped it
+        let v1 = "Chocolatey v1.4.0\ngit|2.43.0\nnodejs.install|20.11.0\n2 packages installed.\n";
+        let v2 = "git|2.43.0\nnodejs.install|20.11.0\n";
+
+        for output in [v1, v2] {
+            let packages = ChocolateyManager::parse_list_output(output);
+            let ids: Vec<(&str, &str)> = packages.iter().map(|p| (p.id.as_str(), p.version.as_str())).collect();
+            assert_eq!(ids, vec![("git", "2.43.0"), ("nodejs.install", "20.11.0")]);
+        }
+    }
+
+    #[test]
+    fn recognizes_removed_local_only_complaint() {
+        assert!(ChocolateyManager::rejects_local_only(
+            "Invalid argument --local-only. This argument has been removed from the list command and cannot be used."
+        ));
+        assert!(!ChocolateyManager::rejects_local_only("Unable to connect to source 'https://community.chocolatey.org/api/v2/'"));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn list_installed_retries_without_local_only_on_unreadable_version() {
+        let dir = tempfile::tempdir().unwrap();
+        let exe = crate::package_manager::test_support::fake_exe(
+            dir.path(),
+            "choco",
+            r#"case "$*" in
+  --version) echo "dev-build" ;;
+  *--local-only*) echo "Invalid argument --local-only. This argument has been removed from the list command and cannot be used."; exit 1 ;;
+  "list --limit-output") echo "git|2.43.0" ;;
+  *) exit 2 ;;
+esac"#,
+        );
+
+        let packages = ChocolateyManager::with_path(exe).list_installed().await.unwrap();
+        assert_eq!(packages.len(), 1);
+        assert_eq!(packages[0].id, "git");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn install_passes_requested_version() {
+        let dir = tempfile::tempdir().unwrap();
+        let exe = crate::package_manager::test_support::fake_exe(
+            dir.path(),
+            "choco",
+            r#"case "$1" in
+  --version) echo "2.2.2" ;;
+  install) echo "$*" >> "$(dirname "$0")/args.log"; echo "Chocolatey installed 1/1 packages." ;;
+esac"#,
+        );
+        let manager = ChocolateyManager::with_path(exe);
+
+        let pinned = InstallOptions {
+            version: Some("2.40.0".to_string()),
+            ..Default::default()
+        };
+        let result = manager.install("git", &pinned).await.unwrap();
+        // Nothing in the output names a version, so the requested one is reported
+        assert_eq!(result.version.as_deref(), Some("2.40.0"));
+
+        let result = manager.install("git", &InstallOptions::default()).await.unwrap();
+        assert_eq!(result.version, None);
+
+        let args = std::fs::read_to_string(dir.path().join("args.log")).unwrap();
+        assert_eq!(args, "install git -y --version 2.40.0\ninstall git -y\n");
+    }
+
+    #[test]
+    fn detects_already_installed() {
+        let v2 = "\
+Chocolatey v2.2.2
+Installing the following packages:
+git
+By installing, you accept licenses for the packages.
+git v2.43.0 already installed.
+ Use --force to reinstall, specify a version to install, or try upgrade.
+
+Chocolatey installed 0/1 packages.
+ See the log for details (C:\\ProgramData\\chocolatey\\logs\\chocolatey.log).
+";
+        let v1 = "git v2.43.0 already installed. Use --force to reinstall, specify a version to install, or try upgrade.\n";
+
+        assert!(ChocolateyManager::reports_already_installed(v2));
+        assert!(ChocolateyManager::reports_already_installed(v1));
+        assert!(!ChocolateyManager::reports_already_installed(INSTALL_OUTPUT));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn install_of_installed_package_is_already_installed_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let exe = crate::package_manager::test_support::fake_exe(
+            dir.path(),
+            "choco",
+            r#"case "$1" in
+  --version) echo "2.2.2" ;;
+  install) echo "git v2.43.0 already installed."; echo " Use --force to reinstall, specify a version to install, or try upgrade." ;;
+esac"#,
+        );
+
+        let result = ChocolateyManager::with_path(exe).install("git", &InstallOptions::default()).await;
+        assert!(matches!(result, Err(PackageError::AlreadyInstalled(id)) if id == "git"));
+    }
+
+    #[test]
+    fn detects_uninstall_of_missing_package() {
+        assert!(ChocolateyManager::reports_not_installed(
+            "Chocolatey v2.2.2\nUninstalling the following packages:\nnope\nnope is not installed. Cannot uninstall a non-existent package.\n"
+        ));
+        assert!(ChocolateyManager::reports_not_installed(
+            " - nope - Cannot uninstall a non-existent package.\n"
+        ));
+        assert!(!ChocolateyManager::reports_not_installed(
+            "Uninstalling git...\n git has been successfully uninstalled.\n"
+        ));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn uninstall_of_missing_package_is_not_installed_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let exe = crate::package_manager::test_support::fake_exe(
+            dir.path(),
+            "choco",
+            r#"case "$1" in
+  --version) echo "2.2.2" ;;
+  uninstall) echo "$2 is not installed. Cannot uninstall a non-existent package." >&2; exit 1 ;;
+esac"#,
+        );
+
+        let result = ChocolateyManager::with_path(exe).uninstall("nope", false).await;
+        assert!(matches!(result, Err(PackageError::NotInstalled(id)) if id == "nope"));
+    }
+
+    #[test]
+    fn progress_lines_report_download_fraction() {
+        let progress: Vec<LineProgress> = INSTALL_OUTPUT
+            .lines()
+            .map(parse_progress)
+            .filter(|p| p.fraction.is_some())
+            .collect();
+
+        assert_eq!(progress.len(), 3);
+        assert!(progress.iter().all(|p| p.phase == Some(Phase::Downloading)));
+        let fractions: Vec<f32> = progress.iter().filter_map(|p| p.fraction).collect();
+        assert_eq!(fractions, vec![0.12, 0.45, 1.0]);
+    }
+
+    #[test]
+    fn step_lines_switch_phase() {
+        let phases: Vec<Phase> = INSTALL_OUTPUT.lines().filter_map(|line| parse_progress(line).phase).collect();
+
+        assert_eq!(phases.first(), Some(&Phase::Downloading));
+        assert_eq!(phases.last(), Some(&Phase::Installing));
+        assert_eq!(parse_progress("Downloading git 64 bit").phase, Some(Phase::Downloading));
+        assert_eq!(parse_progress("Installing git.install...").phase, Some(Phase::Installing));
+        assert_eq!(parse_progress("Upgrading the following packages:"), LineProgress::default());
+        assert_eq!(parse_progress("Installing the following packages:"), LineProgress::default());
+    }
+}
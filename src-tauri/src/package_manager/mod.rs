@@ -1,81 +1,1616 @@
+// Also built for tests elsewhere so the parsers are tested on every CI platform
+#[cfg(any(target_os = "macos", test))]
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+pub mod brew;
+#[cfg(not(target_os = "macos"))]
 pub mod chocolatey;
+#[cfg(not(target_os = "macos"))]
 pub mod winget;
+pub mod status;
 pub mod types;
+pub mod util;
+pub mod version;
+#[cfg(all(test, unix))]
+mod test_support;
 
 pub use types::*;
+#[cfg(target_os = "macos")]
+pub use brew::HomebrewManager;
+#[cfg(not(target_os = "macos"))]
 pub use chocolatey::ChocolateyManager;
+#[cfg(not(target_os = "macos"))]
 pub use winget::WingetManager;
+pub use status::{StatusCallback, StatusSink};
 
+use futures_util::future::join_all;
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::ExitStatus;
 use std::sync::Arc;
-use tokio::sync::Mutex;
+use std::time::{Duration, Instant};
+use tokio::sync::{Mutex, OwnedMutexGuard, RwLock, Semaphore};
 
-/// Central package manager that coordinates between Chocolatey and Winget
+/// Default number of batch operations run at once, overridable with
+/// `SAVVY_MAX_CONCURRENCY`
+const DEFAULT_MAX_CONCURRENCY: usize = 3;
+
+/// Packages per page when `list_installed_paged` isn't given a limit
+pub const DEFAULT_PAGE_SIZE: usize = 100;
+
+/// How long a parsed installed list is reused while paging through it
+const INSTALLED_CACHE_TTL: Duration = Duration::from_secs(30);
+
+/// Installed list for each source and when it was parsed
+type InstalledCache = std::sync::Mutex<HashMap<PackageSource, (Instant, Arc<Vec<InstalledPackage>>)>>;
+
+/// One lock per (source, lowercase package id)
+type PackageLocks = std::sync::Mutex<HashMap<(PackageSource, String), Arc<Mutex<()>>>>;
+
+/// Central package manager that coordinates between Chocolatey, Winget and Homebrew.
+///
+/// Only the managers that belong on this platform are compiled in (Chocolatey
+/// and Winget on Windows, Homebrew on macOS); operations on any other source
+/// fail with `NotFound`. Other platforms (Linux development and CI builds)
+/// compile Chocolatey and Winget so their parsers build and are tested, but
+/// register neither.
+///
+/// Operations on different packages run concurrently; the managers are only
+/// locked exclusively to re-detect or bootstrap them. Operations on the same
+/// package are serialized through `package_locks`. Batch installs and
+/// upgrade-all runs share the `max_concurrency` permits.
 pub struct PackageManager {
-    chocolatey: Arc<Mutex<ChocolateyManager>>,
-    winget: Arc<Mutex<WingetManager>>,
+    #[cfg(not(target_os = "macos"))]
+    chocolatey: Option<Arc<RwLock<ChocolateyManager>>>,
+    #[cfg(not(target_os = "macos"))]
+    winget: Option<Arc<RwLock<WingetManager>>>,
+    #[cfg(target_os = "macos")]
+    homebrew: Option<Arc<RwLock<HomebrewManager>>>,
+    status: StatusSink,
+    package_locks: PackageLocks,
+    max_concurrency: std::sync::Mutex<Arc<Semaphore>>,
+    installed_cache: InstalledCache,
 }
 
 impl PackageManager {
+    /// Create a package manager with the managers for this platform, honoring
+    /// `SAVVY_CHOCO_PATH`, `SAVVY_WINGET_PATH` and `SAVVY_BREW_PATH` overrides
+    /// for the executable locations and `SAVVY_MAX_CONCURRENCY` for the
+    /// batch concurrency limit
     pub fn new() -> Self {
+        let status = StatusSink::default();
+        #[cfg(not(target_os = "macos"))]
+        let chocolatey = match env_path("SAVVY_CHOCO_PATH") {
+            Some(path) => ChocolateyManager::with_path(path),
+            None => ChocolateyManager::new(),
+        };
+        #[cfg(not(target_os = "macos"))]
+        let winget = match env_path("SAVVY_WINGET_PATH") {
+            Some(path) => WingetManager::with_path(path),
+            None => WingetManager::new(),
+        };
+        #[cfg(target_os = "macos")]
+        let homebrew = match env_path("SAVVY_BREW_PATH") {
+            Some(path) => HomebrewManager::with_path(path),
+            None => HomebrewManager::new(),
+        };
+
         Self {
-            chocolatey: Arc::new(Mutex::new(ChocolateyManager::new())),
-            winget: Arc::new(Mutex::new(WingetManager::new())),
+            #[cfg(not(target_os = "macos"))]
+            chocolatey: cfg!(windows).then(|| Arc::new(RwLock::new(chocolatey.with_status(status.clone())))),
+            #[cfg(not(target_os = "macos"))]
+            winget: cfg!(windows).then(|| Arc::new(RwLock::new(winget.with_status(status.clone())))),
+            #[cfg(target_os = "macos")]
+            homebrew: Some(Arc::new(RwLock::new(homebrew.with_status(status.clone())))),
+            status,
+            package_locks: PackageLocks::default(),
+            max_concurrency: std::sync::Mutex::new(Arc::new(Semaphore::new(env_concurrency()))),
+            installed_cache: InstalledCache::default(),
         }
     }
 
-    /// Install a package using the specified package manager
-    pub async fn install(&self, package_id: &str, source: PackageSource) -> Result<InstallResult, PackageError> {
+    #[cfg(not(target_os = "macos"))]
+    fn chocolatey(&self) -> Result<&RwLock<ChocolateyManager>, PackageError> {
+        self.chocolatey.as_deref().ok_or_else(|| unavailable(PackageSource::Chocolatey))
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    fn winget(&self) -> Result<&RwLock<WingetManager>, PackageError> {
+        self.winget.as_deref().ok_or_else(|| unavailable(PackageSource::Winget))
+    }
+
+    #[cfg(target_os = "macos")]
+    fn homebrew(&self) -> Result<&RwLock<HomebrewManager>, PackageError> {
+        self.homebrew.as_deref().ok_or_else(|| unavailable(PackageSource::Homebrew))
+    }
+
+    /// Sources whose package manager is registered on this platform
+    fn registered_sources(&self) -> Vec<PackageSource> {
+        let mut sources = Vec::new();
+        #[cfg(not(target_os = "macos"))]
+        if self.chocolatey.is_some() {
+            sources.push(PackageSource::Chocolatey);
+        }
+        #[cfg(not(target_os = "macos"))]
+        if self.winget.is_some() {
+            sources.push(PackageSource::Winget);
+        }
+        #[cfg(target_os = "macos")]
+        if self.homebrew.is_some() {
+            sources.push(PackageSource::Homebrew);
+        }
+        sources
+    }
+
+    /// Sources whose package manager is registered on this platform and
+    /// installed on this machine, so the frontend can hide the rest
+    pub async fn available_sources(&self) -> Vec<PackageSource> {
+        let mut sources = Vec::new();
+
+        #[cfg(not(target_os = "macos"))]
+        if let Ok(chocolatey) = self.chocolatey() {
+            if chocolatey.read().await.is_installed().await {
+                sources.push(PackageSource::Chocolatey);
+            }
+        }
+        #[cfg(not(target_os = "macos"))]
+        if let Ok(winget) = self.winget() {
+            if winget.read().await.is_installed().await {
+                sources.push(PackageSource::Winget);
+            }
+        }
+        #[cfg(target_os = "macos")]
+        if let Ok(homebrew) = self.homebrew() {
+            if homebrew.read().await.is_installed().await {
+                sources.push(PackageSource::Homebrew);
+            }
+        }
+
+        sources
+    }
+
+    /// Whether each manager registered on this platform is installed, and its
+    /// version
+    pub async fn manager_statuses(&self) -> Vec<ManagerStatus> {
+        let mut statuses = Vec::new();
+
+        #[cfg(not(target_os = "macos"))]
+        if let Ok(chocolatey) = self.chocolatey() {
+            let version = chocolatey.read().await.version().await;
+            statuses.push(ManagerStatus::new(PackageSource::Chocolatey, version));
+        }
+        #[cfg(not(target_os = "macos"))]
+        if let Ok(winget) = self.winget() {
+            let version = winget.read().await.version().await;
+            statuses.push(ManagerStatus::new(PackageSource::Winget, version));
+        }
+        #[cfg(target_os = "macos")]
+        if let Ok(homebrew) = self.homebrew() {
+            let version = homebrew.read().await.version().await;
+            statuses.push(ManagerStatus::new(PackageSource::Homebrew, version));
+        }
+
+        statuses
+    }
+
+    /// Send `OperationStatus` updates for installs, uninstalls and upgrades to `callback`
+    pub fn set_status_callback(&self, callback: StatusCallback) {
+        self.status.set(callback);
+    }
+
+    /// Limit how many batch installs and upgrade-all runs happen at once.
+    ///
+    /// `1` restores fully serial behavior; `0` is treated as `1`. Operations
+    /// already holding a permit finish under the old limit.
+    pub fn set_max_concurrency(&self, max: usize) {
+        let semaphore = Arc::new(Semaphore::new(max.max(1)));
+        *self.max_concurrency.lock().unwrap_or_else(|e| e.into_inner()) = semaphore;
+    }
+
+    /// The current batch permits; cloned so a concurrent `set_max_concurrency`
+    /// doesn't affect operations already underway
+    fn concurrency_permits(&self) -> Arc<Semaphore> {
+        self.max_concurrency.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Re-detect which package managers are installed on the next operation
+    pub async fn refresh_availability(&self) {
+        #[cfg(not(target_os = "macos"))]
+        if let Some(chocolatey) = &self.chocolatey {
+            chocolatey.write().await.refresh_availability();
+        }
+        #[cfg(not(target_os = "macos"))]
+        if let Some(winget) = &self.winget {
+            winget.write().await.refresh_availability();
+        }
+        #[cfg(target_os = "macos")]
+        if let Some(homebrew) = &self.homebrew {
+            homebrew.write().await.refresh_availability();
+        }
+    }
+
+    /// Install the given package manager itself if it's missing
+    pub async fn bootstrap(&self, source: PackageSource) -> Result<InstallResult, PackageError> {
         match source {
+            #[cfg(not(target_os = "macos"))]
             PackageSource::Chocolatey => {
-                let manager = self.chocolatey.lock().await;
-                manager.install(package_id).await
+                let mut manager = self.chocolatey()?.write().await;
+                manager.bootstrap().await
             }
+            #[cfg(not(target_os = "macos"))]
             PackageSource::Winget => {
-                let manager = self.winget.lock().await;
-                manager.install(package_id).await
+                let mut manager = self.winget()?.write().await;
+                manager.bootstrap().await
             }
+            #[cfg(target_os = "macos")]
+            PackageSource::Homebrew => {
+                let mut manager = self.homebrew()?.write().await;
+                manager.bootstrap().await
+            }
+            _ => Err(unavailable(source)),
         }
     }
 
-    /// Uninstall a package
-    pub async fn uninstall(&self, package_id: &str, source: PackageSource) -> Result<UninstallResult, PackageError> {
-        match source {
+    /// Wait for exclusive use of a package id, so two operations never touch
+    /// the same package at once
+    async fn lock_package(&self, source: PackageSource, package_id: &str) -> PackageGuard<'_> {
+        let key = (source, package_id.to_lowercase());
+        let lock = self
+            .package_locks
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .entry(key.clone())
+            .or_default()
+            .clone();
+
+        PackageGuard {
+            locks: &self.package_locks,
+            key,
+            guard: Some(lock.lock_owned().await),
+        }
+    }
+
+    /// Install a package using the specified package manager
+    pub async fn install(&self, package_id: &str, source: PackageSource) -> Result<InstallResult, PackageError> {
+        self.install_version(package_id, source, None).await
+    }
+
+    /// Install a package, pinned to `version` when given
+    pub async fn install_version(
+        &self,
+        package_id: &str,
+        source: PackageSource,
+        version: Option<&str>,
+    ) -> Result<InstallResult, PackageError> {
+        let options = InstallOptions {
+            version: version.map(str::to_string),
+            ..Default::default()
+        };
+        self.install_with_options(package_id, source, &options).await
+    }
+
+    /// Install a package with a pinned version, a named source and/or extra
+    /// package-manager-specific arguments (e.g. choco `--params`, winget `--override`)
+    #[tracing::instrument(skip(self), err)]
+    pub async fn install_with_options(
+        &self,
+        package_id: &str,
+        source: PackageSource,
+        options: &InstallOptions,
+    ) -> Result<InstallResult, PackageError> {
+        if let Some(version) = &options.version {
+            validate_version(version)?;
+        }
+        if let Some(catalog) = &options.catalog {
+            validate_source_name(catalog)?;
+        }
+        validate_extra_args(&options.extra_args)?;
+
+        let _guard = self.lock_package(source, package_id).await;
+        tracing::info!("Installing");
+        let result = match source {
+            #[cfg(not(target_os = "macos"))]
             PackageSource::Chocolatey => {
-                let manager = self.chocolatey.lock().await;
-                manager.uninstall(package_id).await
+                let manager = self.chocolatey()?.read().await;
+                manager.install(package_id, options).await
             }
+            #[cfg(not(target_os = "macos"))]
             PackageSource::Winget => {
-                let manager = self.winget.lock().await;
-                manager.uninstall(package_id).await
+                let manager = self.winget()?.read().await;
+                manager.install(package_id, options).await
+            }
+            #[cfg(target_os = "macos")]
+            PackageSource::Homebrew => {
+                let manager = self.homebrew()?.read().await;
+                manager.install(package_id, options).await
+            }
+            _ => Err(unavailable(source)),
+        };
+        self.forget_installed(source, &result);
+        result
+    }
+
+    /// Install a package again over its current installation, e.g. to fix a
+    /// half-broken install without uninstalling first.
+    ///
+    /// This forces the install, so it skips the package manager's own
+    /// "already installed" check; the result has `forced` set.
+    pub async fn reinstall(&self, package_id: &str, source: PackageSource) -> Result<InstallResult, PackageError> {
+        let options = InstallOptions {
+            force: true,
+            ..Default::default()
+        };
+        self.install_with_options(package_id, source, &options).await
+    }
+
+    /// Install several packages, at most `max_concurrency` at a time.
+    ///
+    /// Every id gets its own result entry in input order; a failure (including
+    /// an error before the package manager ran) never aborts the rest.
+    pub async fn install_batch(&self, ids: Vec<String>, source: PackageSource) -> Vec<InstallResult> {
+        let semaphore = self.concurrency_permits();
+
+        let installs = ids.iter().map(|package_id| {
+            let semaphore = &semaphore;
+            async move {
+                let _permit = semaphore.acquire().await.expect("semaphore is never closed");
+                self.install(package_id, source)
+                    .await
+                    .unwrap_or_else(|e| failed_install(package_id, e))
+            }
+        });
+
+        join_all(installs).await
+    }
+
+    /// Uninstall a package.
+    ///
+    /// `purge` also removes what the package manager knows was left behind:
+    /// unused dependencies (Chocolatey), a portable package's folder (winget)
+    /// or a cask's support files (Homebrew).
+    #[tracing::instrument(skip(self), err)]
+    pub async fn uninstall(&self, package_id: &str, source: PackageSource, purge: bool) -> Result<UninstallResult, PackageError> {
+        let _guard = self.lock_package(source, package_id).await;
+        tracing::info!("Uninstalling");
+        let result = match source {
+            #[cfg(not(target_os = "macos"))]
+            PackageSource::Chocolatey => {
+                let manager = self.chocolatey()?.read().await;
+                manager.uninstall(package_id, purge).await
+            }
+            #[cfg(not(target_os = "macos"))]
+            PackageSource::Winget => {
+                let manager = self.winget()?.read().await;
+                manager.uninstall(package_id, purge).await
+            }
+            #[cfg(target_os = "macos")]
+            PackageSource::Homebrew => {
+                let manager = self.homebrew()?.read().await;
+                manager.uninstall(package_id, purge).await
+            }
+            _ => Err(unavailable(source)),
+        };
+        self.forget_installed(source, &result);
+        result
+    }
+
+    /// Repair an installed package: `winget repair`, a forced reinstall of the
+    /// same version for Chocolatey, or `brew reinstall`
+    #[tracing::instrument(skip(self), err)]
+    pub async fn repair(&self, package_id: &str, source: PackageSource) -> Result<RepairResult, PackageError> {
+        let _guard = self.lock_package(source, package_id).await;
+        tracing::info!("Repairing");
+        let result = match source {
+            #[cfg(not(target_os = "macos"))]
+            PackageSource::Chocolatey => {
+                let manager = self.chocolatey()?.read().await;
+                manager.repair(package_id).await
+            }
+            #[cfg(not(target_os = "macos"))]
+            PackageSource::Winget => {
+                let manager = self.winget()?.read().await;
+                manager.repair(package_id).await
+            }
+            #[cfg(target_os = "macos")]
+            PackageSource::Homebrew => {
+                let manager = self.homebrew()?.read().await;
+                manager.repair(package_id).await
+            }
+            _ => Err(unavailable(source)),
+        };
+        self.forget_installed(source, &result);
+        result
+    }
+
+    /// Download a winget package's installer and manifest into `dest_dir`
+    /// without installing it, e.g. to carry to an offline machine
+    #[cfg(not(target_os = "macos"))]
+    #[tracing::instrument(skip(self), err)]
+    pub async fn download(&self, package_id: &str, dest_dir: &Path) -> Result<DownloadResult, PackageError> {
+        tracing::info!("Downloading installer");
+        let manager = self.winget()?.read().await;
+        manager.download(package_id, dest_dir).await
+    }
+
+    /// Check a package is installed and, for Chocolatey, that its files are
+    /// still in place. Winget and Homebrew can only confirm it's installed.
+    pub async fn verify(&self, package_id: &str, source: PackageSource) -> Result<VerifyResult, PackageError> {
+        let installed = self
+            .list_installed(source)
+            .await?
+            .into_iter()
+            .find(|p| p.id.eq_ignore_ascii_case(package_id));
+
+        let Some(installed) = installed else {
+            return Ok(VerifyResult {
+                package_id: package_id.to_string(),
+                installed: false,
+                version: None,
+                intact: None,
+                problems: vec![format!("{} is not installed", package_id)],
+            });
+        };
+
+        let problems = match source {
+            #[cfg(not(target_os = "macos"))]
+            PackageSource::Chocolatey => Some(self.chocolatey()?.read().await.verify_files(&installed.id)),
+            _ => None,
+        };
+
+        Ok(VerifyResult {
+            package_id: installed.id,
+            installed: true,
+            version: Some(installed.version),
+            intact: problems.as_ref().map(Vec::is_empty),
+            problems: problems.unwrap_or_default(),
+        })
+    }
+
+    /// Look for a package among the installed packages of every available
+    /// source, trying `package_id` in each and then the ids the same app has
+    /// in particular sources (`aliases`, e.g. `Google.Chrome` for winget when
+    /// asked about `googlechrome`). The match says which source it's under.
+    ///
+    /// Called once per search result, so the installed lists come from the
+    /// cache while it's fresh.
+    pub async fn find_installed(
+        &self,
+        package_id: &str,
+        aliases: &[(PackageSource, String)],
+    ) -> Option<InstalledPackage> {
+        for source in self.available_sources().await {
+            let Ok(installed) = self.cached_installed(source).await else {
+                continue;
+            };
+
+            let mut candidates = std::iter::once(package_id).chain(
+                aliases
+                    .iter()
+                    .filter(|(alias_source, _)| *alias_source == source)
+                    .map(|(_, alias)| alias.as_str()),
+            );
+            let found = candidates.find_map(|id| installed.iter().find(|p| p.id.eq_ignore_ascii_case(id)));
+            if let Some(package) = found {
+                return Some(package.clone());
             }
         }
+        None
     }
 
     /// Get list of installed packages
     pub async fn list_installed(&self, source: PackageSource) -> Result<Vec<InstalledPackage>, PackageError> {
         match source {
+            #[cfg(not(target_os = "macos"))]
             PackageSource::Chocolatey => {
-                let manager = self.chocolatey.lock().await;
+                let manager = self.chocolatey()?.read().await;
                 manager.list_installed().await
             }
+            #[cfg(not(target_os = "macos"))]
             PackageSource::Winget => {
-                let manager = self.winget.lock().await;
+                let manager = self.winget()?.read().await;
+                manager.list_installed(None).await
+            }
+            #[cfg(target_os = "macos")]
+            PackageSource::Homebrew => {
+                let manager = self.homebrew()?.read().await;
                 manager.list_installed().await
             }
+            _ => Err(unavailable(source)),
+        }
+    }
+
+    /// Installed packages whose name or id matches `filter`, or all of them
+    /// without one.
+    ///
+    /// Winget filters natively with `--query`; Chocolatey and Homebrew have no
+    /// such flag, so their full list is filtered here by case-insensitive
+    /// substring.
+    pub async fn list_installed_filtered(
+        &self,
+        source: PackageSource,
+        filter: Option<&str>,
+    ) -> Result<Vec<InstalledPackage>, PackageError> {
+        let filter = filter.map(str::trim).filter(|filter| !filter.is_empty());
+
+        #[cfg(not(target_os = "macos"))]
+        if source == PackageSource::Winget {
+            let manager = self.winget()?.read().await;
+            return manager.list_installed(filter).await;
+        }
+
+        let installed = self.list_installed(source).await?;
+        let Some(filter) = filter.map(str::to_lowercase) else {
+            return Ok(installed);
+        };
+
+        Ok(installed
+            .into_iter()
+            .filter(|package| {
+                package.id.to_lowercase().contains(&filter)
+                    || package
+                        .name
+                        .as_deref()
+                        .is_some_and(|name| name.to_lowercase().contains(&filter))
+            })
+            .collect())
+    }
+
+    /// One page of installed packages, `limit` of them starting at `offset`.
+    ///
+    /// The managers can't page, so the full list is parsed and sliced here.
+    /// It's kept for a short while so paging through it doesn't re-run the
+    /// package manager for every page.
+    pub async fn list_installed_paged(
+        &self,
+        source: PackageSource,
+        offset: usize,
+        limit: usize,
+    ) -> Result<PagedPackages, PackageError> {
+        let installed = self.cached_installed(source).await?;
+        let items = installed.iter().skip(offset).take(limit).cloned().collect();
+
+        Ok(PagedPackages {
+            items,
+            total: installed.len(),
+            offset,
+            limit,
+        })
+    }
+
+    /// How many packages are installed from `source`.
+    ///
+    /// Uses the cached installed list while it's fresh. Otherwise winget is
+    /// counted from its `list` table alone; Chocolatey's and Homebrew's
+    /// listings are already one light command, so they're listed (and
+    /// cached) as usual.
+    pub async fn count_installed(&self, source: PackageSource) -> Result<usize, PackageError> {
+        if let Some((listed_at, installed)) = self.installed_cache.lock().unwrap_or_else(|e| e.into_inner()).get(&source) {
+            if listed_at.elapsed() < INSTALLED_CACHE_TTL {
+                return Ok(installed.len());
+            }
+        }
+
+        #[cfg(not(target_os = "macos"))]
+        if source == PackageSource::Winget {
+            let manager = self.winget()?.read().await;
+            return manager.count_installed().await;
+        }
+
+        Ok(self.cached_installed(source).await?.len())
+    }
+
+    /// Installed packages across every source available on this machine,
+    /// counted concurrently.
+    ///
+    /// Like `check_for_updates`, a source that can't be counted is logged and
+    /// left out of the total; only if every source fails is the first error
+    /// returned.
+    pub async fn count_installed_all(&self) -> Result<usize, PackageError> {
+        let sources = self.available_sources().await;
+        let counts = sources
+            .iter()
+            .map(|&source| async move { (source, self.count_installed(source).await) });
+
+        let mut total = 0;
+        let mut counted = false;
+        let mut first_error = None;
+        for (source, result) in join_all(counts).await {
+            match result {
+                Ok(count) => {
+                    total += count;
+                    counted = true;
+                }
+                Err(e) => {
+                    tracing::warn!("Couldn't count installed {} packages: {}", source, e);
+                    first_error.get_or_insert(e);
+                }
+            }
+        }
+
+        match first_error {
+            Some(e) if !counted => Err(e),
+            _ => Ok(total),
+        }
+    }
+
+    /// Drop the cached installed list for `source` and parse it again, e.g.
+    /// once something outside the app installed or removed packages
+    pub async fn refresh_installed(&self, source: PackageSource) -> Result<Vec<InstalledPackage>, PackageError> {
+        self.installed_cache.lock().unwrap_or_else(|e| e.into_inner()).remove(&source);
+        let installed = self.cached_installed(source).await?;
+        Ok(installed.as_ref().clone())
+    }
+
+    /// Forget the cached installed list for `source` once an operation on it
+    /// has run. A failed install or upgrade may still have changed what's
+    /// installed (a reboot-required install has finished its work), so only
+    /// input rejected before any command ran keeps the cache.
+    fn forget_installed<T>(&self, source: PackageSource, result: &Result<T, PackageError>) {
+        if !matches!(result, Err(PackageError::InvalidInput(_))) {
+            self.installed_cache.lock().unwrap_or_else(|e| e.into_inner()).remove(&source);
+        }
+    }
+
+    /// The installed list for `source`, parsed again only if the cached one
+    /// is older than `INSTALLED_CACHE_TTL`
+    async fn cached_installed(&self, source: PackageSource) -> Result<Arc<Vec<InstalledPackage>>, PackageError> {
+        if let Some((listed_at, installed)) = self.installed_cache.lock().unwrap_or_else(|e| e.into_inner()).get(&source) {
+            if listed_at.elapsed() < INSTALLED_CACHE_TTL {
+                return Ok(installed.clone());
+            }
+        }
+
+        let installed = Arc::new(self.list_installed(source).await?);
+        self.installed_cache
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(source, (Instant::now(), installed.clone()));
+        Ok(installed)
+    }
+
+    /// List installed packages that have an update available
+    pub async fn list_upgradable(&self, source: PackageSource) -> Result<Vec<UpgradablePackage>, PackageError> {
+        match source {
+            #[cfg(not(target_os = "macos"))]
+            PackageSource::Chocolatey => {
+                let manager = self.chocolatey()?.read().await;
+                manager.list_outdated().await
+            }
+            #[cfg(not(target_os = "macos"))]
+            PackageSource::Winget => {
+                let manager = self.winget()?.read().await;
+                manager.list_upgradable().await
+            }
+            #[cfg(target_os = "macos")]
+            PackageSource::Homebrew => {
+                let manager = self.homebrew()?.read().await;
+                manager.list_upgradable().await
+            }
+            _ => Err(unavailable(source)),
+        }
+    }
+
+    /// Count available updates across every source registered on this
+    /// platform, checking them concurrently.
+    ///
+    /// A source that can't be checked (not installed, or its command failed)
+    /// is reported in `errors` rather than failing the whole summary; only if
+    /// every source fails is the first error returned.
+    pub async fn check_for_updates(&self) -> Result<UpdateSummary, PackageError> {
+        let sources = self.registered_sources();
+
+        let checks = sources
+            .iter()
+            .map(|&source| async move { (source, self.list_upgradable(source).await) });
+
+        let mut summary = UpdateSummary::default();
+        for (source, result) in join_all(checks).await {
+            match result {
+                Ok(packages) => {
+                    summary.total += packages.len();
+                    summary.by_source.insert(source, packages);
+                }
+                Err(e) => {
+                    tracing::warn!("Couldn't check {} for updates: {}", source, e);
+                    summary.errors.insert(source, e);
+                }
+            }
+        }
+
+        if summary.by_source.is_empty() {
+            if let Some(e) = sources.iter().find_map(|source| summary.errors.remove(source)) {
+                return Err(e);
+            }
+        }
+
+        Ok(summary)
+    }
+
+    /// Upgrade every package that has an update available.
+    ///
+    /// Each run is a single package manager invocation that takes one of the
+    /// `max_concurrency` permits shared with `install_batch`.
+    #[tracing::instrument(skip(self), err)]
+    pub async fn upgrade_all(&self, source: PackageSource) -> Result<Vec<UpgradeResult>, PackageError> {
+        let semaphore = self.concurrency_permits();
+        let _permit = semaphore.acquire().await.expect("semaphore is never closed");
+        tracing::info!("Upgrading all packages");
+        let result = match source {
+            #[cfg(not(target_os = "macos"))]
+            PackageSource::Chocolatey => {
+                let manager = self.chocolatey()?.read().await;
+                manager.upgrade_all().await
+            }
+            #[cfg(not(target_os = "macos"))]
+            PackageSource::Winget => {
+                let manager = self.winget()?.read().await;
+                manager.upgrade_all().await
+            }
+            #[cfg(target_os = "macos")]
+            PackageSource::Homebrew => {
+                let manager = self.homebrew()?.read().await;
+                manager.upgrade_all().await
+            }
+            _ => Err(unavailable(source)),
+        };
+        self.forget_installed(source, &result);
+        result
+    }
+
+    /// Search a package manager's catalog, optionally limited to one named source
+    pub async fn search(
+        &self,
+        query: &str,
+        source: PackageSource,
+        catalog: Option<&str>,
+    ) -> Result<Vec<AvailablePackage>, PackageError> {
+        if let Some(catalog) = catalog {
+            validate_source_name(catalog)?;
+        }
+
+        match source {
+            #[cfg(not(target_os = "macos"))]
+            PackageSource::Chocolatey => {
+                let manager = self.chocolatey()?.read().await;
+                manager.search(query, catalog).await
+            }
+            #[cfg(not(target_os = "macos"))]
+            PackageSource::Winget => {
+                let manager = self.winget()?.read().await;
+                manager.search(query, catalog).await
+            }
+            #[cfg(target_os = "macos")]
+            PackageSource::Homebrew => {
+                let manager = self.homebrew()?.read().await;
+                manager.search(query, catalog).await
+            }
+            _ => Err(unavailable(source)),
+        }
+    }
+
+    /// Get descriptive details about a package
+    pub async fn details(&self, package_id: &str, source: PackageSource) -> Result<PackageDetails, PackageError> {
+        match source {
+            #[cfg(not(target_os = "macos"))]
+            PackageSource::Chocolatey => {
+                let manager = self.chocolatey()?.read().await;
+                manager.info(package_id).await
+            }
+            #[cfg(not(target_os = "macos"))]
+            PackageSource::Winget => {
+                let manager = self.winget()?.read().await;
+                manager.show(package_id).await
+            }
+            #[cfg(target_os = "macos")]
+            PackageSource::Homebrew => {
+                let manager = self.homebrew()?.read().await;
+                manager.info(package_id).await
+            }
+            _ => Err(unavailable(source)),
+        }
+    }
+
+    /// List every version a package offers, newest first
+    pub async fn available_versions(&self, package_id: &str, source: PackageSource) -> Result<Vec<String>, PackageError> {
+        match source {
+            #[cfg(not(target_os = "macos"))]
+            PackageSource::Chocolatey => {
+                let manager = self.chocolatey()?.read().await;
+                manager.available_versions(package_id).await
+            }
+            #[cfg(not(target_os = "macos"))]
+            PackageSource::Winget => {
+                let manager = self.winget()?.read().await;
+                manager.available_versions(package_id).await
+            }
+            #[cfg(target_os = "macos")]
+            PackageSource::Homebrew => {
+                let manager = self.homebrew()?.read().await;
+                manager.available_versions(package_id).await
+            }
+            _ => Err(unavailable(source)),
+        }
+    }
+
+    /// List the repositories a package manager installs from
+    pub async fn list_sources(&self, source: PackageSource) -> Result<Vec<Source>, PackageError> {
+        match source {
+            #[cfg(not(target_os = "macos"))]
+            PackageSource::Chocolatey => {
+                let manager = self.chocolatey()?.read().await;
+                manager.list_sources().await
+            }
+            #[cfg(not(target_os = "macos"))]
+            PackageSource::Winget => {
+                let manager = self.winget()?.read().await;
+                manager.list_sources().await
+            }
+            #[cfg(target_os = "macos")]
+            PackageSource::Homebrew => {
+                let manager = self.homebrew()?.read().await;
+                manager.list_sources().await
+            }
+            _ => Err(unavailable(source)),
+        }
+    }
+
+    /// Register a new repository. `source_type` is only used by winget
+    /// (e.g. "Microsoft.Rest" for a REST feed).
+    pub async fn add_source(
+        &self,
+        source: PackageSource,
+        name: &str,
+        url: &str,
+        source_type: Option<&str>,
+    ) -> Result<(), PackageError> {
+        validate_source_name(name)?;
+        validate_source_url(url)?;
+        if let Some(source_type) = source_type {
+            validate_source_name(source_type)?;
+        }
+
+        match source {
+            #[cfg(not(target_os = "macos"))]
+            PackageSource::Chocolatey => {
+                let manager = self.chocolatey()?.read().await;
+                manager.add_source(name, url).await
+            }
+            #[cfg(not(target_os = "macos"))]
+            PackageSource::Winget => {
+                let manager = self.winget()?.read().await;
+                manager.add_source(name, url, source_type).await
+            }
+            #[cfg(target_os = "macos")]
+            PackageSource::Homebrew => {
+                let manager = self.homebrew()?.read().await;
+                manager.add_source(name, url).await
+            }
+            _ => Err(unavailable(source)),
+        }
+    }
+
+    /// Remove a repository by name
+    pub async fn remove_source(&self, source: PackageSource, name: &str) -> Result<(), PackageError> {
+        validate_source_name(name)?;
+
+        match source {
+            #[cfg(not(target_os = "macos"))]
+            PackageSource::Chocolatey => {
+                let manager = self.chocolatey()?.read().await;
+                manager.remove_source(name).await
+            }
+            #[cfg(not(target_os = "macos"))]
+            PackageSource::Winget => {
+                let manager = self.winget()?.read().await;
+                manager.remove_source(name).await
+            }
+            #[cfg(target_os = "macos")]
+            PackageSource::Homebrew => {
+                let manager = self.homebrew()?.read().await;
+                manager.remove_source(name).await
+            }
+            _ => Err(unavailable(source)),
+        }
+    }
+
+    /// Enable or disable a repository without removing it. Only Chocolatey
+    /// supports this.
+    #[cfg_attr(target_os = "macos", allow(unused_variables))]
+    pub async fn set_source_enabled(&self, source: PackageSource, name: &str, enabled: bool) -> Result<(), PackageError> {
+        validate_source_name(name)?;
+
+        match source {
+            #[cfg(not(target_os = "macos"))]
+            PackageSource::Chocolatey => {
+                let manager = self.chocolatey()?.read().await;
+                if enabled {
+                    manager.enable_source(name).await
+                } else {
+                    manager.disable_source(name).await
+                }
+            }
+            _ => Err(PackageError::InvalidInput(format!(
+                "{} sources can't be disabled, only removed",
+                source
+            ))),
+        }
+    }
+
+    /// Export installed packages from every available source as a JSON manifest.
+    ///
+    /// Sources whose package manager isn't installed are skipped.
+    pub async fn export_installed(&self) -> Result<String, PackageError> {
+        let mut packages = Vec::new();
+
+        for source in self.available_sources().await {
+            let installed = self.list_installed(source).await?;
+
+            packages.extend(installed.into_iter().map(|pkg| ManifestEntry {
+                id: pkg.id,
+                version: pkg.version,
+                source: pkg.source,
+            }));
+        }
+
+        let manifest = PackageManifest {
+            version: MANIFEST_VERSION,
+            exported_at: chrono::Utc::now(),
+            packages,
+        };
+
+        serde_json::to_string_pretty(&manifest)
+            .map_err(|e| PackageError::Unknown(format!("Failed to serialize manifest: {}", e)))
+    }
+
+    /// Export installed winget and Chocolatey packages as a PowerShell script
+    /// that installs them all again, for provisioning a machine without SAVVY.
+    ///
+    /// Homebrew packages can't be installed from PowerShell and are left out,
+    /// as are sources whose package manager isn't installed.
+    pub async fn export_script(&self) -> Result<String, PackageError> {
+        let mut script = format!(
+            "# Generated by SAVVY on {}\n# Reinstalls the winget and Chocolatey packages installed at export time.\n",
+            chrono::Utc::now().to_rfc3339()
+        );
+
+        for source in self.available_sources().await {
+            let command: fn(&str) -> String = match source {
+                PackageSource::Winget => |id| {
+                    format!(
+                        "winget install --id {} --exact --silent --accept-package-agreements --accept-source-agreements",
+                        powershell_quote(id)
+                    )
+                },
+                PackageSource::Chocolatey => |id| format!("choco install {} -y", powershell_quote(id)),
+                PackageSource::Homebrew => continue,
+            };
+
+            let mut installed = self.list_installed(source).await?;
+            installed.sort_by_key(|pkg| pkg.id.to_lowercase());
+
+            script.push_str(&format!("\n# {} ({} packages)\n", source, installed.len()));
+            for pkg in &installed {
+                script.push_str(&command(&pkg.id));
+                script.push('\n');
+            }
+        }
+
+        Ok(script)
+    }
+
+    /// Export installed winget packages as a winget configuration file
+    #[cfg(not(target_os = "macos"))]
+    pub async fn export_winget_configuration(&self) -> Result<String, PackageError> {
+        let manager = self.winget()?.read().await;
+        manager.export_configuration().await
+    }
+
+    /// Write installed winget packages to a `winget export` JSON file
+    #[cfg(not(target_os = "macos"))]
+    pub async fn export_winget_native(&self, path: &Path) -> Result<(), PackageError> {
+        let manager = self.winget()?.read().await;
+        manager.export_native(path).await
+    }
+
+    /// Install the packages in a `winget export` JSON file with `winget import`
+    #[cfg(not(target_os = "macos"))]
+    #[tracing::instrument(skip(self), err)]
+    pub async fn import_winget_native(&self, path: &Path) -> Result<Vec<InstallResult>, PackageError> {
+        tracing::info!("Importing winget export file");
+        let manager = self.winget()?.read().await;
+        let result = manager.import_native(path).await;
+        self.forget_installed(PackageSource::Winget, &result);
+        result
+    }
+
+    /// Apply a winget configuration file with `winget configure`
+    #[cfg(not(target_os = "macos"))]
+    #[tracing::instrument(skip(self), err)]
+    pub async fn apply_winget_configuration(&self, path: &Path) -> Result<ConfigurationResult, PackageError> {
+        tracing::info!("Applying winget configuration");
+        let manager = self.winget()?.read().await;
+        manager.apply_configuration(path).await
+    }
+
+    /// Reinstall every package listed in a JSON manifest produced by `export_installed`.
+    ///
+    /// Packages that are already installed are skipped and reported with a
+    /// successful result; other failures are reported per package.
+    pub async fn import_manifest(&self, manifest_json: &str) -> Result<Vec<InstallResult>, PackageError> {
+        let manifest: PackageManifest = serde_json::from_str(manifest_json)
+            .map_err(|e| PackageError::Unknown(format!("Invalid manifest: {}", e)))?;
+
+        if manifest.version == 0 || manifest.version > MANIFEST_VERSION {
+            return Err(PackageError::Unknown(format!(
+                "Unsupported manifest version {} (expected at most {})",
+                manifest.version, MANIFEST_VERSION
+            )));
+        }
+
+        let mut installed: HashMap<PackageSource, Vec<InstalledPackage>> = HashMap::new();
+        let mut results = Vec::with_capacity(manifest.packages.len());
+
+        for entry in &manifest.packages {
+            if let Entry::Vacant(slot) = installed.entry(entry.source) {
+                // A missing package manager surfaces per package from `install` below
+                slot.insert(self.list_installed(entry.source).await.unwrap_or_default());
+            }
+
+            let existing = installed[&entry.source]
+                .iter()
+                .find(|pkg| pkg.id.eq_ignore_ascii_case(&entry.id));
+
+            let result = match existing {
+                Some(pkg) => InstallResult {
+                    success: true,
+                    package_id: entry.id.clone(),
+                    version: Some(pkg.version.clone()),
+                    output: "Already installed, skipped".to_string(),
+                    error: None,
+                    extra_args: Vec::new(),
+                    duration_ms: 0,
+                    forced: false,
+                },
+                None => self
+                    .install(&entry.id, entry.source)
+                    .await
+                    .unwrap_or_else(|e| failed_install(&entry.id, e)),
+            };
+            results.push(result);
+        }
+
+        Ok(results)
+    }
+
+    /// Install from a local file: a winget manifest, or a choco `.nupkg`/`packages.config`
+    #[tracing::instrument(skip(self), err)]
+    pub async fn install_local(&self, path: &Path, source: PackageSource) -> Result<InstallResult, PackageError> {
+        tracing::info!("Installing from local file");
+        let result = match source {
+            #[cfg(not(target_os = "macos"))]
+            PackageSource::Chocolatey => {
+                let manager = self.chocolatey()?.read().await;
+                manager.install_from_file(path).await
+            }
+            #[cfg(not(target_os = "macos"))]
+            PackageSource::Winget => {
+                let manager = self.winget()?.read().await;
+                manager.install_manifest(path).await
+            }
+            #[cfg(target_os = "macos")]
+            PackageSource::Homebrew => {
+                let manager = self.homebrew()?.read().await;
+                manager.install_from_file(path).await
+            }
+            _ => Err(unavailable(source)),
+        };
+        self.forget_installed(source, &result);
+        result
+    }
+
+    /// Report what installing a package would do, without installing it
+    pub async fn preview_install(&self, package_id: &str, source: PackageSource) -> Result<PackagePreview, PackageError> {
+        match source {
+            #[cfg(not(target_os = "macos"))]
+            PackageSource::Chocolatey => {
+                let manager = self.chocolatey()?.read().await;
+                manager.preview_install(package_id).await
+            }
+            #[cfg(not(target_os = "macos"))]
+            PackageSource::Winget => {
+                let manager = self.winget()?.read().await;
+                manager.preview_install(package_id).await
+            }
+            #[cfg(target_os = "macos")]
+            PackageSource::Homebrew => {
+                let manager = self.homebrew()?.read().await;
+                manager.preview_install(package_id).await
+            }
+            _ => Err(unavailable(source)),
+        }
+    }
+
+    /// Report what upgrading a package would do, without upgrading it
+    pub async fn preview_upgrade(&self, package_id: &str, source: PackageSource) -> Result<PackagePreview, PackageError> {
+        match source {
+            #[cfg(not(target_os = "macos"))]
+            PackageSource::Chocolatey => {
+                let manager = self.chocolatey()?.read().await;
+                manager.preview_upgrade(package_id).await
+            }
+            #[cfg(not(target_os = "macos"))]
+            PackageSource::Winget => {
+                let manager = self.winget()?.read().await;
+                manager.preview_upgrade(package_id).await
+            }
+            #[cfg(target_os = "macos")]
+            PackageSource::Homebrew => {
+                let manager = self.homebrew()?.read().await;
+                manager.preview_upgrade(package_id).await
+            }
+            _ => Err(unavailable(source)),
+        }
+    }
+
+    /// Pin a package so bulk upgrades leave it at its current version
+    pub async fn pin(&self, package_id: &str, source: PackageSource) -> Result<(), PackageError> {
+        let _guard = self.lock_package(source, package_id).await;
+        match source {
+            #[cfg(not(target_os = "macos"))]
+            PackageSource::Chocolatey => {
+                let manager = self.chocolatey()?.read().await;
+                manager.pin(package_id).await
+            }
+            #[cfg(not(target_os = "macos"))]
+            PackageSource::Winget => {
+                let manager = self.winget()?.read().await;
+                manager.pin(package_id).await
+            }
+            #[cfg(target_os = "macos")]
+            PackageSource::Homebrew => {
+                let manager = self.homebrew()?.read().await;
+                manager.pin(package_id).await
+            }
+            _ => Err(unavailable(source)),
+        }
+    }
+
+    /// Remove a package's pin
+    pub async fn unpin(&self, package_id: &str, source: PackageSource) -> Result<(), PackageError> {
+        let _guard = self.lock_package(source, package_id).await;
+        match source {
+            #[cfg(not(target_os = "macos"))]
+            PackageSource::Chocolatey => {
+                let manager = self.chocolatey()?.read().await;
+                manager.unpin(package_id).await
+            }
+            #[cfg(not(target_os = "macos"))]
+            PackageSource::Winget => {
+                let manager = self.winget()?.read().await;
+                manager.unpin(package_id).await
+            }
+            #[cfg(target_os = "macos")]
+            PackageSource::Homebrew => {
+                let manager = self.homebrew()?.read().await;
+                manager.unpin(package_id).await
+            }
+            _ => Err(unavailable(source)),
+        }
+    }
+
+    /// List pinned packages
+    pub async fn list_pinned(&self, source: PackageSource) -> Result<Vec<PinnedPackage>, PackageError> {
+        match source {
+            #[cfg(not(target_os = "macos"))]
+            PackageSource::Chocolatey => {
+                let manager = self.chocolatey()?.read().await;
+                manager.list_pinned().await
+            }
+            #[cfg(not(target_os = "macos"))]
+            PackageSource::Winget => {
+                let manager = self.winget()?.read().await;
+                manager.list_pinned().await
+            }
+            #[cfg(target_os = "macos")]
+            PackageSource::Homebrew => {
+                let manager = self.homebrew()?.read().await;
+                manager.list_pinned().await
+            }
+            _ => Err(unavailable(source)),
         }
     }
 
     /// Upgrade a package to the latest version
+    #[tracing::instrument(skip(self), err)]
     pub async fn upgrade(&self, package_id: &str, source: PackageSource) -> Result<UpgradeResult, PackageError> {
-        match source {
+        let _guard = self.lock_package(source, package_id).await;
+
+        if let Some(up_to_date) = self.up_to_date_result(package_id, source).await {
+            tracing::info!("Already up to date");
+            return Ok(up_to_date);
+        }
+
+        tracing::info!("Upgrading");
+
+        let result = match source {
+            #[cfg(not(target_os = "macos"))]
             PackageSource::Chocolatey => {
-                let manager = self.chocolatey.lock().await;
+                let manager = self.chocolatey()?.read().await;
                 manager.upgrade(package_id).await
             }
+            #[cfg(not(target_os = "macos"))]
             PackageSource::Winget => {
-                let manager = self.winget.lock().await;
+                let manager = self.winget()?.read().await;
+                manager.upgrade(package_id).await
+            }
+            #[cfg(target_os = "macos")]
+            PackageSource::Homebrew => {
+                let manager = self.homebrew()?.read().await;
                 manager.upgrade(package_id).await
             }
+            _ => Err(unavailable(source)),
+        };
+        self.forget_installed(source, &result);
+        result
+    }
+
+    /// Install an older version of a package.
+    ///
+    /// The target must be lower than the installed version unless `force` is
+    /// set. The result reuses `UpgradeResult` with the same meaning as an
+    /// upgrade: `old_version` is what was installed before (the newer one) and
+    /// `new_version` is what's installed now (the older target).
+    #[tracing::instrument(skip(self), err)]
+    pub async fn downgrade(
+        &self,
+        package_id: &str,
+        source: PackageSource,
+        target_version: &str,
+        force: bool,
+    ) -> Result<UpgradeResult, PackageError> {
+        validate_version(target_version)?;
+        let _guard = self.lock_package(source, package_id).await;
+
+        let current = self
+            .list_installed(source)
+            .await?
+            .into_iter()
+            .find(|p| p.id.eq_ignore_ascii_case(package_id))
+            .ok_or_else(|| PackageError::NotInstalled(package_id.to_string()))?
+            .version;
+
+        // For winget's "< 1.2.3" only the bound is known, and the target
+        // must at least be below it
+        let upper = current.strip_prefix('<').map(str::trim).unwrap_or(&current);
+        if !force && version::is_comparable(upper) && !version::is_newer(upper, target_version) {
+            return Err(PackageError::InvalidInput(format!(
+                "{} {} is not older than the installed {}; pass force to install it anyway",
+                package_id, target_version, current
+            )));
+        }
+
+        tracing::info!("Downgrading from {}", current);
+
+        let result = match source {
+            #[cfg(not(target_os = "macos"))]
+            PackageSource::Chocolatey => {
+                let manager = self.chocolatey()?.read().await;
+                manager.downgrade(package_id, target_version).await
+            }
+            #[cfg(not(target_os = "macos"))]
+            PackageSource::Winget => {
+                let manager = self.winget()?.read().await;
+                manager.downgrade(package_id, target_version).await
+            }
+            #[cfg(target_os = "macos")]
+            PackageSource::Homebrew => {
+                let manager = self.homebrew()?.read().await;
+                manager.downgrade(package_id, target_version).await
+            }
+            _ => Err(unavailable(source)),
+        };
+        self.forget_installed(source, &result);
+        result
+    }
+
+    /// A no-op upgrade result when there's nothing newer to install: the
+    /// package is installed but not listed as upgradable, or the listed
+    /// version isn't newer. `None` means go ahead and upgrade, including when
+    /// the versions can't be determined.
+    ///
+    /// Costs one `list_upgradable`; the installed list is only needed for
+    /// packages it leaves out, and then comes from the cache when fresh.
+    async fn up_to_date_result(&self, package_id: &str, source: PackageSource) -> Option<UpgradeResult> {
+        let upgradable = self.list_upgradable(source).await.ok()?;
+        let (current, available) = match upgradable.into_iter().find(|p| p.id.eq_ignore_ascii_case(package_id)) {
+            Some(package) => (package.current_version, package.available_version),
+            None => {
+                let installed = self.cached_installed(source).await.ok()?;
+                let current = installed.iter().find(|p| p.id.eq_ignore_ascii_case(package_id))?.version.clone();
+                (current.clone(), current)
+            }
+        };
+
+        // Winget shows "Unknown" or "< 1.0" when it can't tell, and may leave
+        // such packages out of its upgrade list; don't guess
+        if !version::is_comparable(&current)
+            || !version::is_comparable(&available)
+            || version::is_newer(&available, &current)
+        {
+            return None;
+        }
+
+        Some(UpgradeResult {
+            success: true,
+            package_id: package_id.to_string(),
+            old_version: Some(current.clone()),
+            new_version: Some(current),
+            output: format!("Installed version is already {} or newer", available),
+            error: None,
+            duration_ms: 0,
+        })
+    }
+}
+
+/// Error for a source whose package manager isn't registered on this platform
+fn unavailable(source: PackageSource) -> PackageError {
+    PackageError::NotFound(format!("source not available on this platform: {}", source))
+}
+
+/// Read a non-empty path override from the environment
+fn env_path(var: &str) -> Option<String> {
+    path_override(std::env::var(var).ok())
+}
+
+/// A path override's value, ignoring one that's blank
+fn path_override(value: Option<String>) -> Option<String> {
+    value.filter(|path| !path.trim().is_empty())
+}
+
+/// `SAVVY_MAX_CONCURRENCY` if it's a positive number, otherwise the default
+fn env_concurrency() -> usize {
+    std::env::var("SAVVY_MAX_CONCURRENCY")
+        .ok()
+        .and_then(|max| max.trim().parse::<usize>().ok())
+        .filter(|&max| max > 0)
+        .unwrap_or(DEFAULT_MAX_CONCURRENCY)
+}
+
+/// Check a package id is safe to pass as a command-line argument.
+///
+/// Ids may not be blank, start with `-` (so `--source x` can't pass as an
+/// id and add a flag) or contain control characters such as NUL or a
+/// newline, which could confuse the package manager's argument parsing.
+pub(crate) fn validate_package_id(package_id: &str) -> Result<(), PackageError> {
+    let valid = !package_id.trim().is_empty()
+        && !package_id.starts_with('-')
+        && !package_id.chars().any(char::is_control);
+
+    if valid {
+        Ok(())
+    } else {
+        Err(PackageError::InvalidInput(format!("Invalid package id: {:?}", package_id)))
+    }
+}
+
+/// Check a search query can't be read as an option.
+///
+/// Queries go to the package manager as a positional argument (Chocolatey,
+/// Homebrew), so one starting with `-` would be taken as a flag. Control
+/// characters are rejected for the same reason as in package ids.
+pub(crate) fn validate_search_query(query: &str) -> Result<(), PackageError> {
+    let valid = !query.trim_start().starts_with('-') && !query.chars().any(char::is_control);
+
+    if valid {
+        Ok(())
+    } else {
+        Err(PackageError::InvalidInput(format!("Invalid search query: {:?}", query)))
+    }
+}
+
+/// Check a version string is safe to pass as a command-line argument.
+///
+/// Versions are limited to alphanumerics and `.`, `-`, `+`, `_` and may not
+/// start with `-`, so they can't be mistaken for an extra flag.
+pub(crate) fn validate_version(version: &str) -> Result<(), PackageError> {
+    let valid = !version.is_empty()
+        && version.len() <= 64
+        && !version.starts_with('-')
+        && version
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '+' | '_'));
+
+    if valid {
+        Ok(())
+    } else {
+        Err(PackageError::InvalidInput(format!("Invalid version string: {:?}", version)))
+    }
+}
+
+/// Check a source name is safe to pass as a command-line argument: non-empty,
+/// no whitespace or control characters, and not starting with `-`
+pub(crate) fn validate_source_name(name: &str) -> Result<(), PackageError> {
+    let valid = !name.is_empty()
+        && name.len() <= 100
+        && !name.starts_with('-')
+        && !name.chars().any(|c| c.is_whitespace() || c.is_control());
+
+    if valid {
+        Ok(())
+    } else {
+        Err(PackageError::InvalidInput(format!("Invalid source name: {:?}", name)))
+    }
+}
+
+/// Check a source location is an http(s) URL or an absolute local path
+/// (file shares and folders are valid feeds)
+pub(crate) fn validate_source_url(url: &str) -> Result<(), PackageError> {
+    let is_url = ["http://", "https://"]
+        .iter()
+        .any(|scheme| url.len() > scheme.len() && url[..scheme.len()].eq_ignore_ascii_case(scheme));
+    let is_path = Path::new(url).is_absolute() || url.starts_with("\\\\");
+
+    if (is_url || is_path) && !url.chars().any(|c| c.is_control()) {
+        Ok(())
+    } else {
+        Err(PackageError::InvalidInput(format!("Invalid source URL: {:?}", url)))
+    }
+}
+
+/// Check extra install arguments can be passed through as-is.
+///
+/// Arguments go straight to the process (never through a shell), so only
+/// values that can't be represented or would split a batch-file command
+/// line are rejected: empty arguments, NUL and line breaks.
+pub(crate) fn validate_extra_args(args: &[String]) -> Result<(), PackageError> {
+    for arg in args {
+        if arg.trim().is_empty() || arg.contains(['\0', '\r', '\n']) {
+            return Err(PackageError::InvalidInput(format!("Invalid install argument: {:?}", arg)));
+        }
+    }
+    Ok(())
+}
+
+/// Quote a value as a PowerShell single-quoted string, where only `'` is
+/// special and is escaped by doubling it
+fn powershell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+/// Check a local install file exists and has one of the expected extensions
+#[cfg(not(target_os = "macos"))]
+pub(crate) fn validate_local_file(path: &Path, extensions: &[&str]) -> Result<(), PackageError> {
+    if !path.is_file() {
+        return Err(PackageError::NotFound(format!("File not found: {}", path.display())));
+    }
+
+    let has_extension = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| extensions.iter().any(|e| ext.eq_ignore_ascii_case(e)));
+
+    if has_extension {
+        Ok(())
+    } else {
+        Err(PackageError::InvalidInput(format!(
+            "Expected a .{} file: {}",
+            extensions.join(" or ."),
+            path.display()
+        )))
+    }
+}
+
+/// Create `dir` if needed and check a file can be written in it, so a bad
+/// destination fails before anything is downloaded
+#[cfg(not(target_os = "macos"))]
+pub(crate) fn validate_download_dir(dir: &Path) -> Result<(), PackageError> {
+    if dir.exists() && !dir.is_dir() {
+        return Err(PackageError::InvalidInput(format!("Not a directory: {}", dir.display())));
+    }
+
+    let probe = dir.join(".savvy-write-test");
+    std::fs::create_dir_all(dir)
+        .and_then(|()| std::fs::File::create(&probe))
+        .and_then(|_| std::fs::remove_file(&probe))
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::PermissionDenied | std::io::ErrorKind::ReadOnlyFilesystem => {
+                PackageError::PermissionDenied(format!("Cannot write to {}: {}", dir.display(), e))
+            }
+            _ => PackageError::InvalidInput(format!("Cannot write to {}: {}", dir.display(), e)),
+        })
+}
+
+/// Typed error for a failed subprocess, if its exit code means something specific.
+///
+/// Returns `None` for generic failures, which callers report as an
+/// unsuccessful result carrying stderr as before.
+pub(crate) fn exit_code_error(status: &ExitStatus, stderr: &str, package_id: &str) -> Option<PackageError> {
+    if status.success() {
+        return None;
+    }
+
+    match PackageError::from_exit_code(status.code()?, stderr, package_id) {
+        PackageError::CommandFailed(_) => None,
+        PackageError::PermissionDenied(msg) if !util::is_elevated() => Some(PackageError::PermissionDenied(
+            format!("{} (administrator rights are required; run SAVVY as administrator)", msg),
+        )),
+        error => Some(error),
+    }
+}
+
+/// Turn an install error into a failed result so batch operations can carry on
+fn failed_install(package_id: &str, error: PackageError) -> InstallResult {
+    InstallResult {
+        success: false,
+        package_id: package_id.to_string(),
+        version: None,
+        output: String::new(),
+        error: Some(error.to_string()),
+        extra_args: Vec::new(),
+        duration_ms: 0,
+        forced: false,
+    }
+}
+
+/// Build upgrade results from installed-package snapshots taken before and
+/// after a bulk upgrade, one entry per package whose version changed.
+///
+/// Bulk output doesn't reliably attribute lines to packages, so each entry's
+/// `output` is just the lines that mention its id.
+pub(crate) fn collect_version_changes(
+    before: &[InstalledPackage],
+    after: &[InstalledPackage],
+    output: &str,
+    duration_ms: u64,
+) -> Vec<UpgradeResult> {
+    after
+        .iter()
+        .filter_map(|pkg| {
+            let old = before.iter().find(|p| p.id == pkg.id)?;
+            if old.version == pkg.version {
+                return None;
+            }
+
+            let id_lower = pkg.id.to_lowercase();
+            let package_output = output
+                .lines()
+                .filter(|line| line.to_lowercase().contains(&id_lower))
+                .collect::<Vec<_>>()
+                .join("\n");
+
+            Some(UpgradeResult {
+                success: true,
+                package_id: pkg.id.clone(),
+                old_version: Some(old.version.clone()),
+                new_version: Some(pkg.version.clone()),
+                output: package_output,
+                error: None,
+                duration_ms,
+            })
+        })
+        .collect()
+}
+
+/// Exclusive use of one package id, from `PackageManager::lock_package`.
+///
+/// Dropping it releases the package and removes its lock from
+/// `package_locks` when no other operation is waiting for it, so the map
+/// doesn't keep an entry for every package ever touched.
+struct PackageGuard<'a> {
+    locks: &'a PackageLocks,
+    key: (PackageSource, String),
+    guard: Option<OwnedMutexGuard<()>>,
+}
+
+impl Drop for PackageGuard<'_> {
+    fn drop(&mut self) {
+        self.guard.take();
+        let mut locks = self.locks.lock().unwrap_or_else(|e| e.into_inner());
+        // Waiters clone the Arc under this same lock, so a count of one means nobody is
+        if let Entry::Occupied(entry) = locks.entry(self.key.clone()) {
+            if Arc::strong_count(entry.get()) == 1 {
+                entry.remove();
+            }
         }
     }
 }
@@ -85,3 +1620,319 @@ impl Default for PackageManager {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cache_installed(manager: &PackageManager, source: PackageSource) {
+        manager
+            .installed_cache
+            .lock()
+            .unwrap()
+            .insert(source, (Instant::now(), Arc::new(Vec::new())));
+    }
+
+    fn is_cached(manager: &PackageManager, source: PackageSource) -> bool {
+        manager.installed_cache.lock().unwrap().contains_key(&source)
+    }
+
+    #[test]
+    fn installed_cache_is_dropped_after_any_operation_that_ran() {
+        let manager = PackageManager::new();
+        let results: [Result<(), PackageError>; 4] = [
+            Ok(()),
+            Err(PackageError::RebootRequired("git".to_string())),
+            Err(PackageError::CommandFailed("exit code 1".to_string())),
+            Err(PackageError::PermissionDenied("git".to_string())),
+        ];
+
+        for result in results {
+            cache_installed(&manager, PackageSource::Chocolatey);
+            manager.forget_installed(PackageSource::Chocolatey, &result);
+            assert!(!is_cached(&manager, PackageSource::Chocolatey), "{result:?} should drop the cache");
+        }
+    }
+
+    #[test]
+    fn installed_cache_is_kept_when_input_was_rejected() {
+        let manager = PackageManager::new();
+        cache_installed(&manager, PackageSource::Winget);
+        cache_installed(&manager, PackageSource::Chocolatey);
+
+        manager.forget_installed::<()>(PackageSource::Winget, &Err(PackageError::InvalidInput("-y".to_string())));
+        assert!(is_cached(&manager, PackageSource::Winget));
+
+        manager.forget_installed(PackageSource::Winget, &Ok(()));
+        assert!(!is_cached(&manager, PackageSource::Winget));
+        assert!(is_cached(&manager, PackageSource::Chocolatey));
+    }
+
+    /// A manager whose Chocolatey is the shell script `choco`, in a temp dir
+    /// that must outlive it
+    #[cfg(all(unix, not(target_os = "macos")))]
+    fn with_fake_choco(choco: &str) -> (tempfile::TempDir, PackageManager) {
+        let dir = tempfile::tempdir().unwrap();
+        let exe = test_support::fake_exe(dir.path(), "choco", choco);
+        let mut manager = PackageManager::new();
+        manager.chocolatey = Some(Arc::new(RwLock::new(ChocolateyManager::with_path(exe))));
+        (dir, manager)
+    }
+
+    #[cfg(all(unix, not(target_os = "macos")))]
+    #[tokio::test]
+    async fn batch_install_reports_every_package_in_order() {
+        let (_dir, manager) = with_fake_choco(
+            r#"case "$1" in
+  --version) echo "2.2.2" ;;
+  install)
+    case "$2" in
+      nope) echo "nope not installed. The package was not found with the source(s) listed." >&2; exit 1 ;;
+      *) echo "The install of $2 was successful." ;;
+    esac ;;
+esac"#,
+        );
+
+        let ids = ["wget", "nope", "jq"].map(str::to_string).to_vec();
+        let results = manager.install_batch(ids, PackageSource::Chocolatey).await;
+
+        let outcomes: Vec<(&str, bool)> = results.iter().map(|r| (r.package_id.as_str(), r.success)).collect();
+        assert_eq!(outcomes, [("wget", true), ("nope", false), ("jq", true)]);
+        assert!(results[1].error.as_deref().is_some_and(|e| e.contains("nope")));
+    }
+
+    /// Chocolatey that lists `installed.txt` and appends to it (and to
+    /// `install.log`) on install
+    #[cfg(all(unix, not(target_os = "macos")))]
+    const STATEFUL_CHOCO: &str = r#"dir="$(dirname "$0")"
+case "$1" in
+  --version) echo "2.2.2" ;;
+  list) cat "$dir/installed.txt" 2>/dev/null ;;
+  install) echo "$2|1.0" >> "$dir/installed.txt"; echo "$2" >> "$dir/install.log" ;;
+esac"#;
+
+    #[cfg(all(unix, not(target_os = "macos")))]
+    #[tokio::test]
+    async fn exported_manifest_imports_on_another_machine() {
+        let (old_dir, old_machine) = with_fake_choco(STATEFUL_CHOCO);
+        std::fs::write(old_dir.path().join("installed.txt"), "wget|1.21.4\njq|1.7.1\n").unwrap();
+        let manifest = old_machine.export_installed().await.unwrap();
+
+        let (new_dir, new_machine) = with_fake_choco(STATEFUL_CHOCO);
+        std::fs::write(new_dir.path().join("installed.txt"), "wget|1.21.4\n").unwrap();
+        let results = new_machine.import_manifest(&manifest).await.unwrap();
+
+        let outcomes: Vec<(&str, bool, &str)> =
+            results.iter().map(|r| (r.package_id.as_str(), r.success, r.output.as_str())).collect();
+        assert_eq!(outcomes[0], ("wget", true, "Already installed, skipped"));
+        assert_eq!((outcomes[1].0, outcomes[1].1), ("jq", true));
+        assert_eq!(std::fs::read_to_string(new_dir.path().join("install.log")).unwrap(), "jq\n");
+    }
+
+    #[tokio::test]
+    async fn manifest_from_a_newer_version_is_rejected() {
+        let manager = PackageManager::new();
+        let manifest = format!(
+            r#"{{"version": {}, "exported_at": "2024-01-01T00:00:00Z", "packages": []}}"#,
+            MANIFEST_VERSION + 1
+        );
+        assert!(manager.import_manifest(&manifest).await.is_err());
+        assert!(manager.import_manifest("not json").await.is_err());
+    }
+
+    #[cfg(all(unix, not(target_os = "macos")))]
+    #[tokio::test]
+    async fn parallel_installs_of_one_package_are_serialized() {
+        let (dir, manager) = with_fake_choco(
+            r#"log="$(dirname "$0")/calls.log"
+case "$1" in
+  --version) echo "2.2.2" ;;
+  install) echo "start $2" >> "$log"; sleep 0.3; echo "end $2" >> "$log" ;;
+esac"#,
+        );
+
+        let (first, second, other) = tokio::join!(
+            manager.install("git", PackageSource::Chocolatey),
+            manager.install("Git", PackageSource::Chocolatey),
+            manager.install("wget", PackageSource::Chocolatey),
+        );
+        assert!(first.is_ok() && second.is_ok() && other.is_ok());
+
+        let log = std::fs::read_to_string(dir.path().join("calls.log")).unwrap();
+        let git: Vec<&str> = log.lines().filter(|line| line.to_lowercase().ends_with(" git")).collect();
+        assert_eq!(git, ["start git", "end git", "start Git", "end Git"]);
+
+        // A different package didn't wait for both git installs to finish
+        let lines: Vec<&str> = log.lines().collect();
+        let wget_started = lines.iter().position(|line| *line == "start wget").unwrap();
+        let git_finished = lines.iter().rposition(|line| line.eq_ignore_ascii_case("end git")).unwrap();
+        assert!(wget_started < git_finished, "{log}");
+
+        assert!(manager.package_locks.lock().unwrap().is_empty());
+    }
+
+    #[cfg(all(unix, not(target_os = "macos")))]
+    #[tokio::test]
+    async fn upgrade_skips_packages_with_nothing_newer() {
+        let (dir, manager) = with_fake_choco(
+            r#"dir="$(dirname "$0")"
+case "$1" in
+  --version) echo "2.2.2" ;;
+  list) printf 'git|2.43.0\nwget|1.21.4\ncurl|8.4.0\n' ;;
+  outdated) printf 'wget|1.21.4|1.21.4|false\ncurl|8.4.0|8.5.0|false\n' ;;
+  upgrade) echo "$2" >> "$dir/upgrade.log"; echo "Chocolatey upgraded 1/1 packages." ;;
+esac"#,
+        );
+
+        for id in ["git", "wget"] {
+            let result = manager.upgrade(id, PackageSource::Chocolatey).await.unwrap();
+            assert!(result.success);
+            assert_eq!(result.old_version, result.new_version, "{id}");
+        }
+        manager.upgrade("curl", PackageSource::Chocolatey).await.unwrap();
+
+        assert_eq!(std::fs::read_to_string(dir.path().join("upgrade.log")).unwrap(), "curl\n");
+    }
+
+    #[cfg(all(unix, not(target_os = "macos")))]
+    #[tokio::test]
+    async fn finding_installed_packages_lists_each_source_once() {
+        let (dir, manager) = with_fake_choco(
+            r#"dir="$(dirname "$0")"
+case "$1" in
+  --version) echo "2.2.2" ;;
+  list) echo list >> "$dir/calls.log"; printf 'googlechrome|121.0.6167.85\ngit|2.43.0\n' ;;
+esac"#,
+        );
+        let aliases = [
+            (PackageSource::Winget, "Google.Chrome".to_string()),
+            (PackageSource::Chocolatey, "googlechrome".to_string()),
+        ];
+
+        let chrome = manager.find_installed("Google.Chrome", &aliases).await.unwrap();
+        assert_eq!((chrome.id.as_str(), chrome.source), ("googlechrome", PackageSource::Chocolatey));
+        assert!(manager.find_installed("GIT", &[]).await.is_some());
+        assert!(manager.find_installed("wget", &[]).await.is_none());
+
+        assert_eq!(std::fs::read_to_string(dir.path().join("calls.log")).unwrap(), "list\n");
+    }
+
+    #[cfg(all(unix, not(target_os = "macos")))]
+    #[tokio::test]
+    async fn poisoned_installed_cache_still_lists() {
+        let (_dir, manager) = with_fake_choco(
+            r#"case "$1" in
+  --version) echo "2.2.2" ;;
+  list) echo "git|2.43.0" ;;
+esac"#,
+        );
+        let _ = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            let _cache = manager.installed_cache.lock().unwrap();
+            panic!("poison the cache");
+        }));
+
+        let page = manager.list_installed_paged(PackageSource::Chocolatey, 0, 10).await.unwrap();
+        assert_eq!(page.total, 1);
+        assert_eq!(page.items[0].id, "git");
+    }
+
+    #[cfg(all(unix, not(target_os = "macos")))]
+    #[tokio::test]
+    async fn counting_all_installed_skips_a_failing_source() {
+        let (dir, mut manager) = with_fake_choco(
+            r#"case "$1" in
+  --version) echo "2.2.2" ;;
+  list) printf 'git|2.43.0\ncurl|8.5.0\n' ;;
+esac"#,
+        );
+        let winget = test_support::fake_exe(
+            dir.path(),
+            "winget",
+            r#"case "$1" in
+  --version) echo "v1.7.10861" ;;
+  list) echo "Failed when searching source: winget" >&2; exit 1 ;;
+esac"#,
+        );
+        manager.winget = Some(Arc::new(RwLock::new(WingetManager::with_path(winget))));
+
+        assert_eq!(manager.count_installed_all().await.unwrap(), 2);
+
+        manager.chocolatey = None;
+        assert!(manager.count_installed_all().await.is_err());
+    }
+
+    #[test]
+    fn poisoned_package_locks_are_still_usable() {
+        let manager = PackageManager::new();
+        let _ = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            let _locks = manager.package_locks.lock().unwrap();
+            panic!("poison the lock");
+        }));
+        assert!(manager.package_locks.is_poisoned());
+
+        let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+        runtime.block_on(async {
+            let guard = manager.lock_package(PackageSource::Winget, "Git.Git").await;
+            assert_eq!(manager.package_locks.lock().unwrap_or_else(|e| e.into_inner()).len(), 1);
+            drop(guard);
+        });
+        assert!(manager.package_locks.lock().unwrap_or_else(|e| e.into_inner()).is_empty());
+    }
+
+    #[cfg(all(unix, not(target_os = "macos")))]
+    #[tokio::test]
+    async fn executable_path_overrides_are_honored() {
+        let dir = tempfile::tempdir().unwrap();
+        let exe = test_support::fake_exe(dir.path(), "winget-portable", "echo v1.7.10861");
+
+        let path = path_override(Some(exe)).unwrap();
+        assert_eq!(path_override(Some("  ".to_string())), None);
+        assert_eq!(path_override(None), None);
+
+        let winget = WingetManager::with_path(path);
+        assert!(winget.is_installed().await);
+        assert_eq!(winget.version().await.as_deref(), Some("1.7.10861"));
+
+        let missing = ChocolateyManager::with_path(dir.path().join("no-choco").to_string_lossy());
+        assert!(!missing.is_installed().await);
+    }
+
+    #[test]
+    fn versions_that_could_be_flags_are_rejected() {
+        for version in ["1.0", "2.43.0", "1.2.3-beta.1", "17.8.34330.188", "1.0+build_5"] {
+            assert!(validate_version(version).is_ok(), "{version} should be accepted");
+        }
+        for version in ["", "-1.0", "--force", "1.0 --force", "1.0;calc", "1.0&&x", "$(id)", &"9".repeat(65)] {
+            assert!(
+                matches!(validate_version(version), Err(PackageError::InvalidInput(_))),
+                "{version:?} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn package_ids_that_could_be_flags_are_rejected() {
+        for id in ["Git.Git", "git", "nodejs.install", "Microsoft.VisualStudioCode", "python@3.12"] {
+            assert!(validate_package_id(id).is_ok(), "{id} should be accepted");
+        }
+        for id in ["", "   ", "-y", "--source", "--override=/S", "git\n--force", "git\0"] {
+            assert!(
+                matches!(validate_package_id(id), Err(PackageError::InvalidInput(_))),
+                "{id:?} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn search_queries_that_could_be_flags_are_rejected() {
+        for query in ["git", "visual studio code", "c++", "node-js", "", "7-zip"] {
+            assert!(validate_search_query(query).is_ok(), "{query:?} should be accepted");
+        }
+        for query in ["-h", "--source evil", " --exact", "git\r\n--force", "git\t"] {
+            assert!(
+                matches!(validate_search_query(query), Err(PackageError::InvalidInput(_))),
+                "{query:?} should be rejected"
+            );
+        }
+    }
+}
@@ -0,0 +1,213 @@
+use super::types::OperationStatus;
+use regex::Regex;
+use std::sync::{Arc, LazyLock, RwLock};
+use std::time::{Duration, Instant};
+
+/// Receives status updates while package operations run
+pub type StatusCallback = Arc<dyn Fn(OperationStatus) + Send + Sync>;
+
+/// A percentage anywhere in a progress line, e.g. "Progress: Downloading git 45%"
+static PERCENT: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(\d{1,3}(?:\.\d+)?)\s*%").unwrap());
+
+/// Minimum interval between progress-only updates for one operation (~10/sec).
+/// Phase changes and the final status are always reported.
+const STATUS_EMIT_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Phase of an operation a line of output belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    Downloading,
+    Installing,
+}
+
+/// What one line of output says about an operation's progress
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct LineProgress {
+    /// The phase the line starts or belongs to, `None` to stay in the current one
+    pub phase: Option<Phase>,
+    /// Fraction of the phase done, 0.0 to 1.0
+    pub fraction: Option<f32>,
+}
+
+/// Reads a package manager's output lines; see `StatusTracker::with_parser`
+pub type LineParser = fn(&str) -> LineProgress;
+
+/// Winget's byte counter next to its progress bar, e.g. "12.0 MB / 50.3 MB"
+static BYTE_COUNTER: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)([\d.]+)\s*([KMG]?B)\s*/\s*([\d.]+)\s*([KMG]?B)").unwrap()
+});
+
+/// Shared slot for the status callback, which the app sets once it's running.
+/// Cloned into each manager so they all report through the same callback.
+#[derive(Clone, Default)]
+pub struct StatusSink {
+    callback: Arc<RwLock<Option<StatusCallback>>>,
+}
+
+impl StatusSink {
+    pub fn set(&self, callback: StatusCallback) {
+        *self.callback.write().unwrap() = Some(callback);
+    }
+
+    fn emit(&self, status: OperationStatus) {
+        if let Some(callback) = self.callback.read().unwrap().as_ref() {
+            callback(status);
+        }
+    }
+
+    /// Start tracking an operation; emits a "started" status right away
+    pub fn track(&self, operation: &str, package_id: &str) -> StatusTracker {
+        let mut tracker = StatusTracker {
+            sink: self.clone(),
+            operation: operation.to_string(),
+            package_id: package_id.to_string(),
+            stage: Stage::Started,
+            progress: 0.0,
+            completed: false,
+            parser: generic_progress,
+            last_report: Instant::now(),
+        };
+        tracker.report("Started");
+        tracker
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Stage {
+    Started,
+    Downloading,
+    Installing,
+}
+
+/// Follows one operation's output and emits a status at each milestone.
+/// If it's dropped before `finish`, it reports the operation as failed.
+pub struct StatusTracker {
+    sink: StatusSink,
+    operation: String,
+    package_id: String,
+    stage: Stage,
+    progress: f32,
+    completed: bool,
+    parser: LineParser,
+    last_report: Instant,
+}
+
+impl StatusTracker {
+    /// Read output lines with a package manager's own parser instead of the
+    /// keyword matching that suits any of them
+    #[cfg(not(target_os = "macos"))]
+    pub fn with_parser(mut self, parser: LineParser) -> Self {
+        self.parser = parser;
+        self
+    }
+
+    /// Feed one line of command output
+    pub fn line(&mut self, line: &str) {
+        let LineProgress { phase, fraction } = (self.parser)(line);
+        let stage = match phase {
+            Some(Phase::Downloading) => Stage::Downloading,
+            Some(Phase::Installing) => Stage::Installing,
+            None => self.stage,
+        };
+
+        // Downloading covers the first half of the overall progress, installing the second
+        let (start, message) = match stage {
+            Stage::Started => (0.0, "Started"),
+            Stage::Downloading => (0.0, "Downloading"),
+            Stage::Installing => (0.5, self.working_message()),
+        };
+        let progress = match fraction {
+            Some(fraction) if stage != Stage::Started => start + fraction * 0.5,
+            _ => start,
+        }
+        .max(self.progress);
+
+        // Only report a new stage, or a visible step in progress once the
+        // last update has had time to show; redrawn progress bars are rapid
+        let stepped = progress - self.progress >= 0.01 && self.last_report.elapsed() >= STATUS_EMIT_INTERVAL;
+        if stage != self.stage || stepped {
+            self.stage = stage;
+            self.progress = progress;
+            self.report(message);
+        }
+    }
+
+    /// Message for the main phase of the operation
+    fn working_message(&self) -> &'static str {
+        match self.operation.as_str() {
+            "uninstall" => "Uninstalling",
+            "upgrade" => "Upgrading",
+            _ => "Installing",
+        }
+    }
+
+    /// Emit the final status
+    pub fn finish(mut self, success: bool) {
+        self.complete(success);
+    }
+
+    fn complete(&mut self, success: bool) {
+        self.completed = true;
+        self.progress = 1.0;
+        self.report(if success { "Done" } else { "Failed" });
+    }
+
+    fn report(&mut self, message: &str) {
+        self.last_report = Instant::now();
+        self.sink.emit(OperationStatus {
+            operation: self.operation.clone(),
+            package_id: self.package_id.clone(),
+            progress: self.progress,
+            message: message.to_string(),
+            completed: self.completed,
+        });
+    }
+}
+
+impl Drop for StatusTracker {
+    fn drop(&mut self) {
+        if !self.completed {
+            self.complete(false);
+        }
+    }
+}
+
+/// Phase from keywords anywhere in the line, fraction from a percentage or
+/// byte counter
+fn generic_progress(line: &str) -> LineProgress {
+    let lower = line.to_lowercase();
+    let phase = if lower.contains("download") {
+        Some(Phase::Downloading)
+    } else if lower.contains("install") || lower.contains("upgrad") {
+        Some(Phase::Installing)
+    } else {
+        None
+    };
+
+    LineProgress {
+        phase,
+        fraction: line_fraction(line),
+    }
+}
+
+/// Fraction complete shown on a progress line, from a percentage or byte counter
+pub(crate) fn line_fraction(line: &str) -> Option<f32> {
+    if let Some(caps) = PERCENT.captures(line) {
+        let percent: f32 = caps[1].parse().ok()?;
+        return Some((percent / 100.0).clamp(0.0, 1.0));
+    }
+
+    let caps = BYTE_COUNTER.captures(line)?;
+    let done = caps[1].parse::<f32>().ok()? * unit_scale(&caps[2]);
+    let total = caps[3].parse::<f32>().ok()? * unit_scale(&caps[4]);
+    (total > 0.0).then(|| (done / total).clamp(0.0, 1.0))
+}
+
+fn unit_scale(unit: &str) -> f32 {
+    match unit.to_ascii_uppercase().as_str() {
+        "KB" => 1e3,
+        "MB" => 1e6,
+        "GB" => 1e9,
+        _ => 1.0,
+    }
+}
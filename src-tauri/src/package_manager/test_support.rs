@@ -0,0 +1,15 @@
+//! Stand-in package manager executables for tests that run real commands
+
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+
+/// Write `script` as an executable shell script at `dir/name` and return its
+/// path, for passing to a manager's `with_path`. The script sees the
+/// manager's arguments as `$@`.
+pub fn fake_exe(dir: &Path, name: &str, script: &str) -> String {
+    let path = dir.join(name);
+    fs::write(&path, format!("#!/bin/sh\n{script}\n")).unwrap();
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+    path.to_string_lossy().to_string()
+}
@@ -1,12 +1,18 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fmt;
 
-/// Package source (Chocolatey or Winget)
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+/// Current schema version of exported package manifests
+pub const MANIFEST_VERSION: u32 = 1;
+
+/// Package source (Chocolatey or Winget on Windows, Homebrew on macOS)
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
 #[serde(rename_all = "lowercase")]
 pub enum PackageSource {
     Chocolatey,
     Winget,
+    Homebrew,
 }
 
 impl fmt::Display for PackageSource {
@@ -14,6 +20,7 @@ impl fmt::Display for PackageSource {
         match self {
             PackageSource::Chocolatey => write!(f, "chocolatey"),
             PackageSource::Winget => write!(f, "winget"),
+            PackageSource::Homebrew => write!(f, "homebrew"),
         }
     }
 }
@@ -26,6 +33,46 @@ pub struct InstallResult {
     pub version: Option<String>,
     pub output: String,
     pub error: Option<String>,
+    /// Extra arguments passed through to the package manager, kept for auditing
+    #[serde(default)]
+    pub extra_args: Vec<String>,
+    /// How long the package manager ran; 0 when it didn't run at all
+    #[serde(default)]
+    pub duration_ms: u64,
+    /// Installed with `force`, reinstalling over whatever was there
+    #[serde(default)]
+    pub forced: bool,
+}
+
+/// Optional settings for an install
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct InstallOptions {
+    /// Exact version to install instead of the latest
+    #[serde(default)]
+    pub version: Option<String>,
+    /// Named source to install from (e.g. winget's "msstore" or an internal feed)
+    #[serde(default)]
+    pub catalog: Option<String>,
+    /// Package-manager-specific arguments appended after the standard ones
+    #[serde(default)]
+    pub extra_args: Vec<String>,
+    /// Reinstall even if the package is already installed (`--force` for
+    /// Chocolatey and winget, `brew reinstall` for Homebrew)
+    #[serde(default)]
+    pub force: bool,
+}
+
+/// A repository a package manager installs from
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Source {
+    pub name: String,
+    pub url: String,
+    /// Source kind as the package manager reports it (e.g. "Microsoft.Rest")
+    #[serde(rename = "type")]
+    pub source_type: Option<String>,
+    /// Disabled sources stay configured but aren't queried (Chocolatey only)
+    #[serde(default)]
+    pub disabled: bool,
 }
 
 /// Result of a package uninstallation
@@ -35,6 +82,12 @@ pub struct UninstallResult {
     pub package_id: String,
     pub output: String,
     pub error: Option<String>,
+    /// What a purge removed beyond the package itself (dependencies, data
+    /// folders, files); empty for a plain uninstall
+    #[serde(default)]
+    pub removed: Vec<String>,
+    #[serde(default)]
+    pub duration_ms: u64,
 }
 
 /// Result of a package upgrade
@@ -46,6 +99,78 @@ pub struct UpgradeResult {
     pub new_version: Option<String>,
     pub output: String,
     pub error: Option<String>,
+    /// For bulk upgrades, the duration of the whole run
+    #[serde(default)]
+    pub duration_ms: u64,
+}
+
+/// Result of repairing an installed package
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RepairResult {
+    pub success: bool,
+    pub package_id: String,
+    pub version: Option<String>,
+    pub output: String,
+    pub error: Option<String>,
+}
+
+/// Result of downloading a package's installer without installing it
+#[cfg(not(target_os = "macos"))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DownloadResult {
+    pub success: bool,
+    pub package_id: String,
+    pub directory: String,
+    /// Installer and manifest files written to `directory`
+    pub files: Vec<String>,
+    pub output: String,
+    pub error: Option<String>,
+}
+
+/// How applying one unit of a winget configuration went
+#[cfg(not(target_os = "macos"))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConfigurationUnitState {
+    Applied,
+    /// Nothing to do; the unit already matched
+    Unchanged,
+    Failed,
+    /// Winget listed the unit but reported no outcome for it
+    Unknown,
+}
+
+/// One resource of a winget configuration and its outcome
+#[cfg(not(target_os = "macos"))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigurationUnitResult {
+    /// Resource type, e.g. "Microsoft.WinGet.DSC/WinGetPackage"
+    pub resource: String,
+    /// The unit's `id` (or description) from the configuration file
+    pub unit: String,
+    pub state: ConfigurationUnitState,
+    pub message: Option<String>,
+}
+
+/// Result of applying a winget configuration file
+#[cfg(not(target_os = "macos"))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigurationResult {
+    pub success: bool,
+    pub units: Vec<ConfigurationUnitResult>,
+    pub output: String,
+    pub error: Option<String>,
+}
+
+/// Whether a package is installed and, where the manager can tell, intact
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VerifyResult {
+    pub package_id: String,
+    pub installed: bool,
+    pub version: Option<String>,
+    /// `None` when the package manager has no way to check the files
+    pub intact: Option<bool>,
+    pub problems: Vec<String>,
 }
 
 /// Information about an installed package
@@ -57,6 +182,122 @@ pub struct InstalledPackage {
     pub name: Option<String>,
 }
 
+/// One page of installed packages
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PagedPackages {
+    pub items: Vec<InstalledPackage>,
+    /// Installed packages across all pages
+    pub total: usize,
+    pub offset: usize,
+    pub limit: usize,
+}
+
+/// A package available from a package manager's catalog
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AvailablePackage {
+    pub id: String,
+    pub name: Option<String>,
+    pub version: String,
+    pub source: PackageSource,
+    /// Catalog the package comes from (e.g. "winget", "msstore")
+    pub catalog: Option<String>,
+}
+
+/// An installed package with a newer version available
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpgradablePackage {
+    pub id: String,
+    pub current_version: String,
+    pub available_version: String,
+    pub source: PackageSource,
+}
+
+/// A registered package manager and the version found on this machine
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManagerStatus {
+    pub source: PackageSource,
+    pub installed: bool,
+    pub version: Option<String>,
+}
+
+impl ManagerStatus {
+    pub fn new(source: PackageSource, version: Option<String>) -> Self {
+        Self {
+            source,
+            installed: version.is_some(),
+            version,
+        }
+    }
+}
+
+/// Available updates across every source
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UpdateSummary {
+    pub total: usize,
+    pub by_source: HashMap<PackageSource, Vec<UpgradablePackage>>,
+    /// Sources that couldn't be checked and why; they're left out of `total`
+    #[serde(default)]
+    pub errors: HashMap<PackageSource, PackageError>,
+}
+
+/// A package held at its current version so upgrades skip it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PinnedPackage {
+    pub id: String,
+    pub version: Option<String>,
+    pub source: PackageSource,
+    /// Winget's pin type ("Pinning", "Blocking" or "Gating"); choco has only one kind
+    pub pin_type: Option<String>,
+}
+
+/// What an install or upgrade would do, without making any changes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PackagePreview {
+    pub package_id: String,
+    pub action: PreviewAction,
+    pub from_version: Option<String>,
+    pub to_version: Option<String>,
+}
+
+/// The change a previewed operation would make
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PreviewAction {
+    Install,
+    Upgrade,
+    AlreadyInstalled,
+    UpToDate,
+}
+
+/// Descriptive metadata about a package, for display before installing
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PackageDetails {
+    pub id: String,
+    pub name: Option<String>,
+    pub version: Option<String>,
+    pub description: Option<String>,
+    pub publisher: Option<String>,
+    pub homepage: Option<String>,
+    pub license: Option<String>,
+}
+
+/// A snapshot of installed packages that can be replayed on another machine
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PackageManifest {
+    /// Schema version, bumped whenever the format changes incompatibly
+    pub version: u32,
+    pub exported_at: DateTime<Utc>,
+    pub packages: Vec<ManifestEntry>,
+}
+
+/// A single package recorded in a manifest
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    pub id: String,
+    pub version: String,
+    pub source: PackageSource,
+}
+
 /// Package operation status for real-time updates
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OperationStatus {
@@ -68,13 +309,19 @@ pub struct OperationStatus {
 }
 
 /// Errors that can occur during package operations
+///
+/// Serialized as `{ "type": "<Variant>", "message": "..." }` so the frontend
+/// can branch on the kind of error.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", content = "message")]
 pub enum PackageError {
     NotFound(String),
     CommandFailed(String),
     PermissionDenied(String),
     AlreadyInstalled(String),
     NotInstalled(String),
+    InvalidInput(String),
+    RebootRequired(String),
     Unknown(String),
 }
 
@@ -86,9 +333,39 @@ impl fmt::Display for PackageError {
             PackageError::PermissionDenied(msg) => write!(f, "Permission denied: {}", msg),
             PackageError::AlreadyInstalled(msg) => write!(f, "Already installed: {}", msg),
             PackageError::NotInstalled(msg) => write!(f, "Not installed: {}", msg),
+            PackageError::InvalidInput(msg) => write!(f, "Invalid input: {}", msg),
+            PackageError::RebootRequired(msg) => write!(f, "Reboot required: {}", msg),
             PackageError::Unknown(msg) => write!(f, "Unknown error: {}", msg),
         }
     }
 }
 
 impl std::error::Error for PackageError {}
+
+/// Winget: no package matched the given id
+pub const WINGET_NO_PACKAGE_FOUND: u32 = 0x8A15_0014;
+/// Winget: the package is installed but no applicable update exists
+pub const WINGET_UPDATE_NOT_APPLICABLE: u32 = 0x8A15_002B;
+
+impl PackageError {
+    /// Map a package manager's non-zero exit code to a typed error.
+    ///
+    /// Codes that carry no particular meaning fall back to `CommandFailed`
+    /// with the captured stderr.
+    pub fn from_exit_code(code: i32, stderr: &str, package_id: &str) -> PackageError {
+        // Winget reports HRESULTs, which come back as negative i32s
+        match code as u32 {
+            // MSI/choco reboot codes and winget's INSTALL_REBOOT_* results
+            1641 | 3010 | 0x8A15_0109 | 0x8A15_010A | 0x8A15_010B => {
+                PackageError::RebootRequired(package_id.to_string())
+            }
+            // ERROR_ACCESS_DENIED, ERROR_ELEVATION_REQUIRED, MSI insufficient privileges, E_ACCESSDENIED
+            5 | 740 | 1925 | 0x8007_0005 => PackageError::PermissionDenied(package_id.to_string()),
+            WINGET_NO_PACKAGE_FOUND => PackageError::NotFound(package_id.to_string()),
+            WINGET_UPDATE_NOT_APPLICABLE => {
+                PackageError::NotInstalled(format!("{}: no applicable update found", package_id))
+            }
+            _ => PackageError::CommandFailed(stderr.to_string()),
+        }
+    }
+}
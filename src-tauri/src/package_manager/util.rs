@@ -0,0 +1,354 @@
+#[cfg(not(target_os = "macos"))]
+use base64::engine::general_purpose::STANDARD as BASE64;
+#[cfg(not(target_os = "macos"))]
+use base64::Engine;
+use regex::Regex;
+use std::process::{Output, Stdio};
+use std::sync::LazyLock;
+use std::time::Instant;
+use tokio::io::AsyncReadExt;
+use tokio::process::Command as TokioCommand;
+
+#[cfg(not(target_os = "macos"))]
+use super::types::PackageError;
+
+/// ANSI escape sequences: CSI (colors, cursor moves), OSC (titles/links) and two-byte escapes
+static ANSI_ESCAPE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\x1b\[[0-?]*[ -/]*[@-~]|\x1b\][^\x07\x1b]*(?:\x07|\x1b\\)|\x1b[@-Z\\-_]").unwrap()
+});
+
+/// "Version 1.2.3" / "Version: 1.2.3", as printed by winget's "Found" line
+/// and choco's "You have x v1.0 installed. Version 1.1 is available"
+#[cfg(not(target_os = "macos"))]
+static VERSION_LABEL: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)\bversion:?\s+v?(\d+(?:\.\d+)+(?:[-+][0-9A-Za-z.-]+)?)").unwrap());
+
+/// A dotted version on a line reporting a completed install or upgrade
+#[cfg(not(target_os = "macos"))]
+static VERSION_NEAR_RESULT: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)\b(?:installed|upgraded)\b.*?\bv?(\d+(?:\.\d+)+)\b").unwrap());
+
+/// Choco's per-package header, e.g. "git.install v2.43.0 [Approved]"
+#[cfg(not(target_os = "macos"))]
+static PACKAGE_HEADER: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(\S+) v(\d+(?:\.\d+)+(?:[-+][0-9A-Za-z.-]+)?)(?:\s+\[|$)").unwrap());
+
+/// Remove terminal control noise from captured output.
+///
+/// Strips ANSI escape sequences, resolves carriage-return overwrites (progress
+/// bars and spinners redrawn in place) to the text that was finally visible,
+/// and drops lines left holding nothing but a spinner glyph.
+pub fn strip_control_sequences(s: &str) -> String {
+    let without_ansi = ANSI_ESCAPE.replace_all(s, "");
+
+    without_ansi
+        .split('\n')
+        .map(|line| {
+            let line = line.strip_suffix('\r').unwrap_or(line);
+            line.rsplit('\r').next().unwrap_or(line)
+        })
+        .filter(|line| !matches!(line.trim(), "-" | "\\" | "|" | "/"))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Milliseconds since `started`, for the `duration_ms` of operation results
+pub fn elapsed_ms(started: Instant) -> u64 {
+    u64::try_from(started.elapsed().as_millis()).unwrap_or(u64::MAX)
+}
+
+/// Decode captured subprocess output and strip control sequences from it
+pub fn clean_output(bytes: &[u8]) -> String {
+    strip_control_sequences(&String::from_utf8_lossy(bytes))
+}
+
+/// Extract the installed/upgraded version from winget or choco output.
+///
+/// Patterns are tried in order of reliability: an explicit "Version" label,
+/// then a version on an "installed"/"upgraded" line, then choco's package
+/// header line. Choco's own "Chocolatey vX.Y.Z" banner is ignored.
+#[cfg(not(target_os = "macos"))]
+pub fn parse_version(output: &str) -> Option<String> {
+    if let Some(caps) = VERSION_LABEL.captures(output) {
+        return Some(caps[1].to_string());
+    }
+
+    for line in output.lines() {
+        if let Some(caps) = VERSION_NEAR_RESULT.captures(line) {
+            return Some(caps[1].to_string());
+        }
+    }
+
+    output.lines().find_map(|line| {
+        let caps = PACKAGE_HEADER.captures(line.trim())?;
+        if caps[1].eq_ignore_ascii_case("chocolatey") {
+            return None;
+        }
+        Some(caps[2].to_string())
+    })
+}
+
+/// Probe an executable with `--version`.
+///
+/// Returns `None` if it can't be launched, otherwise the first line it
+/// printed without a leading "v" (or "unknown" if it printed nothing).
+#[cfg(not(target_os = "macos"))]
+pub async fn probe_version(exe_path: &str) -> Option<String> {
+    let output = TokioCommand::new(exe_path)
+        .arg("--version")
+        .output()
+        .await
+        .ok()?;
+
+    let stdout = clean_output(&output.stdout);
+    let version = stdout
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .map(|line| line.trim_start_matches('v').to_string())
+        .unwrap_or_else(|| "unknown".to_string());
+
+    Some(version)
+}
+
+/// Run a PowerShell script, optionally in a new elevated window (prompting UAC).
+///
+/// The elevated script is passed as `-EncodedCommand` (base64 UTF-16LE) so it
+/// needs no quoting, and the outer process exits with the elevated one's code.
+#[cfg(not(target_os = "macos"))]
+pub async fn run_powershell(script: &str, elevated: bool) -> Result<Output, PackageError> {
+    let command = if elevated {
+        let utf16: Vec<u8> = script.encode_utf16().flat_map(u16::to_le_bytes).collect();
+        format!(
+            "$p = Start-Process powershell -Verb RunAs -Wait -PassThru -ArgumentList \
+             '-NoProfile -ExecutionPolicy Bypass -EncodedCommand {}'; exit $p.ExitCode",
+            BASE64.encode(utf16)
+        )
+    } else {
+        script.to_string()
+    };
+
+    TokioCommand::new("powershell")
+        .args(["-NoProfile", "-ExecutionPolicy", "Bypass", "-Command", &command])
+        .output()
+        .await
+        .map_err(|e| PackageError::CommandFailed(e.to_string()))
+}
+
+/// Whether this process has administrator rights (an elevated token on
+/// Windows, root elsewhere)
+#[cfg(windows)]
+pub fn is_elevated() -> bool {
+    use windows::Win32::Foundation::{CloseHandle, HANDLE};
+    use windows::Win32::Security::{GetTokenInformation, TokenElevation, TOKEN_ELEVATION, TOKEN_QUERY};
+    use windows::Win32::System::Threading::{GetCurrentProcess, OpenProcessToken};
+
+    unsafe {
+        let mut token = HANDLE::default();
+        if OpenProcessToken(GetCurrentProcess(), TOKEN_QUERY, &mut token).is_err() {
+            return false;
+        }
+
+        let mut elevation = TOKEN_ELEVATION { TokenIsElevated: 0 };
+        let mut size = 0u32;
+        let result = GetTokenInformation(
+            token,
+            TokenElevation,
+            Some(&mut elevation as *mut TOKEN_ELEVATION as *mut std::ffi::c_void),
+            std::mem::size_of::<TOKEN_ELEVATION>() as u32,
+            &mut size,
+        );
+        let _ = CloseHandle(token);
+
+        result.is_ok() && elevation.TokenIsElevated != 0
+    }
+}
+
+/// Whether this process has administrator rights (an elevated token on
+/// Windows, root elsewhere)
+#[cfg(unix)]
+pub fn is_elevated() -> bool {
+    unsafe { libc::geteuid() == 0 }
+}
+
+/// Whether Windows is waiting for a restart to finish installing something.
+///
+/// Checks the same registry markers as most deployment tools: Component Based
+/// Servicing's `RebootPending`, Windows Update's `RebootRequired`, and queued
+/// `PendingFileRenameOperations` from installers that replace in-use files.
+#[cfg(windows)]
+pub fn reboot_pending() -> bool {
+    use winreg::enums::HKEY_LOCAL_MACHINE;
+    use winreg::RegKey;
+
+    let hklm = RegKey::predef(HKEY_LOCAL_MACHINE);
+    let key_exists = |path: &str| hklm.open_subkey(path).is_ok();
+
+    let pending_renames = hklm
+        .open_subkey(r"SYSTEM\CurrentControlSet\Control\Session Manager")
+        .and_then(|key| key.get_raw_value("PendingFileRenameOperations"))
+        .is_ok_and(|value| value.bytes.iter().any(|&b| b != 0));
+
+    key_exists(r"SOFTWARE\Microsoft\Windows\CurrentVersion\Component Based Servicing\RebootPending")
+        || key_exists(r"SOFTWARE\Microsoft\Windows\CurrentVersion\WindowsUpdate\Auto Update\RebootRequired")
+        || pending_renames
+}
+
+/// Whether Windows is waiting for a restart to finish installing something.
+/// Always false on other platforms.
+#[cfg(not(windows))]
+pub fn reboot_pending() -> bool {
+    false
+}
+
+/// Run a command like `Command::output`, passing each line of stdout to
+/// `on_line` as it arrives. Lines redrawn with `\r` are passed each time, with
+/// ANSI sequences removed.
+pub async fn output_with_lines(
+    command: &mut TokioCommand,
+    mut on_line: impl FnMut(&str),
+) -> std::io::Result<Output> {
+    let mut child = command
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    let mut stdout = child.stdout.take().ok_or_else(|| std::io::Error::other("stdout unavailable"))?;
+    let mut stderr = child.stderr.take().ok_or_else(|| std::io::Error::other("stderr unavailable"))?;
+
+    // Drain stderr alongside stdout so neither pipe can fill up and block
+    let stderr_task = tokio::spawn(async move {
+        let mut buf = Vec::new();
+        stderr.read_to_end(&mut buf).await.map(|_| buf)
+    });
+
+    let mut out = Vec::new();
+    let mut chunk = [0u8; 4096];
+    let mut line_start = 0;
+    loop {
+        let read = stdout.read(&mut chunk).await?;
+        if read == 0 {
+            break;
+        }
+        out.extend_from_slice(&chunk[..read]);
+
+        while let Some(len) = out[line_start..].iter().position(|&b| b == b'\n' || b == b'\r') {
+            emit_line(&out[line_start..line_start + len], &mut on_line);
+            line_start += len + 1;
+        }
+    }
+    emit_line(&out[line_start..], &mut on_line);
+
+    let status = child.wait().await?;
+    let stderr = stderr_task.await.map_err(std::io::Error::other)??;
+
+    Ok(Output { status, stdout: out, stderr })
+}
+
+fn emit_line(bytes: &[u8], on_line: &mut impl FnMut(&str)) {
+    let line = ANSI_ESCAPE.replace_all(&String::from_utf8_lossy(bytes), "").to_string();
+    let line = line.trim();
+    if !line.is_empty() {
+        on_line(line);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strips_winget_progress_bar_and_colors() {
+        // Raw bytes of a `winget install` run: spinner frames and bar redraws
+        // separated by \r, an erase-line sequence, and a colored result
+        let raw = "Found Git [Git.Git] Version 2.43.0\r\n\
+   - \r   \\ \r   | \r\
+Downloading https://github.com/git-for-windows/git/releases/download/v2.43.0.windows.1/Git-2.43.0-64-bit.exe\r\n\
+\x1b[2K  ██████▒▒▒▒▒▒▒▒▒▒▒▒▒▒▒▒▒▒▒▒▒▒▒▒  12.0 MB / 58.3 MB\r\
+\x1b[2K  ██████████████████████████████  58.3 MB / 58.3 MB\r\n\
+   / \r\n\
+Successfully verified installer hash\r\n\
+Starting package install...\r\n\
+\x1b[32mSuccessfully installed\x1b[0m\r\n\
+\x1b]0;winget\x07";
+
+        assert_eq!(
+            strip_control_sequences(raw),
+            "Found Git [Git.Git] Version 2.43.0
+Downloading https://github.com/git-for-windows/git/releases/download/v2.43.0.windows.1/Git-2.43.0-64-bit.exe
+  ██████████████████████████████  58.3 MB / 58.3 MB
+Successfully verified installer hash
+Starting package install...
+Successfully installed
+"
+        );
+    }
+
+    #[test]
+    fn clean_output_keeps_plain_text() {
+        let plain = "git|2.43.0\nnodejs.install|20.11.0\n";
+        assert_eq!(clean_output(plain.as_bytes()), plain);
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    #[test]
+    fn parses_versions_from_real_output() {
+        let choco_install = "\
+Chocolatey v2.2.2
+Installing the following packages:
+git
+By installing, you accept licenses for the packages.
+
+git.install v2.43.0 [Approved]
+git.install package files install completed. Performing other installation steps.
+ The install of git.install was successful.
+  Software installed to 'C:\\Program Files\\Git\\'
+
+git v2.43.0 [Approved]
+git package files install completed. Performing other installation steps.
+ The install of git was successful.
+
+Chocolatey installed 2/2 packages.
+";
+        let choco_upgrade = "\
+Chocolatey v2.2.2
+Upgrading the following packages:
+git
+By upgrading, you accept licenses for the packages.
+You have git v2.42.0 installed. Version 2.43.0 is available based on your source(s).
+Chocolatey upgraded 1/1 packages.
+";
+        let winget_install = "\
+Found Git [Git.Git] Version 2.43.0
+This application is licensed to you by its owner.
+Microsoft is not responsible for, nor does it grant any licenses to, third-party packages.
+Downloading https://github.com/git-for-windows/git/releases/download/v2.43.0.windows.1/Git-2.43.0-64-bit.exe
+  ██████████████████████████████  58.3 MB / 58.3 MB
+Successfully verified installer hash
+Starting package install...
+Successfully installed
+";
+        let winget_upgrade = "\
+Found Microsoft Edge [Microsoft.Edge] Version 121.0.2277.83
+Downloading https://msedge.sf.dl.delivery.mp.microsoft.com/filestreamingservice/files/MicrosoftEdgeEnterpriseX64.msi
+Successfully installed
+";
+        let upgraded_line = "jq upgraded to 1.7.1 from 1.6";
+
+        assert_eq!(parse_version(choco_install).as_deref(), Some("2.43.0"));
+        assert_eq!(parse_version(choco_upgrade).as_deref(), Some("2.43.0"));
+        assert_eq!(parse_version(winget_install).as_deref(), Some("2.43.0"));
+        assert_eq!(parse_version(winget_upgrade).as_deref(), Some("121.0.2277.83"));
+        assert_eq!(parse_version(upgraded_line).as_deref(), Some("1.7.1"));
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    #[test]
+    fn ignores_banners_and_unrelated_numbers() {
+        assert_eq!(parse_version("Chocolatey v2.2.2\nChocolatey installed 0/0 packages.\n"), None);
+        assert_eq!(
+            parse_version("Downloading https://example.com/tool-1.2.3.exe\n  ██████  12.0 MB / 50.3 MB\n"),
+            None
+        );
+    }
+}
@@ -0,0 +1,125 @@
+use std::cmp::Ordering;
+
+/// Compare two package versions.
+///
+/// Strict semver is compared by precedence with the `semver` crate, so
+/// build metadata ("+build.5") doesn't count. Anything else, like
+/// choco/winget's four-part "1.2.3.4", is compared component by component:
+/// numerically where both sides are numbers, missing components count as 0,
+/// and a pre-release ("1.2.0-beta") sorts before its release.
+pub fn compare(a: &str, b: &str) -> Ordering {
+    let a = a.trim().trim_start_matches(['v', 'V']);
+    let b = b.trim().trim_start_matches(['v', 'V']);
+
+    if let (Ok(a), Ok(b)) = (semver::Version::parse(a), semver::Version::parse(b)) {
+        return a.cmp_precedence(&b);
+    }
+
+    let (a_release, a_pre) = split_pre_release(a);
+    let (b_release, b_pre) = split_pre_release(b);
+
+    compare_components(a_release, b_release).then_with(|| match (a_pre, b_pre) {
+        (None, None) => Ordering::Equal,
+        (None, Some(_)) => Ordering::Greater,
+        (Some(_), None) => Ordering::Less,
+        (Some(a), Some(b)) => compare_components(a, b),
+    })
+}
+
+/// Whether `candidate` is a newer version than `current`
+pub fn is_newer(candidate: &str, current: &str) -> bool {
+    compare(candidate, current) == Ordering::Greater
+}
+
+/// Whether `version` is an actual version number rather than a placeholder
+/// like winget's "Unknown" or "< 1.0"
+pub fn is_comparable(version: &str) -> bool {
+    version.trim().trim_start_matches(['v', 'V']).starts_with(|c: char| c.is_ascii_digit())
+}
+
+/// Sort versions newest first, dropping duplicates
+#[cfg(not(target_os = "macos"))]
+pub fn sort_newest_first(versions: &mut Vec<String>) {
+    versions.sort_by(|a, b| compare(b, a));
+    versions.dedup_by(|a, b| compare(a, b) == Ordering::Equal);
+}
+
+/// Split "1.2.3-beta.1+build" into ("1.2.3", Some("beta.1")); build metadata is ignored
+fn split_pre_release(version: &str) -> (&str, Option<&str>) {
+    let version = version.split('+').next().unwrap_or(version);
+    match version.split_once('-') {
+        Some((release, pre)) => (release, Some(pre)),
+        None => (version, None),
+    }
+}
+
+fn compare_components(a: &str, b: &str) -> Ordering {
+    let a: Vec<&str> = a.split('.').collect();
+    let b: Vec<&str> = b.split('.').collect();
+
+    for i in 0..a.len().max(b.len()) {
+        let x = a.get(i).copied().unwrap_or("0");
+        let y = b.get(i).copied().unwrap_or("0");
+
+        let ordering = match (x.parse::<u64>(), y.parse::<u64>()) {
+            (Ok(x), Ok(y)) => x.cmp(&y),
+            // Numeric identifiers sort before alphanumeric ones, as in semver
+            (Ok(_), Err(_)) => Ordering::Less,
+            (Err(_), Ok(_)) => Ordering::Greater,
+            (Err(_), Err(_)) => x.cmp(y),
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+
+    Ordering::Equal
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compares_four_component_versions() {
+        assert!(is_newer("1.2.3.10", "1.2.3.9"));
+        assert!(is_newer("17.8.34330.188", "17.8.34316.72"));
+        assert!(is_newer("1.2.3.1", "1.2.3"));
+        assert_eq!(compare("1.2.3.0", "1.2.3"), Ordering::Equal);
+        assert_eq!(compare("v2.43.0", "2.43.0"), Ordering::Equal);
+        assert!(!is_newer("2.43.0", "2.43.0.1"));
+    }
+
+    #[test]
+    fn pre_releases_sort_before_their_release() {
+        assert!(is_newer("1.2.0", "1.2.0-beta.2"));
+        assert!(is_newer("1.2.0-beta.10", "1.2.0-beta.2"));
+        assert!(is_newer("1.2.0-rc.1", "1.2.0-beta.9"));
+        assert!(is_newer("1.2.0.1", "1.2.0.1-preview"));
+        assert_eq!(compare("1.2.0+build.5", "1.2.0"), Ordering::Equal);
+    }
+
+    #[test]
+    fn odd_components_still_order() {
+        assert!(is_newer("121.0.2277.83", "99.0.1150.36"));
+        assert!(is_newer("2024.01a", "2024.01"));
+        assert_eq!(compare("unknown", "unknown"), Ordering::Equal);
+    }
+
+    #[test]
+    fn placeholders_are_not_comparable() {
+        assert!(is_comparable("v2.43.0"));
+        assert!(is_comparable("17.8.34330.188"));
+        assert!(!is_comparable("Unknown"));
+        assert!(!is_comparable("< 5.17.5"));
+        assert!(!is_comparable(""));
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    #[test]
+    fn sorts_newest_first_without_duplicates() {
+        let mut versions = ["1.0", "2.0.0.1", "1.10", "2.0.0", "1.0.0", "2.0.0-rc.1"].map(str::to_string).to_vec();
+        sort_newest_first(&mut versions);
+        assert_eq!(versions, ["2.0.0.1", "2.0.0", "2.0.0-rc.1", "1.10", "1.0"]);
+    }
+}
@@ -1,164 +1,865 @@
 use super::types::*;
-use std::process::Command;
+use super::{exit_code_error, validate_download_dir, validate_local_file, validate_package_id, version};
+use super::status::{line_fraction, LineProgress, Phase, StatusSink, StatusTracker};
+use super::util::{clean_output, elapsed_ms, output_with_lines, parse_version, probe_version, run_powershell};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Instant, SystemTime};
+use tokio::sync::OnceCell;
 use tokio::process::Command as TokioCommand;
 
+/// Registers App Installer (which ships winget) for the current user, and
+/// downloads it from https://aka.ms/getwinget if it isn't on the machine
+const INSTALL_SCRIPT: &str = "$ErrorActionPreference = 'Stop'; \
+    try { Add-AppxPackage -RegisterByFamilyName -MainPackage Microsoft.DesktopAppInstaller_8wekyb3d8bbwe } catch {}; \
+    if (-not (Get-AppxPackage Microsoft.DesktopAppInstaller)) { \
+        $bundle = Join-Path $env:TEMP 'Microsoft.DesktopAppInstaller.msixbundle'; \
+        Invoke-WebRequest -Uri 'https://aka.ms/getwinget' -OutFile $bundle -UseBasicParsing; \
+        Add-AppxPackage -Path $bundle \
+    }";
+
+/// First winget release with the `repair` command
+const REPAIR_MIN_VERSION: &str = "1.7";
+
+/// First winget release with the `download` command
+const DOWNLOAD_MIN_VERSION: &str = "1.8";
+
+/// First winget release with the `configure` command
+const CONFIGURE_MIN_VERSION: &str = "1.6";
+
+/// First winget release whose `export` JSON `list_installed` reads instead of
+/// relying on the `list` table alone
+const JSON_EXPORT_MIN_VERSION: &str = "1.4";
+
+/// Numbers the temporary files `export` writes, so concurrent listings
+/// don't share one
+static EXPORT_SEQUENCE: AtomicUsize = AtomicUsize::new(0);
+
+/// `winget export` output (also what `winget import` reads), limited to the
+/// package ids and versions
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ExportFile {
+    #[serde(default)]
+    sources: Vec<ExportSource>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ExportSource {
+    #[serde(default)]
+    packages: Vec<ExportPackage>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ExportPackage {
+    package_identifier: String,
+    #[serde(default)]
+    version: Option<String>,
+}
+
+/// Schema of the configuration files `export_configuration` writes
+const CONFIGURATION_VERSION: &str = "0.2.0";
+const CONFIGURATION_SCHEMA: &str = "https://aka.ms/configuration-dsc-schema/0.2";
+
+/// DSC resource that installs a winget package
+const PACKAGE_RESOURCE: &str = "Microsoft.WinGet.DSC/WinGetPackage";
+
+/// A winget configuration (`configuration.dsc.yaml`), limited to what
+/// `export_configuration` writes
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ConfigurationFile {
+    properties: ConfigurationProperties,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ConfigurationProperties {
+    configuration_version: String,
+    resources: Vec<ConfigurationResource>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ConfigurationResource {
+    resource: String,
+    id: String,
+    directives: ResourceDirectives,
+    settings: PackageSettings,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ResourceDirectives {
+    description: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PackageSettings {
+    id: String,
+    source: String,
+}
+
 /// Winget package manager wrapper
 pub struct WingetManager {
     exe_path: String,
+    /// Detected Winget version, probed once on first use (`None` if not installed)
+    availability: OnceCell<Option<String>>,
+    status: StatusSink,
 }
 
 impl WingetManager {
     pub fn new() -> Self {
+        Self::with_path("winget")
+    }
+
+    /// Use a specific Winget executable instead of the one on PATH
+    pub fn with_path(path: impl Into<String>) -> Self {
         Self {
-            exe_path: "winget".to_string(),
+            exe_path: path.into(),
+            availability: OnceCell::new(),
+            status: StatusSink::default(),
         }
     }
 
+    /// Report operation progress through `status`
+    pub fn with_status(mut self, status: StatusSink) -> Self {
+        self.status = status;
+        self
+    }
+
+    /// Start reporting an operation's progress from winget's output
+    fn track(&self, operation: &str, package_id: &str) -> StatusTracker {
+        self.status.track(operation, package_id).with_parser(parse_progress)
+    }
+
     /// Check if Winget is installed
-    pub fn is_installed(&self) -> bool {
-        Command::new(&self.exe_path)
-            .arg("--version")
-            .output()
-            .is_ok()
+    pub async fn is_installed(&self) -> bool {
+        self.version().await.is_some()
+    }
+
+    /// Get the installed Winget version, probing only on first call
+    pub async fn version(&self) -> Option<String> {
+        self.availability
+            .get_or_init(|| probe_version(&self.exe_path))
+            .await
+            .clone()
+    }
+
+    /// Whether this winget can report installed packages as JSON through
+    /// `export`, so listing needn't depend on scraping the `list` table
+    pub async fn supports_json(&self) -> bool {
+        self.version()
+            .await
+            .is_some_and(|v| version::compare(&v, JSON_EXPORT_MIN_VERSION).is_ge())
+    }
+
+    /// Forget the cached availability, e.g. after Winget was installed mid-session
+    pub fn refresh_availability(&mut self) {
+        self.availability = OnceCell::new();
+    }
+
+    /// Install or re-register App Installer so winget becomes available.
+    /// Does nothing if winget already works.
+    ///
+    /// This downloads and installs an app package, so it must only be
+    /// triggered by an explicit user action.
+    pub async fn bootstrap(&mut self) -> Result<InstallResult, PackageError> {
+        if self.is_installed().await {
+            return Ok(InstallResult {
+                success: true,
+                package_id: "winget".to_string(),
+                version: self.version().await,
+                output: "Winget is already installed".to_string(),
+                error: None,
+                extra_args: Vec::new(),
+                duration_ms: 0,
+                forced: false,
+            });
+        }
+
+        // App Installer is per-user, so this doesn't need elevation
+        let started = Instant::now();
+        let output = run_powershell(INSTALL_SCRIPT, false).await?;
+        let stdout = clean_output(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+
+        self.refresh_availability();
+
+        let version = self.version().await;
+        let success = output.status.success() && version.is_some();
+
+        Ok(InstallResult {
+            success,
+            package_id: "winget".to_string(),
+            version,
+            output: stdout,
+            error: if success { None } else { Some(stderr) },
+            extra_args: Vec::new(),
+            duration_ms: elapsed_ms(started),
+            forced: false,
+        })
     }
 
-    /// Install a package
-    pub async fn install(&self, package_id: &str) -> Result<InstallResult, PackageError> {
-        if !self.is_installed() {
+    /// Install a package, optionally pinned to a specific version or taken
+    /// from a named source, with `extra_args` appended after the standard arguments
+    pub async fn install(&self, package_id: &str, options: &InstallOptions) -> Result<InstallResult, PackageError> {
+        validate_package_id(package_id)?;
+
+        if !self.is_installed().await {
             return Err(PackageError::NotFound(
                 "Winget is not installed".to_string(),
             ));
         }
 
-        let output = TokioCommand::new(&self.exe_path)
-            .args(&["install", "--id", package_id, "--silent", "--accept-package-agreements", "--accept-source-agreements"])
-            .output()
+        let version = options.version.as_deref();
+        let mut args = vec!["install", "--id", package_id, "--exact", "--silent", "--accept-package-agreements", "--accept-source-agreements"];
+        if let Some(version) = version {
+            args.extend(["--version", version]);
+        }
+        if let Some(catalog) = options.catalog.as_deref() {
+            args.extend(["--source", catalog]);
+        }
+        if options.force {
+            args.push("--force");
+        }
+        args.extend(options.extra_args.iter().map(String::as_str));
+
+        let started = Instant::now();
+        let mut tracker = self.track("install", package_id);
+        let output = output_with_lines(TokioCommand::new(&self.exe_path).args(&args), |line| tracker.line(line))
             .await
             .map_err(|e| PackageError::CommandFailed(e.to_string()))?;
+        tracker.finish(output.status.success());
 
-        let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+        let stdout = clean_output(&output.stdout);
         let stderr = String::from_utf8_lossy(&output.stderr).to_string();
         let success = output.status.success();
 
-        // Parse version from output
-        let version = Self::parse_version_from_output(&stdout);
+        if Self::reports_already_installed(&stdout) {
+            return Err(PackageError::AlreadyInstalled(package_id.to_string()));
+        }
+
+        if Self::reports_multiple_matches(&stdout) {
+            return Err(Self::ambiguous_id(package_id));
+        }
+
+        if let Some(error) = exit_code_error(&output.status, &stderr, package_id) {
+            return Err(error);
+        }
+
+        // Parse version from output, falling back to the one requested
+        let installed_version = parse_version(&stdout)
+            .or_else(|| version.map(str::to_string));
 
         Ok(InstallResult {
             success,
             package_id: package_id.to_string(),
-            version,
+            version: installed_version,
             output: stdout,
             error: if success { None } else { Some(stderr) },
+            extra_args: options.extra_args.clone(),
+            duration_ms: elapsed_ms(started),
+            forced: options.force,
         })
     }
 
-    /// Uninstall a package
-    pub async fn uninstall(&self, package_id: &str) -> Result<UninstallResult, PackageError> {
-        if !self.is_installed() {
+    /// Install from a local manifest file or directory (`winget install --manifest`).
+    ///
+    /// Winget only accepts this once local manifests are enabled with
+    /// `winget settings --enable LocalManifestFiles`.
+    pub async fn install_manifest(&self, path: &Path) -> Result<InstallResult, PackageError> {
+        if !self.is_installed().await {
             return Err(PackageError::NotFound(
                 "Winget is not installed".to_string(),
             ));
         }
 
+        if !path.is_dir() {
+            validate_local_file(path, &["yaml", "yml"])?;
+        }
+        let manifest = path.to_string_lossy().to_string();
+
+        let started = Instant::now();
         let output = TokioCommand::new(&self.exe_path)
-            .args(&["uninstall", "--id", package_id, "--silent"])
+            .args(["install", "--manifest", &manifest, "--silent", "--accept-package-agreements", "--accept-source-agreements"])
             .output()
             .await
             .map_err(|e| PackageError::CommandFailed(e.to_string()))?;
 
-        let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+        let stdout = clean_output(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+        let success = output.status.success();
+
+        if let Some(error) = exit_code_error(&output.status, &stderr, &manifest) {
+            return Err(error);
+        }
+
+        Ok(InstallResult {
+            success,
+            package_id: manifest,
+            version: parse_version(&stdout),
+            output: stdout,
+            error: if success { None } else { Some(stderr) },
+            extra_args: Vec::new(),
+            duration_ms: elapsed_ms(started),
+            forced: false,
+        })
+    }
+
+    /// Preview an install. Winget has no dry run, so the would-be version
+    /// comes from `winget show`.
+    pub async fn preview_install(&self, package_id: &str) -> Result<PackagePreview, PackageError> {
+        validate_package_id(package_id)?;
+
+        if let Some(current) = self.installed_version(package_id).await? {
+            return Ok(PackagePreview {
+                package_id: package_id.to_string(),
+                action: PreviewAction::AlreadyInstalled,
+                from_version: Some(current.clone()),
+                to_version: Some(current),
+            });
+        }
+
+        let details = self.show(package_id).await?;
+
+        Ok(PackagePreview {
+            package_id: package_id.to_string(),
+            action: PreviewAction::Install,
+            from_version: None,
+            to_version: details.version,
+        })
+    }
+
+    /// Preview an upgrade from the versions `winget upgrade` reports
+    pub async fn preview_upgrade(&self, package_id: &str) -> Result<PackagePreview, PackageError> {
+        validate_package_id(package_id)?;
+
+        let current = self
+            .installed_version(package_id)
+            .await?
+            .ok_or_else(|| PackageError::NotInstalled(package_id.to_string()))?;
+
+        let available = self
+            .list_upgradable()
+            .await?
+            .into_iter()
+            .find(|p| p.id.eq_ignore_ascii_case(package_id))
+            .map(|p| p.available_version);
+
+        Ok(PackagePreview {
+            package_id: package_id.to_string(),
+            action: if available.is_some() { PreviewAction::Upgrade } else { PreviewAction::UpToDate },
+            to_version: available.or_else(|| Some(current.clone())),
+            from_version: Some(current),
+        })
+    }
+
+    /// Installed version of a package, if it's installed
+    async fn installed_version(&self, package_id: &str) -> Result<Option<String>, PackageError> {
+        Ok(self
+            .list_installed(None)
+            .await?
+            .into_iter()
+            .find(|p| p.id.eq_ignore_ascii_case(package_id))
+            .map(|p| p.version))
+    }
+
+    /// Whether install output shows the package was already present and nothing changed.
+    ///
+    /// Recent winget treats installing an existing package as an upgrade
+    /// attempt, so only report it when no newer version was available.
+    fn reports_already_installed(output: &str) -> bool {
+        output.contains("Found an existing package already installed")
+            && (output.contains("No available upgrade found")
+                || output.contains("No newer package versions are available"))
+    }
+
+    /// Uninstall a package
+    ///
+    /// With `purge`, winget also deletes a portable package's folder including
+    /// anything the app wrote there; it has no purge for other installer types.
+    pub async fn uninstall(&self, package_id: &str, purge: bool) -> Result<UninstallResult, PackageError> {
+        validate_package_id(package_id)?;
+
+        if !self.is_installed().await {
+            return Err(PackageError::NotFound(
+                "Winget is not installed".to_string(),
+            ));
+        }
+
+        let mut args = vec!["uninstall", "--id", package_id, "--exact", "--silent"];
+        if purge {
+            args.push("--purge");
+        }
+        let portable_dirs = if purge { Self::portable_package_dirs(package_id) } else { Vec::new() };
+
+        let started = Instant::now();
+        let mut tracker = self.track("uninstall", package_id);
+        let output = output_with_lines(
+            TokioCommand::new(&self.exe_path).args(&args),
+            |line| tracker.line(line),
+        )
+        .await
+        .map_err(|e| PackageError::CommandFailed(e.to_string()))?;
+        tracker.finish(output.status.success());
+
+        let stdout = clean_output(&output.stdout);
         let stderr = String::from_utf8_lossy(&output.stderr).to_string();
         let success = output.status.success();
 
+        if Self::reports_not_installed(&stdout) || Self::reports_not_installed(&stderr) {
+            return Err(PackageError::NotInstalled(package_id.to_string()));
+        }
+
+        if Self::reports_multiple_matches(&stdout) {
+            return Err(Self::ambiguous_id(package_id));
+        }
+
+        if let Some(error) = exit_code_error(&output.status, &stderr, package_id) {
+            return Err(error);
+        }
+
+        let removed = portable_dirs
+            .into_iter()
+            .filter(|dir| !dir.exists())
+            .map(|dir| dir.display().to_string())
+            .collect();
+
         Ok(UninstallResult {
             success,
             package_id: package_id.to_string(),
             output: stdout,
             error: if success { None } else { Some(stderr) },
+            removed,
+            duration_ms: elapsed_ms(started),
         })
     }
 
-    /// List installed packages
-    pub async fn list_installed(&self) -> Result<Vec<InstalledPackage>, PackageError> {
-        if !self.is_installed() {
+    /// Folders winget extracted a portable package into, named
+    /// `<id>_<source>_<hash>` under `%LOCALAPPDATA%\Microsoft\WinGet\Packages`
+    fn portable_package_dirs(package_id: &str) -> Vec<PathBuf> {
+        let Some(packages_dir) = dirs::data_local_dir().map(|dir| dir.join("Microsoft").join("WinGet").join("Packages"))
+        else {
+            return Vec::new();
+        };
+        let prefix = format!("{}_", package_id.to_lowercase());
+
+        std::fs::read_dir(packages_dir)
+            .map(|entries| {
+                entries
+                    .flatten()
+                    .filter(|entry| entry.file_name().to_string_lossy().to_lowercase().starts_with(&prefix))
+                    .map(|entry| entry.path())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Whether winget refused to act because the id matched several packages.
+    /// `--exact` rules out prefix matches, but the same id can still exist in
+    /// more than one source (e.g. winget and msstore).
+    fn reports_multiple_matches(output: &str) -> bool {
+        output.contains("Multiple packages found matching input criteria")
+    }
+
+    /// Error for an id that didn't identify a single package
+    fn ambiguous_id(package_id: &str) -> PackageError {
+        PackageError::NotFound(format!(
+            "{}: multiple packages match this id",
+            package_id
+        ))
+    }
+
+    /// Whether uninstall output shows there was nothing to remove
+    fn reports_not_installed(output: &str) -> bool {
+        output.contains("No installed package found matching input criteria")
+    }
+
+    /// List installed packages, only those matching `filter` if given
+    /// (winget's `--query`, which checks names, ids and monikers).
+    ///
+    /// The `list` table is enough unless it cut an id or version short; only
+    /// then is the slower `winget export` run for the exact values.
+    pub async fn list_installed(&self, filter: Option<&str>) -> Result<Vec<InstalledPackage>, PackageError> {
+        let listed = self.list_table(filter).await?;
+        if filter.is_some() || !listed.iter().any(Self::is_truncated) || !self.supports_json().await {
+            return Ok(listed);
+        }
+
+        match self.export_installed().await {
+            Ok(exported) => Ok(Self::merge_exported(listed, exported)),
+            Err(e) => {
+                tracing::warn!("Using the winget list table alone; export failed: {}", e);
+                Ok(listed)
+            }
+        }
+    }
+
+    /// Count installed packages from the `list` table alone, skipping the
+    /// export `list_installed` adds for exact ids and versions
+    pub async fn count_installed(&self) -> Result<usize, PackageError> {
+        Ok(self.list_table(None).await?.len())
+    }
+
+    /// Installed packages as the `list` table shows them
+    async fn list_table(&self, filter: Option<&str>) -> Result<Vec<InstalledPackage>, PackageError> {
+        if !self.is_installed().await {
             return Err(PackageError::NotFound(
                 "Winget is not installed".to_string(),
             ));
         }
 
+        let mut args = vec!["list"];
+        if let Some(filter) = filter {
+            args.extend(["--query", filter]);
+        }
+
         let output = TokioCommand::new(&self.exe_path)
-            .args(&["list"])
+            .args(args)
             .output()
             .await
             .map_err(|e| PackageError::CommandFailed(e.to_string()))?;
 
+        let stdout = clean_output(&output.stdout);
+
+        // A query nothing matches fails rather than printing an empty table
+        if filter.is_some() && Self::reports_not_installed(&stdout) {
+            return Ok(Vec::new());
+        }
+
         if !output.status.success() {
             return Err(PackageError::CommandFailed(
                 String::from_utf8_lossy(&output.stderr).to_string(),
             ));
         }
 
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let mut packages = Vec::new();
+        Ok(Self::parse_list_output(&stdout))
+    }
+
+    /// Installed packages with their versions from `winget export`
+    async fn export_installed(&self) -> Result<Vec<ExportPackage>, PackageError> {
+        let path = std::env::temp_dir().join(format!(
+            "savvy-winget-export-{}-{}.json",
+            std::process::id(),
+            EXPORT_SEQUENCE.fetch_add(1, Ordering::Relaxed)
+        ));
+
+        let output = TokioCommand::new(&self.exe_path)
+            .args(["export", "--include-versions", "--accept-source-agreements", "-o"])
+            .arg(&path)
+            .output()
+            .await
+            .map_err(|e| PackageError::CommandFailed(e.to_string()));
+
+        // Export exits non-zero when some packages couldn't be exported, but
+        // still writes the rest, so the file decides
+        let exported = std::fs::read_to_string(&path);
+        let _ = std::fs::remove_file(&path);
+        let output = output?;
+
+        let json = exported.map_err(|_| {
+            PackageError::CommandFailed(String::from_utf8_lossy(&output.stderr).to_string())
+        })?;
+        let file: ExportFile = serde_json::from_str(&json)
+            .map_err(|e| PackageError::Unknown(format!("Invalid winget export: {}", e)))?;
+
+        Ok(file.sources.into_iter().flat_map(|source| source.packages).collect())
+    }
+
+    /// Write installed packages to `path` with `winget export`, in the JSON
+    /// format `winget import` (and `import_native`) reads back.
+    ///
+    /// Only packages winget can match to a source are exported; the rest are
+    /// left out, as the winget CLI does.
+    pub async fn export_native(&self, path: &Path) -> Result<(), PackageError> {
+        if !self.is_installed().await {
+            return Err(PackageError::NotFound(
+                "Winget is not installed".to_string(),
+            ));
+        }
+
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            if !dir.is_dir() {
+                return Err(PackageError::InvalidInput(format!("Not a directory: {}", dir.display())));
+            }
+        }
+
+        let output = TokioCommand::new(&self.exe_path)
+            .args(["export", "--include-versions", "--accept-source-agreements", "-o"])
+            .arg(path)
+            .output()
+            .await
+            .map_err(|e| PackageError::CommandFailed(e.to_string()))?;
+
+        // Non-zero when some packages couldn't be exported; the rest still are
+        if !path.is_file() {
+            return Err(PackageError::CommandFailed(
+                String::from_utf8_lossy(&output.stderr).to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Install the packages in a `winget export` file with `winget import`,
+    /// one result per package in the file.
+    ///
+    /// Packages that are already installed are reported as successful, like
+    /// `import_manifest` does. Unavailable packages are skipped instead of
+    /// stopping the import. Winget doesn't time packages separately, so
+    /// `duration_ms` is left at zero.
+    pub async fn import_native(&self, path: &Path) -> Result<Vec<InstallResult>, PackageError> {
+        if !self.is_installed().await {
+            return Err(PackageError::NotFound(
+                "Winget is not installed".to_string(),
+            ));
+        }
+
+        validate_local_file(path, &["json"])?;
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| PackageError::Unknown(format!("Failed to read {}: {}", path.display(), e)))?;
+        let file: ExportFile = serde_json::from_str(&contents)
+            .map_err(|e| PackageError::InvalidInput(format!("{} is not a winget export file: {}", path.display(), e)))?;
+        let packages: Vec<ExportPackage> = file.sources.into_iter().flat_map(|source| source.packages).collect();
 
-        // Skip header lines
-        let lines: Vec<&str> = stdout.lines().skip(2).collect();
+        let mut tracker = self.track("import", &path.to_string_lossy());
+        let output = output_with_lines(
+            TokioCommand::new(&self.exe_path)
+                .args([
+                    "import",
+                    "--ignore-unavailable",
+                    "--accept-package-agreements",
+                    "--accept-source-agreements",
+                    "-i",
+                ])
+                .arg(path),
+            |line| tracker.line(line),
+        )
+        .await
+        .map_err(|e| PackageError::CommandFailed(e.to_string()))?;
+        tracker.finish(output.status.success());
 
-        for line in lines {
-            if line.trim().is_empty() || line.starts_with('-') {
+        let stdout = clean_output(&output.stdout);
+        let results = Self::parse_import_output(&stdout, &packages);
+
+        // Failed before reaching any package, e.g. the sources couldn't be opened
+        if !output.status.success() && results.iter().all(|result| result.output.is_empty()) {
+            let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+            return Err(PackageError::CommandFailed(if stderr.trim().is_empty() { stdout } else { stderr }));
+        }
+
+        Ok(results)
+    }
+
+    /// Per-package outcomes from `winget import` output.
+    ///
+    /// A line naming a package from the file, in brackets ("Found Git
+    /// [Git.Git] Version 2.43.0") or after a colon ("Package is already
+    /// installed: Git.Git"), starts that package's section; outcome lines
+    /// after it apply to it. Packages winget never mentioned are failures.
+    fn parse_import_output(output: &str, packages: &[ExportPackage]) -> Vec<InstallResult> {
+        let mut results: Vec<InstallResult> = packages
+            .iter()
+            .map(|package| InstallResult {
+                success: false,
+                package_id: package.package_identifier.clone(),
+                version: package.version.clone(),
+                output: String::new(),
+                error: Some("winget reported no result for this package".to_string()),
+                extra_args: Vec::new(),
+                duration_ms: 0,
+                forced: false,
+            })
+            .collect();
+        let mut current: Option<usize> = None;
+
+        for line in output.lines() {
+            let trimmed = line.trim();
+            if trimmed.is_empty() {
                 continue;
             }
 
-            // Parse winget list output format: Name   Id   Version   Source
-            let parts: Vec<&str> = line.split_whitespace().collect();
-            if parts.len() >= 3 {
-                // Find the ID (looks like Publisher.AppName)
-                let id_index = parts.iter().position(|&p| p.contains('.'));
-                if let Some(idx) = id_index {
-                    let id = parts[idx].to_string();
-                    let version = parts.get(idx + 1).unwrap_or(&"unknown").to_string();
-                    let name = parts[..idx].join(" ");
-
-                    packages.push(InstalledPackage {
-                        id: id.clone(),
-                        version,
-                        source: PackageSource::Winget,
-                        name: Some(if name.is_empty() { id } else { name }),
-                    });
-                }
+            if let Some(i) = packages
+                .iter()
+                .position(|package| Self::names_package(trimmed, &package.package_identifier))
+            {
+                current = Some(i);
+            }
+            let Some(i) = current else {
+                continue;
+            };
+
+            let result = &mut results[i];
+            if !result.output.is_empty() {
+                result.output.push('\n');
             }
+            result.output.push_str(trimmed);
+
+            let lower = trimmed.to_lowercase();
+            if lower.contains("successfully installed") || lower.contains("already installed") {
+                result.success = true;
+                result.error = None;
+            } else if lower.contains("not found") || lower.contains("not available") || lower.contains("failed") {
+                result.success = false;
+                result.error = Some(trimmed.to_string());
+            }
+        }
+
+        results
+    }
+
+    /// Whether an import output line names `id`: "[Git.Git]", or ": Git.Git" at the end
+    fn names_package(line: &str, id: &str) -> bool {
+        let lower = line.to_lowercase();
+        let id = id.to_lowercase();
+        lower.contains(&format!("[{}]", id))
+            || lower
+                .rsplit_once(": ")
+                .is_some_and(|(_, named)| named.trim() == id)
+    }
+
+    /// Take ids and versions from `winget export`, which can't be cut short
+    /// or misaligned the way table columns can. The `list` table still
+    /// supplies display names, and the packages export leaves out because no
+    /// source provides them (e.g. apps installed outside winget).
+    fn merge_exported(mut listed: Vec<InstalledPackage>, exported: Vec<ExportPackage>) -> Vec<InstalledPackage> {
+        let mut merged = Vec::with_capacity(listed.len().max(exported.len()));
+
+        for package in exported {
+            let row = listed
+                .iter()
+                .position(|row| Self::same_listed_id(&row.id, &package.package_identifier))
+                .map(|i| listed.remove(i));
+
+            let version = package
+                .version
+                .filter(|v| !v.is_empty())
+                .or_else(|| row.as_ref().map(|row| row.version.clone()))
+                .unwrap_or_else(|| "unknown".to_string());
+
+            merged.push(InstalledPackage {
+                name: row
+                    .and_then(|row| row.name)
+                    .or_else(|| Some(package.package_identifier.clone())),
+                id: package.package_identifier,
+                version,
+                source: PackageSource::Winget,
+            });
+        }
+
+        merged.extend(listed);
+        merged
+    }
+
+    /// Whether the `list` table cut a package's id or version short
+    fn is_truncated(package: &InstalledPackage) -> bool {
+        package.id.ends_with('…') || package.version.ends_with('…')
+    }
+
+    /// Whether a `list` table id is `id`, allowing for the table cutting
+    /// long ids short with a trailing ellipsis
+    fn same_listed_id(listed: &str, id: &str) -> bool {
+        if listed.eq_ignore_ascii_case(id) {
+            return true;
+        }
+        listed
+            .strip_suffix('…')
+            .is_some_and(|prefix| id.to_lowercase().starts_with(&prefix.to_lowercase()))
+    }
+
+    /// Parse `winget list` output (Name, Id, Version, [Available], [Source]).
+    ///
+    /// Uses the header's column offsets, so names containing dots or spaces
+    /// (e.g. "Node.js", "Microsoft Edge") don't shift the id/version split.
+    fn parse_list_output(output: &str) -> Vec<InstalledPackage> {
+        let (headers, rows) = parse_table(output);
+        if headers.len() < 3 {
+            return Vec::new();
         }
 
-        Ok(packages)
+        rows.into_iter()
+            .filter_map(|row| {
+                let id = row.get(1).filter(|id| !id.is_empty())?.clone();
+                let name = row.first().filter(|n| !n.is_empty()).cloned();
+                // "< 1.2.3" marks a version winget could only bound from above,
+                // so it's kept as is rather than passed off as 1.2.3
+                let version = row
+                    .get(2)
+                    .map(|v| v.trim())
+                    .filter(|v| !v.is_empty())
+                    .unwrap_or("unknown")
+                    .to_string();
+
+                Some(InstalledPackage {
+                    name: Some(name.unwrap_or_else(|| id.clone())),
+                    id,
+                    version,
+                    source: PackageSource::Winget,
+                })
+            })
+            .collect()
     }
 
     /// Upgrade a package
     pub async fn upgrade(&self, package_id: &str) -> Result<UpgradeResult, PackageError> {
-        if !self.is_installed() {
+        validate_package_id(package_id)?;
+
+        if !self.is_installed().await {
             return Err(PackageError::NotFound(
                 "Winget is not installed".to_string(),
             ));
         }
 
         // Get current version first
-        let installed = self.list_installed().await?;
+        let installed = self.list_installed(None).await?;
         let old_version = installed
             .iter()
             .find(|p| p.id == package_id)
             .map(|p| p.version.clone());
 
-        let output = TokioCommand::new(&self.exe_path)
-            .args(&["upgrade", "--id", package_id, "--silent", "--accept-package-agreements"])
-            .output()
-            .await
-            .map_err(|e| PackageError::CommandFailed(e.to_string()))?;
+        let started = Instant::now();
+        let mut tracker = self.track("upgrade", package_id);
+        let output = output_with_lines(
+            TokioCommand::new(&self.exe_path).args(["upgrade", "--id", package_id, "--exact", "--silent", "--accept-package-agreements"]),
+            |line| tracker.line(line),
+        )
+        .await
+        .map_err(|e| PackageError::CommandFailed(e.to_string()))?;
+        tracker.finish(output.status.success());
 
-        let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+        let stdout = clean_output(&output.stdout);
         let stderr = String::from_utf8_lossy(&output.stderr).to_string();
         let success = output.status.success();
 
+        if Self::reports_multiple_matches(&stdout) {
+            return Err(Self::ambiguous_id(package_id));
+        }
+
+        // Nothing newer to install: report a no-op rather than a failure
+        if output.status.code().map(|code| code as u32) == Some(WINGET_UPDATE_NOT_APPLICABLE) {
+            return Ok(UpgradeResult {
+                success: true,
+                package_id: package_id.to_string(),
+                new_version: old_version.clone(),
+                old_version,
+                output: stdout,
+                error: None,
+                duration_ms: elapsed_ms(started),
+            });
+        }
+
+        if let Some(error) = exit_code_error(&output.status, &stderr, package_id) {
+            return Err(error);
+        }
+
         // Parse new version from output
-        let new_version = Self::parse_version_from_output(&stdout);
+        let new_version = parse_version(&stdout);
 
         Ok(UpgradeResult {
             success,
@@ -167,29 +868,1481 @@ impl WingetManager {
             new_version,
             output: stdout,
             error: if success { None } else { Some(stderr) },
+            duration_ms: elapsed_ms(started),
         })
     }
 
-    /// Parse version number from command output
-    fn parse_version_from_output(output: &str) -> Option<String> {
-        // Look for version patterns in winget output
-        for line in output.lines() {
-            if line.contains("Successfully installed") || line.contains("upgraded") {
-                let words: Vec<&str> = line.split_whitespace().collect();
-                // Version is usually after "version" keyword or in format X.Y.Z
-                for word in words {
-                    if word.chars().any(|c| c == '.') && word.chars().any(|c| c.is_numeric()) {
-                        return Some(word.to_string());
-                    }
-                }
-            }
+    /// Install an older version over the current one. Winget allows this
+    /// through a plain `install --version`.
+    pub async fn downgrade(&self, package_id: &str, version: &str) -> Result<UpgradeResult, PackageError> {
+        validate_package_id(package_id)?;
+
+        if !self.is_installed().await {
+            return Err(PackageError::NotFound(
+                "Winget is not installed".to_string(),
+            ));
+        }
+
+        let old_version = self.installed_version(package_id).await?;
+
+        let started = Instant::now();
+        let mut tracker = self.track("downgrade", package_id);
+        let output = output_with_lines(
+            TokioCommand::new(&self.exe_path).args([
+                "install",
+                "--id",
+                package_id,
+                "--exact",
+                "--version",
+                version,
+                "--silent",
+                "--accept-package-agreements",
+                "--accept-source-agreements",
+            ]),
+            |line| tracker.line(line),
+        )
+        .await
+        .map_err(|e| PackageError::CommandFailed(e.to_string()))?;
+        tracker.finish(output.status.success());
+
+        let stdout = clean_output(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+        let success = output.status.success();
+
+        if Self::reports_multiple_matches(&stdout) {
+            return Err(Self::ambiguous_id(package_id));
+        }
+
+        if let Some(error) = exit_code_error(&output.status, &stderr, package_id) {
+            return Err(error);
         }
-        None
+
+        Ok(UpgradeResult {
+            success,
+            package_id: package_id.to_string(),
+            old_version,
+            new_version: parse_version(&stdout).or_else(|| Some(version.to_string())),
+            output: stdout,
+            error: if success { None } else { Some(stderr) },
+            duration_ms: elapsed_ms(started),
+        })
     }
-}
 
-impl Default for WingetManager {
-    fn default() -> Self {
-        Self::new()
+    /// Repair an installed package with `winget repair`, which runs the
+    /// installer's own repair (MSI repair, reinstall of the same version, ...).
+    /// Needs winget 1.7 or later.
+    pub async fn repair(&self, package_id: &str) -> Result<RepairResult, PackageError> {
+        validate_package_id(package_id)?;
+
+        let Some(winget_version) = self.version().await else {
+            return Err(PackageError::NotFound(
+                "Winget is not installed".to_string(),
+            ));
+        };
+
+        if version::compare(&winget_version, REPAIR_MIN_VERSION).is_lt() {
+            return Err(Self::repair_unsupported(&winget_version));
+        }
+
+        let mut tracker = self.track("repair", package_id);
+        let output = output_with_lines(
+            TokioCommand::new(&self.exe_path).args([
+                "repair",
+                "--id",
+                package_id,
+                "--exact",
+                "--silent",
+                "--accept-package-agreements",
+                "--accept-source-agreements",
+            ]),
+            |line| tracker.line(line),
+        )
+        .await
+        .map_err(|e| PackageError::CommandFailed(e.to_string()))?;
+        tracker.finish(output.status.success());
+
+        let stdout = clean_output(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+        let success = output.status.success();
+
+        // The version probe said "unknown" and this winget predates `repair`
+        if stdout.contains("Unrecognized command") {
+            return Err(Self::repair_unsupported(&winget_version));
+        }
+
+        if Self::reports_multiple_matches(&stdout) {
+            return Err(Self::ambiguous_id(package_id));
+        }
+
+        if let Some(error) = exit_code_error(&output.status, &stderr, package_id) {
+            return Err(error);
+        }
+
+        Ok(RepairResult {
+            success,
+            package_id: package_id.to_string(),
+            version: self.installed_version(package_id).await.ok().flatten(),
+            output: stdout,
+            error: if success { None } else { Some(stderr) },
+        })
+    }
+
+    fn repair_unsupported(winget_version: &str) -> PackageError {
+        PackageError::InvalidInput(format!(
+            "winget {} doesn't support repair; update App Installer to get winget {} or later",
+            winget_version, REPAIR_MIN_VERSION
+        ))
+    }
+
+    /// Download a package's installer and manifest into `dest_dir` with
+    /// `winget download`, without installing it. Needs winget 1.8 or later.
+    pub async fn download(&self, package_id: &str, dest_dir: &Path) -> Result<DownloadResult, PackageError> {
+        validate_package_id(package_id)?;
+
+        let Some(winget_version) = self.version().await else {
+            return Err(PackageError::NotFound(
+                "Winget is not installed".to_string(),
+            ));
+        };
+
+        if version::compare(&winget_version, DOWNLOAD_MIN_VERSION).is_lt() {
+            return Err(Self::download_unsupported(&winget_version));
+        }
+
+        validate_download_dir(dest_dir)?;
+        let started = SystemTime::now();
+
+        let mut tracker = self.track("download", package_id);
+        let output = output_with_lines(
+            TokioCommand::new(&self.exe_path)
+                .args([
+                    "download",
+                    "--id",
+                    package_id,
+                    "--exact",
+                    "--accept-package-agreements",
+                    "--accept-source-agreements",
+                    "--download-directory",
+                ])
+                .arg(dest_dir),
+            |line| tracker.line(line),
+        )
+        .await
+        .map_err(|e| PackageError::CommandFailed(e.to_string()))?;
+        tracker.finish(output.status.success());
+
+        let stdout = clean_output(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+        let success = output.status.success();
+
+        // The version probe said "unknown" and this winget predates `download`
+        if stdout.contains("Unrecognized command") {
+            return Err(Self::download_unsupported(&winget_version));
+        }
+
+        if Self::reports_multiple_matches(&stdout) {
+            return Err(Self::ambiguous_id(package_id));
+        }
+
+        if let Some(error) = exit_code_error(&output.status, &stderr, package_id) {
+            return Err(error);
+        }
+
+        Ok(DownloadResult {
+            success,
+            package_id: package_id.to_string(),
+            directory: dest_dir.to_string_lossy().to_string(),
+            files: if success { Self::written_since(dest_dir, started) } else { Vec::new() },
+            output: stdout,
+            error: if success { None } else { Some(stderr) },
+        })
+    }
+
+    fn download_unsupported(winget_version: &str) -> PackageError {
+        PackageError::InvalidInput(format!(
+            "winget {} doesn't support download; update App Installer to get winget {} or later",
+            winget_version, DOWNLOAD_MIN_VERSION
+        ))
+    }
+
+    /// Files in `dir` written at or after `since`. Winget names the installer
+    /// and manifest after the package, version and architecture, and
+    /// overwrites them on a repeat download, so this is more reliable than
+    /// guessing the names.
+    fn written_since(dir: &Path, since: SystemTime) -> Vec<String> {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return Vec::new();
+        };
+
+        let mut files: Vec<String> = entries
+            .flatten()
+            .filter(|entry| {
+                entry
+                    .metadata()
+                    .ok()
+                    .filter(|metadata| metadata.is_file())
+                    .and_then(|metadata| metadata.modified().ok())
+                    .is_some_and(|modified| modified >= since)
+            })
+            .map(|entry| entry.path().to_string_lossy().to_string())
+            .collect();
+        files.sort();
+        files
+    }
+
+    /// Render the installed packages that came from the winget catalog as a
+    /// winget configuration file, for `winget configure` on another machine.
+    ///
+    /// The YAML is parsed back and checked before it's returned, so a file
+    /// winget would reject is never written. Ids the `list` table cut short
+    /// are completed from `winget export`; any it can't complete are left out
+    /// and named in a comment at the top of the file.
+    pub async fn export_configuration(&self) -> Result<String, PackageError> {
+        if !self.is_installed().await {
+            return Err(PackageError::NotFound(
+                "Winget is not installed".to_string(),
+            ));
+        }
+
+        // Only packages winget can match to its catalog can be reinstalled
+        let output = TokioCommand::new(&self.exe_path)
+            .args(["list", "--source", "winget", "--accept-source-agreements"])
+            .output()
+            .await
+            .map_err(|e| PackageError::CommandFailed(e.to_string()))?;
+
+        if !output.status.success() {
+            return Err(PackageError::CommandFailed(
+                String::from_utf8_lossy(&output.stderr).to_string(),
+            ));
+        }
+
+        let listed = Self::parse_list_output(&clean_output(&output.stdout));
+        let exported = if listed.iter().any(|pkg| pkg.id.ends_with('…')) {
+            self.export_installed().await.unwrap_or_else(|e| {
+                tracing::warn!("Can't complete truncated ids; export failed: {}", e);
+                Vec::new()
+            })
+        } else {
+            Vec::new()
+        };
+        let (mut installed, skipped) = Self::complete_truncated_ids(listed, &exported);
+        if !skipped.is_empty() {
+            tracing::warn!("Leaving truncated ids out of the configuration: {}", skipped.join(", "));
+        }
+        installed.sort_by_key(|pkg| pkg.id.to_lowercase());
+        installed.dedup_by(|a, b| a.id.eq_ignore_ascii_case(&b.id));
+
+        let configuration = ConfigurationFile {
+            properties: ConfigurationProperties {
+                configuration_version: CONFIGURATION_VERSION.to_string(),
+                resources: installed
+                    .into_iter()
+                    .map(|pkg| ConfigurationResource {
+                        resource: PACKAGE_RESOURCE.to_string(),
+                        directives: ResourceDirectives {
+                            description: format!("Install {}", pkg.name.as_deref().unwrap_or(&pkg.id)),
+                        },
+                        settings: PackageSettings {
+                            id: pkg.id.clone(),
+                            source: "winget".to_string(),
+                        },
+                        id: pkg.id,
+                    })
+                    .collect(),
+            },
+        };
+
+        let yaml = serde_yaml::to_string(&configuration)
+            .map_err(|e| PackageError::Unknown(format!("Failed to serialize configuration: {}", e)))?;
+        Self::validate_configuration(&yaml)?;
+
+        let skipped: String = skipped
+            .iter()
+            .map(|id| format!("# Skipped, id cut short by winget: {}\n", id))
+            .collect();
+        Ok(format!("# yaml-language-server: $schema={}\n{}{}", CONFIGURATION_SCHEMA, skipped, yaml))
+    }
+
+    /// Replace ids the `list` table cut short with the full ones from
+    /// `exported`, returning the packages and the ids that couldn't be completed
+    fn complete_truncated_ids(
+        listed: Vec<InstalledPackage>,
+        exported: &[ExportPackage],
+    ) -> (Vec<InstalledPackage>, Vec<String>) {
+        let mut complete = Vec::with_capacity(listed.len());
+        let mut skipped = Vec::new();
+
+        for mut package in listed {
+            if package.id.ends_with('…') {
+                // Only an unambiguous completion will do
+                let mut matches = exported.iter().filter(|e| Self::same_listed_id(&package.id, &e.package_identifier));
+                match (matches.next(), matches.next()) {
+                    (Some(full), None) => package.id = full.package_identifier.clone(),
+                    _ => {
+                        skipped.push(package.id);
+                        continue;
+                    }
+                }
+            }
+            complete.push(package);
+        }
+
+        (complete, skipped)
+    }
+
+    /// Check generated YAML has the shape `winget configure` expects: the
+    /// current schema version, and every unit a package resource with a
+    /// unique, complete id that matches the package it installs
+    fn validate_configuration(yaml: &str) -> Result<(), PackageError> {
+        let parsed: ConfigurationFile = serde_yaml::from_str(yaml)
+            .map_err(|e| PackageError::Unknown(format!("Generated configuration is invalid: {}", e)))?;
+
+        if parsed.properties.configuration_version != CONFIGURATION_VERSION {
+            return Err(PackageError::Unknown(format!(
+                "Generated configuration has version {}",
+                parsed.properties.configuration_version
+            )));
+        }
+
+        let mut ids = HashSet::new();
+        for resource in &parsed.properties.resources {
+            let valid = resource.resource == PACKAGE_RESOURCE
+                && !resource.id.trim().is_empty()
+                && !resource.id.ends_with('…')
+                && resource.settings.id == resource.id
+                && ids.insert(resource.id.to_lowercase());
+            if !valid {
+                return Err(PackageError::Unknown(format!(
+                    "Generated configuration has an invalid unit: {}",
+                    resource.id
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Apply a winget configuration file with `winget configure`, accepting
+    /// its agreements, and report each unit's outcome. Needs winget 1.6 or later.
+    pub async fn apply_configuration(&self, path: &Path) -> Result<ConfigurationResult, PackageError> {
+        let Some(winget_version) = self.version().await else {
+            return Err(PackageError::NotFound(
+                "Winget is not installed".to_string(),
+            ));
+        };
+
+        if version::compare(&winget_version, CONFIGURE_MIN_VERSION).is_lt() {
+            return Err(Self::configure_unsupported(&winget_version));
+        }
+
+        validate_local_file(path, &["yaml", "yml"])?;
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| PackageError::Unknown(format!("Failed to read {}: {}", path.display(), e)))?;
+        let document: serde_yaml::Value = serde_yaml::from_str(&contents)
+            .map_err(|e| PackageError::InvalidInput(format!("{} is not valid YAML: {}", path.display(), e)))?;
+        if document.get("properties").is_none() {
+            return Err(PackageError::InvalidInput(format!(
+                "{} is not a winget configuration (no `properties`)",
+                path.display()
+            )));
+        }
+
+        let mut tracker = self.track("configure", &path.to_string_lossy());
+        let output = output_with_lines(
+            TokioCommand::new(&self.exe_path)
+                .args(["configure", "--accept-configuration-agreements", "--disable-interactivity", "--file"])
+                .arg(path),
+            |line| tracker.line(line),
+        )
+        .await
+        .map_err(|e| PackageError::CommandFailed(e.to_string()))?;
+        tracker.finish(output.status.success());
+
+        let stdout = clean_output(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+        let success = output.status.success();
+
+        if stdout.contains("Unrecognized command") {
+            return Err(Self::configure_unsupported(&winget_version));
+        }
+
+        Ok(ConfigurationResult {
+            success,
+            units: Self::parse_configure_output(&stdout),
+            output: stdout,
+            error: if success { None } else { Some(stderr) },
+        })
+    }
+
+    fn configure_unsupported(winget_version: &str) -> PackageError {
+        PackageError::InvalidInput(format!(
+            "winget {} doesn't support configure; update App Installer to get winget {} or later",
+            winget_version, CONFIGURE_MIN_VERSION
+        ))
+    }
+
+    /// Per-unit outcomes from `winget configure` output.
+    ///
+    /// Each unit is announced by an unindented "Resource [unit]" line, e.g.
+    /// "Microsoft.WinGet.DSC/WinGetPackage [Git.Git]", once when the
+    /// configuration is listed and again while it's applied, followed by
+    /// indented details. The applying pass ends each unit with a line
+    /// saying how it went; a unit without one is `Unknown`.
+    fn parse_configure_output(output: &str) -> Vec<ConfigurationUnitResult> {
+        let mut units: Vec<ConfigurationUnitResult> = Vec::new();
+        let mut current: Option<usize> = None;
+
+        for line in output.lines() {
+            let trimmed = line.trim();
+            if trimmed.is_empty() {
+                continue;
+            }
+
+            if !line.starts_with(char::is_whitespace) {
+                current = Self::unit_header(trimmed).map(|(resource, unit)| {
+                    units
+                        .iter()
+                        .position(|u| u.resource == resource && u.unit == unit)
+                        .unwrap_or_else(|| {
+                            units.push(ConfigurationUnitResult {
+                                resource: resource.to_string(),
+                                unit: unit.to_string(),
+                                state: ConfigurationUnitState::Unknown,
+                                message: None,
+                            });
+                            units.len() - 1
+                        })
+                });
+                continue;
+            }
+
+            let Some(i) = current else {
+                continue;
+            };
+            let lower = trimmed.to_lowercase();
+            let state = if lower.contains("already in the desired state") {
+                ConfigurationUnitState::Unchanged
+            } else if lower.contains("successfully applied") {
+                ConfigurationUnitState::Applied
+            } else if lower.contains("failed") || lower.contains("could not be found") || lower.contains("was not run") {
+                ConfigurationUnitState::Failed
+            } else {
+                continue;
+            };
+            units[i].state = state;
+            units[i].message = Some(trimmed.to_string());
+        }
+
+        units
+    }
+
+    /// Split a unit header like "Microsoft.WinGet.DSC/WinGetPackage [Git.Git]"
+    /// into the resource and unit; `None` for any other line
+    fn unit_header(line: &str) -> Option<(&str, &str)> {
+        let (resource, rest) = line.split_once(" [")?;
+        let unit = rest.strip_suffix(']')?;
+        (!resource.contains(' ') && !unit.is_empty()).then_some((resource, unit))
+    }
+
+    /// Search the winget catalog, or only the named source when `catalog` is given
+    pub async fn search(&self, query: &str, catalog: Option<&str>) -> Result<Vec<AvailablePackage>, PackageError> {
+        if !self.is_installed().await {
+            return Err(PackageError::NotFound(
+                "Winget is not installed".to_string(),
+            ));
+        }
+
+        // Passed as --query's value so a query starting with "-" isn't read as a flag
+        let mut args = vec!["search", "--query", query, "--accept-source-agreements"];
+        if let Some(catalog) = catalog {
+            args.extend(["--source", catalog]);
+        }
+
+        let output = TokioCommand::new(&self.exe_path)
+            .args(&args)
+            .output()
+            .await
+            .map_err(|e| PackageError::CommandFailed(e.to_string()))?;
+
+        let stdout = clean_output(&output.stdout);
+
+        // Winget exits non-zero when nothing matches; that's an empty result, not an error
+        if stdout.contains("No package found matching input criteria") {
+            return Ok(Vec::new());
+        }
+
+        if !output.status.success() {
+            return Err(PackageError::CommandFailed(
+                String::from_utf8_lossy(&output.stderr).to_string(),
+            ));
+        }
+
+        Ok(Self::parse_search_output(&stdout, catalog))
+    }
+
+    /// Parse `winget search` output (Name, Id, Version, [Match], [Source]).
+    ///
+    /// Winget drops the Source column when searching a single source, so
+    /// rows then belong to `searched`, the source that was asked for.
+    fn parse_search_output(output: &str, searched: Option<&str>) -> Vec<AvailablePackage> {
+        let (headers, rows) = parse_table(output);
+        if headers.len() < 3 {
+            return Vec::new();
+        }
+        let source = column(&headers, "Source");
+
+        rows.into_iter()
+            .filter_map(|row| {
+                let id = row.get(1).filter(|id| !id.is_empty())?.clone();
+                let name = row.first().filter(|n| !n.is_empty()).cloned();
+                let version = row.get(2).cloned().unwrap_or_default();
+                let catalog = source
+                    .and_then(|i| row.get(i))
+                    .filter(|c| !c.is_empty())
+                    .cloned()
+                    .or_else(|| searched.map(str::to_string));
+
+                Some(AvailablePackage {
+                    id,
+                    name,
+                    version,
+                    source: PackageSource::Winget,
+                    catalog,
+                })
+            })
+            .collect()
+    }
+
+    /// Show catalog details for a package
+    pub async fn show(&self, package_id: &str) -> Result<PackageDetails, PackageError> {
+        validate_package_id(package_id)?;
+
+        if !self.is_installed().await {
+            return Err(PackageError::NotFound(
+                "Winget is not installed".to_string(),
+            ));
+        }
+
+        let output = TokioCommand::new(&self.exe_path)
+            .args(["show", "--id", package_id, "--exact", "--accept-source-agreements"])
+            .output()
+            .await
+            .map_err(|e| PackageError::CommandFailed(e.to_string()))?;
+
+        let stdout = clean_output(&output.stdout);
+
+        if stdout.contains("No package found matching input criteria") {
+            return Err(PackageError::NotFound(package_id.to_string()));
+        }
+
+        if Self::reports_multiple_matches(&stdout) {
+            return Err(Self::ambiguous_id(package_id));
+        }
+
+        if !output.status.success() {
+            return Err(PackageError::CommandFailed(
+                String::from_utf8_lossy(&output.stderr).to_string(),
+            ));
+        }
+
+        Ok(Self::parse_show_output(package_id, &stdout))
+    }
+
+    /// Every version of a package the winget catalog offers, newest first
+    pub async fn available_versions(&self, package_id: &str) -> Result<Vec<String>, PackageError> {
+        validate_package_id(package_id)?;
+
+        if !self.is_installed().await {
+            return Err(PackageError::NotFound(
+                "Winget is not installed".to_string(),
+            ));
+        }
+
+        let output = TokioCommand::new(&self.exe_path)
+            .args(["show", "--id", package_id, "--exact", "--versions", "--accept-source-agreements"])
+            .output()
+            .await
+            .map_err(|e| PackageError::CommandFailed(e.to_string()))?;
+
+        let stdout = clean_output(&output.stdout);
+
+        if stdout.contains("No package found matching input criteria") {
+            return Err(PackageError::NotFound(package_id.to_string()));
+        }
+
+        if Self::reports_multiple_matches(&stdout) {
+            return Err(Self::ambiguous_id(package_id));
+        }
+
+        if !output.status.success() {
+            return Err(PackageError::CommandFailed(
+                String::from_utf8_lossy(&output.stderr).to_string(),
+            ));
+        }
+
+        // A single "Version" column under the "Found <name> [<id>]" line
+        let (_, rows) = parse_table(&stdout);
+        let mut versions: Vec<String> = rows
+            .into_iter()
+            .filter_map(|row| row.into_iter().next().filter(|v| !v.is_empty()))
+            .collect();
+
+        version::sort_newest_first(&mut versions);
+        Ok(versions)
+    }
+
+    /// Parse `winget show` output into package details.
+    ///
+    /// Labels are matched against a small set of translations since winget
+    /// localizes them; any field that can't be found is left as `None`.
+    fn parse_show_output(package_id: &str, output: &str) -> PackageDetails {
+        const VERSION_LABELS: &[&str] = &["Version", "Versión", "Versione", "版本"];
+        const DESCRIPTION_LABELS: &[&str] = &["Description", "Beschreibung", "Descripción", "Descrizione", "描述"];
+        const PUBLISHER_LABELS: &[&str] = &["Publisher", "Herausgeber", "Éditeur", "Editor", "Editore", "发布者"];
+        const HOMEPAGE_LABELS: &[&str] = &["Homepage", "Startseite", "Page d'accueil", "Página principal", "Home page", "主页"];
+        const LICENSE_LABELS: &[&str] = &["License", "Lizenz", "Licence", "Licencia", "Licenza", "许可证"];
+
+        let mut details = PackageDetails {
+            id: package_id.to_string(),
+            ..Default::default()
+        };
+        let id_marker = format!("[{}]", package_id.to_lowercase());
+        let mut in_description = false;
+
+        for raw_line in output.lines() {
+            let line = raw_line.rsplit('\r').next().unwrap_or(raw_line);
+
+            // Indented lines continue the previous field (multi-line descriptions, tag lists)
+            if line.starts_with(char::is_whitespace) {
+                if in_description && !line.trim().is_empty() {
+                    if let Some(description) = details.description.as_mut() {
+                        if !description.is_empty() {
+                            description.push('\n');
+                        }
+                        description.push_str(line.trim());
+                    }
+                }
+                continue;
+            }
+            in_description = false;
+
+            // "Found <name> [<id>]" header, whatever the leading word is in this locale
+            if details.name.is_none() && line.to_lowercase().trim_end().ends_with(&id_marker) {
+                let without_id = &line.trim_end()[..line.trim_end().len() - id_marker.len()];
+                let name = without_id.trim().split_once(' ').map(|(_, n)| n.trim());
+                details.name = name.filter(|n| !n.is_empty()).map(str::to_string);
+                continue;
+            }
+
+            let Some((label, value)) = line.split_once(':').or_else(|| line.split_once('：')) else {
+                continue;
+            };
+            let label = label.trim();
+            let value = value.trim();
+            let value = if value.is_empty() { None } else { Some(value.to_string()) };
+
+            if VERSION_LABELS.contains(&label) {
+                details.version = value;
+            } else if DESCRIPTION_LABELS.contains(&label) {
+                details.description = value.or_else(|| Some(String::new()));
+                in_description = true;
+            } else if PUBLISHER_LABELS.contains(&label) {
+                details.publisher = value;
+            } else if HOMEPAGE_LABELS.contains(&label) {
+                details.homepage = value;
+            } else if LICENSE_LABELS.contains(&label) {
+                details.license = value;
+            }
+        }
+
+        if details.description.as_deref() == Some("") {
+            details.description = None;
+        }
+
+        details
+    }
+
+    /// Pin a package so `upgrade --all` leaves it alone
+    pub async fn pin(&self, package_id: &str) -> Result<(), PackageError> {
+        self.run_pin(&["pin", "add", "--id", package_id, "--exact", "--accept-source-agreements"], package_id)
+            .await
+    }
+
+    /// Remove a package's pin
+    pub async fn unpin(&self, package_id: &str) -> Result<(), PackageError> {
+        self.run_pin(&["pin", "remove", "--id", package_id, "--exact", "--accept-source-agreements"], package_id)
+            .await
+    }
+
+    async fn run_pin(&self, args: &[&str], package_id: &str) -> Result<(), PackageError> {
+        validate_package_id(package_id)?;
+
+        if !self.is_installed().await {
+            return Err(PackageError::NotFound(
+                "Winget is not installed".to_string(),
+            ));
+        }
+
+        let output = TokioCommand::new(&self.exe_path)
+            .args(args)
+            .output()
+            .await
+            .map_err(|e| PackageError::CommandFailed(e.to_string()))?;
+
+        let stdout = clean_output(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+
+        if let Some(error) = exit_code_error(&output.status, &stderr, package_id) {
+            return Err(error);
+        }
+        if !output.status.success() {
+            return Err(PackageError::CommandFailed(if stderr.trim().is_empty() { stdout } else { stderr }));
+        }
+
+        Ok(())
+    }
+
+    /// List pinned packages
+    pub async fn list_pinned(&self) -> Result<Vec<PinnedPackage>, PackageError> {
+        if !self.is_installed().await {
+            return Err(PackageError::NotFound(
+                "Winget is not installed".to_string(),
+            ));
+        }
+
+        let output = TokioCommand::new(&self.exe_path)
+            .args(["pin", "list", "--accept-source-agreements"])
+            .output()
+            .await
+            .map_err(|e| PackageError::CommandFailed(e.to_string()))?;
+
+        let stdout = clean_output(&output.stdout);
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+            return Err(PackageError::CommandFailed(if stderr.trim().is_empty() { stdout } else { stderr }));
+        }
+
+        Ok(Self::parse_pin_list_output(&stdout))
+    }
+
+    /// Parse `winget pin list`'s table: Name, Id, Version, Source, Pin type
+    fn parse_pin_list_output(output: &str) -> Vec<PinnedPackage> {
+        let (headers, rows) = parse_table(output);
+        if headers.len() < 3 {
+            return Vec::new();
+        }
+        let pin_type = column(&headers, "Pin type");
+
+        rows.into_iter()
+            .filter_map(|row| {
+                let id = row.get(1).filter(|id| !id.is_empty())?.clone();
+                let non_empty = |i: usize| row.get(i).filter(|v| !v.is_empty()).cloned();
+
+                Some(PinnedPackage {
+                    id,
+                    version: non_empty(2),
+                    source: PackageSource::Winget,
+                    pin_type: pin_type.and_then(non_empty),
+                })
+            })
+            .collect()
+    }
+
+    /// List configured sources (`winget source list`)
+    pub async fn list_sources(&self) -> Result<Vec<Source>, PackageError> {
+        if !self.is_installed().await {
+            return Err(PackageError::NotFound(
+                "Winget is not installed".to_string(),
+            ));
+        }
+
+        let output = TokioCommand::new(&self.exe_path)
+            .args(["source", "list"])
+            .output()
+            .await
+            .map_err(|e| PackageError::CommandFailed(e.to_string()))?;
+
+        if !output.status.success() {
+            return Err(PackageError::CommandFailed(
+                String::from_utf8_lossy(&output.stderr).to_string(),
+            ));
+        }
+
+        let stdout = clean_output(&output.stdout);
+        Ok(Self::parse_source_list_output(&stdout))
+    }
+
+    /// Parse `winget source list`'s table: Name, Argument, and on newer
+    /// versions Explicit (and sometimes Type)
+    fn parse_source_list_output(output: &str) -> Vec<Source> {
+        let (headers, rows) = parse_table(output);
+        if headers.len() < 2 {
+            return Vec::new();
+        }
+        let type_column = headers.iter().position(|h| h.eq_ignore_ascii_case("Type"));
+
+        rows.into_iter()
+            .filter_map(|row| {
+                let name = row.first().filter(|n| !n.is_empty())?.clone();
+                let url = row.get(1).cloned().unwrap_or_default();
+                let source_type = type_column
+                    .and_then(|i| row.get(i))
+                    .filter(|t| !t.is_empty())
+                    .cloned();
+
+                Some(Source { name, url, source_type, disabled: false })
+            })
+            .collect()
+    }
+
+    /// Add a source, e.g. an internal REST feed (`source_type` "Microsoft.Rest").
+    /// Winget requires administrator rights for this.
+    pub async fn add_source(&self, name: &str, url: &str, source_type: Option<&str>) -> Result<(), PackageError> {
+        let mut args = vec!["source", "add", "--name", name, "--arg", url, "--accept-source-agreements"];
+        if let Some(source_type) = source_type {
+            args.extend(["--type", source_type]);
+        }
+        self.run_source_command(&args, name).await
+    }
+
+    /// Remove a source. Winget requires administrator rights for this.
+    pub async fn remove_source(&self, name: &str) -> Result<(), PackageError> {
+        self.run_source_command(&["source", "remove", "--name", name], name).await
+    }
+
+    async fn run_source_command(&self, args: &[&str], name: &str) -> Result<(), PackageError> {
+        if !self.is_installed().await {
+            return Err(PackageError::NotFound(
+                "Winget is not installed".to_string(),
+            ));
+        }
+
+        let output = TokioCommand::new(&self.exe_path)
+            .args(args)
+            .output()
+            .await
+            .map_err(|e| PackageError::CommandFailed(e.to_string()))?;
+
+        let stdout = clean_output(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+
+        if stdout.contains("Did not find a source named") {
+            return Err(PackageError::NotFound(format!("source {}", name)));
+        }
+        if let Some(error) = exit_code_error(&output.status, &stderr, name) {
+            return Err(error);
+        }
+        if !output.status.success() {
+            return Err(PackageError::CommandFailed(if stderr.trim().is_empty() { stdout } else { stderr }));
+        }
+
+        Ok(())
+    }
+
+    /// Upgrade every installed package with an update available.
+    ///
+    /// Returns one result per package whose installed version changed.
+    /// Pinned packages are skipped by `winget upgrade --all` itself.
+    pub async fn upgrade_all(&self) -> Result<Vec<UpgradeResult>, PackageError> {
+        if !self.is_installed().await {
+            return Err(PackageError::NotFound(
+                "Winget is not installed".to_string(),
+            ));
+        }
+
+        let started = Instant::now();
+        let before = self.list_installed(None).await?;
+
+        let mut tracker = self.track("upgrade", "all");
+        let output = output_with_lines(
+            TokioCommand::new(&self.exe_path)
+                .args(["upgrade", "--all", "--silent", "--accept-package-agreements", "--accept-source-agreements"]),
+            |line| tracker.line(line),
+        )
+        .await
+        .map_err(|e| PackageError::CommandFailed(e.to_string()))?;
+        tracker.finish(output.status.success());
+
+        let stdout = clean_output(&output.stdout);
+        let after = self.list_installed(None).await?;
+        let results = super::collect_version_changes(&before, &after, &stdout, elapsed_ms(started));
+
+        if results.is_empty() && !output.status.success() {
+            return Err(PackageError::CommandFailed(
+                String::from_utf8_lossy(&output.stderr).to_string(),
+            ));
+        }
+
+        Ok(results)
+    }
+
+    /// List installed packages that have an update available
+    pub async fn list_upgradable(&self) -> Result<Vec<UpgradablePackage>, PackageError> {
+        if !self.is_installed().await {
+            return Err(PackageError::NotFound(
+                "Winget is not installed".to_string(),
+            ));
+        }
+
+        // Without an id, `winget upgrade` only lists what could be upgraded
+        let output = TokioCommand::new(&self.exe_path)
+            .args(["upgrade", "--accept-source-agreements"])
+            .output()
+            .await
+            .map_err(|e| PackageError::CommandFailed(e.to_string()))?;
+
+        let stdout = clean_output(&output.stdout);
+
+        if stdout.contains("No installed package found matching input criteria") {
+            return Ok(Vec::new());
+        }
+
+        if !output.status.success() {
+            return Err(PackageError::CommandFailed(
+                String::from_utf8_lossy(&output.stderr).to_string(),
+            ));
+        }
+
+        Ok(Self::parse_upgrade_output(&stdout))
+    }
+
+    /// Parse `winget upgrade` output (Name, Id, Version, Available, Source)
+    fn parse_upgrade_output(output: &str) -> Vec<UpgradablePackage> {
+        let (headers, rows) = parse_table(output);
+        if headers.len() < 4 {
+            return Vec::new();
+        }
+
+        rows.into_iter()
+            .filter_map(|row| {
+                let id = row.get(1).filter(|id| !id.is_empty())?;
+                let current_version = row.get(2).filter(|v| !v.is_empty())?;
+                // Footer lines like "3 upgrades available." never fill the version columns
+                let available_version = row.get(3).filter(|v| !v.is_empty())?;
+
+                Some(UpgradablePackage {
+                    id: id.clone(),
+                    current_version: current_version.clone(),
+                    available_version: available_version.clone(),
+                    source: PackageSource::Winget,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Header names winget prints with a space inside, which would otherwise
+/// read as two columns
+const MULTI_WORD_HEADERS: [&str; 1] = ["Pin type"];
+
+/// Index of the column named `name` in `parse_table`'s headers
+fn column(headers: &[String], name: &str) -> Option<usize> {
+    headers.iter().position(|header| header.eq_ignore_ascii_case(name))
+}
+
+/// Split winget's tabular output into header names and row cells.
+///
+/// Column boundaries come from where each header name starts, so cells may
+/// contain spaces (e.g. "Microsoft Edge") or dots without confusing the split.
+/// Offsets are measured in characters, since winget aligns by character.
+fn parse_table(output: &str) -> (Vec<String>, Vec<Vec<String>>) {
+    // Winget redraws its spinner with carriage returns; keep only the final text of each line
+    let lines: Vec<&str> = output
+        .lines()
+        .map(|line| line.rsplit('\r').next().unwrap_or(line))
+        .collect();
+
+    // The header is the line directly above the dashed separator
+    let separator = match lines
+        .iter()
+        .position(|line| line.len() >= 3 && line.trim().chars().all(|c| c == '-'))
+    {
+        Some(idx) if idx > 0 => idx,
+        _ => return (Vec::new(), Vec::new()),
+    };
+
+    let header: Vec<char> = lines[separator - 1].chars().collect();
+    let mut starts = Vec::new();
+    let mut i = 0;
+    while i < header.len() {
+        if !header[i].is_whitespace() && (i == 0 || header[i - 1].is_whitespace()) {
+            starts.push(i);
+            let rest: String = header[i..].iter().collect();
+            if let Some(name) = MULTI_WORD_HEADERS.iter().find(|name| rest.starts_with(*name)) {
+                i += name.chars().count();
+                continue;
+            }
+        }
+        i += 1;
+    }
+
+    let headers = starts
+        .iter()
+        .enumerate()
+        .map(|(i, &start)| {
+            let end = starts.get(i + 1).copied().unwrap_or(header.len());
+            header[start..end].iter().collect::<String>().trim().to_string()
+        })
+        .collect();
+
+    // The table ends at the first blank line; anything after is footer text
+    let rows = lines[separator + 1..]
+        .iter()
+        .take_while(|line| !line.trim().is_empty())
+        .map(|line| {
+            let chars: Vec<char> = line.chars().collect();
+            starts
+                .iter()
+                .enumerate()
+                .map(|(i, &start)| {
+                    let end = starts.get(i + 1).copied().unwrap_or(chars.len()).min(chars.len());
+                    if start >= end {
+                        return String::new();
+                    }
+                    chars[start..end].iter().collect::<String>().trim().to_string()
+                })
+                .collect()
+        })
+        .collect();
+
+    (headers, rows)
+}
+
+impl Default for WingetManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Cells of winget's progress bar: filled, and the two glyphs it uses for empty
+const BAR_FILLED: char = '█';
+const BAR_EMPTY: [char; 2] = ['▒', '░'];
+
+/// Progress from one line of winget output, after ANSI stripping and with
+/// each `\r` redraw of the progress bar passed as its own line.
+///
+/// Bar lines look like "██████▒▒▒▒  12.0 MB / 50.3 MB" while downloading
+/// and "██████▒▒▒▒  60%" for MSIX installs; the counter or percentage is
+/// used, else the share of filled cells. Phases start at
+/// "Downloading <url>" and "Starting package install...".
+fn parse_progress(line: &str) -> LineProgress {
+    let line = line.trim();
+    let lower = line.to_lowercase();
+
+    if lower.starts_with("downloading ") {
+        return LineProgress {
+            phase: Some(Phase::Downloading),
+            fraction: None,
+        };
+    }
+    if lower.starts_with("successfully verified installer hash")
+        || lower.starts_with("starting package install")
+        || lower.starts_with("starting package uninstall")
+    {
+        return LineProgress {
+            phase: Some(Phase::Installing),
+            fraction: None,
+        };
+    }
+
+    let filled = line.chars().filter(|&c| c == BAR_FILLED).count();
+    let empty = line.chars().filter(|c| BAR_EMPTY.contains(c)).count();
+    if filled + empty == 0 {
+        return LineProgress::default();
+    }
+
+    LineProgress {
+        phase: None,
+        fraction: line_fraction(line).or(Some(filled as f32 / (filled + empty) as f32)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PIN_LIST: &str = "\
+Name            Id                         Version      Source Pin type
+-------------------------------------------------------------------------
+PowerToys       Microsoft.PowerToys        0.75.1       winget Pinning
+Git             Git.Git                    2.43.0       winget Blocking
+Contoso Tool    Contoso.Tool               1.0          winget Gating 1.0.*
+";
+
+    #[test]
+    fn pin_list_reads_multi_word_pin_type_column() {
+        let (headers, _) = parse_table(PIN_LIST);
+        assert_eq!(headers, vec!["Name", "Id", "Version", "Source", "Pin type"]);
+
+        let pins = WingetManager::parse_pin_list_output(PIN_LIST);
+        let summary: Vec<(&str, Option<&str>, Option<&str>)> = pins
+            .iter()
+            .map(|p| (p.id.as_str(), p.version.as_deref(), p.pin_type.as_deref()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("Microsoft.PowerToys", Some("0.75.1"), Some("Pinning")),
+                ("Git.Git", Some("2.43.0"), Some("Blocking")),
+                ("Contoso.Tool", Some("1.0"), Some("Gating 1.0.*")),
+            ]
+        );
+    }
+
+    #[test]
+    fn search_reads_source_column_by_name() {
+        let output = "\
+Name             Id                   Version      Match         Source
+-------------------------------------------------------------------------
+Git              Git.Git              2.43.0                     winget
+Git Extensions   GitExtensionsTeam.G… 4.2.1        Tag: git      winget
+Windows Terminal 9N0DX20HK701         Unknown                    msstore
+";
+        let results = WingetManager::parse_search_output(output, None);
+        let catalogs: Vec<(&str, Option<&str>)> =
+            results.iter().map(|p| (p.id.as_str(), p.catalog.as_deref())).collect();
+        assert_eq!(
+            catalogs,
+            vec![
+                ("Git.Git", Some("winget")),
+                ("GitExtensionsTeam.G…", Some("winget")),
+                ("9N0DX20HK701", Some("msstore")),
+            ]
+        );
+    }
+
+    #[test]
+    fn search_of_one_source_uses_requested_source() {
+        let output = "\
+Name             Id                   Version      Match
+-------------------------------------------------------------
+Git              Git.Git              2.43.0
+Git Extensions   GitExtensionsTeam.G… 4.2.1        Tag: git
+";
+        let results = WingetManager::parse_search_output(output, Some("internal"));
+        assert_eq!(results.len(), 2);
+        assert!(results.iter().all(|p| p.catalog.as_deref() == Some("internal")));
+        assert_eq!(results[1].version, "4.2.1");
+
+        let results = WingetManager::parse_search_output(output, None);
+        assert!(results.iter().all(|p| p.catalog.is_none()));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn search_passes_query_as_option_value() {
+        let dir = tempfile::tempdir().unwrap();
+        let exe = crate::package_manager::test_support::fake_exe(
+            dir.path(),
+            "winget",
+            r#"case "$1" in
+  --version) echo "v1.7.10861" ;;
+  search)
+    [ "$2" = "--query" ] && [ "$3" = "--help" ] || exit 1
+    printf 'Name  Id       Version Source\n----------------------------------\nHelp  Help.Me  1.0     winget\n' ;;
+esac"#,
+        );
+
+        let results = WingetManager::with_path(exe).search("--help", None).await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].id, "Help.Me");
+    }
+
+    #[test]
+    fn export_fills_in_truncated_ids_and_versions() {
+        let output = "\
+Name                 Id                    Version      Source
+------------------------------------------------------------------
+Git                  Git.Git               2.43.0       winget
+Visual Studio Build… Microsoft.VisualStud… 17.8.34330.… winget
+Local Tool           ARP\\Machine\\X64\\Tool  1.0
+";
+        let listed = WingetManager::parse_list_output(output);
+        assert_eq!(listed.iter().filter(|p| WingetManager::is_truncated(p)).count(), 1);
+
+        let exported = vec![
+            ExportPackage {
+                package_identifier: "Git.Git".to_string(),
+                version: Some("2.43.0".to_string()),
+            },
+            ExportPackage {
+                package_identifier: "Microsoft.VisualStudio.2022.BuildTools".to_string(),
+                version: Some("17.8.34330.188".to_string()),
+            },
+        ];
+        let merged = WingetManager::merge_exported(listed, exported);
+        let summary: Vec<(&str, &str, Option<&str>)> = merged
+            .iter()
+            .map(|p| (p.id.as_str(), p.version.as_str(), p.name.as_deref()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("Git.Git", "2.43.0", Some("Git")),
+                ("Microsoft.VisualStudio.2022.BuildTools", "17.8.34330.188", Some("Visual Studio Build…")),
+                ("ARP\\Machine\\X64\\Tool", "1.0", Some("Local Tool")),
+            ]
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn export_runs_only_for_truncated_lists() {
+        let dir = tempfile::tempdir().unwrap();
+        let exe = crate::package_manager::test_support::fake_exe(
+            dir.path(),
+            "winget",
+            r#"dir="$(dirname "$0")"
+case "$1" in
+  --version) echo "v1.7.10861" ;;
+  list) cat "$dir/list.txt" ;;
+  export)
+    touch "$dir/exported"
+    echo '{"Sources":[{"Packages":[{"PackageIdentifier":"Microsoft.VisualStudio.2022.BuildTools","Version":"17.8.34330.188"}]}]}' > "$5" ;;
+esac"#,
+        );
+        let manager = WingetManager::with_path(exe);
+        let exported = dir.path().join("exported");
+
+        std::fs::write(dir.path().join("list.txt"), "Name Id      Version\n--------------------\nGit  Git.Git 2.43.0\n").unwrap();
+        let installed = manager.list_installed(None).await.unwrap();
+        assert_eq!(installed[0].id, "Git.Git");
+        assert!(!exported.exists());
+
+        std::fs::write(
+            dir.path().join("list.txt"),
+            "Name         Id                    Version\n----------------------------------------------\nBuild Tools  Microsoft.VisualStud… 17.8.34330.…\n",
+        )
+        .unwrap();
+        let installed = manager.list_installed(None).await.unwrap();
+        assert!(exported.exists());
+        assert_eq!(installed[0].id, "Microsoft.VisualStudio.2022.BuildTools");
+        assert_eq!(installed[0].version, "17.8.34330.188");
+    }
+
+    #[test]
+    fn detects_already_installed() {
+        let output = "\
+Found an existing package already installed. Trying to upgrade the installed package...
+No available upgrade found.
+No newer package versions are available from the configured sources.
+";
+        assert!(WingetManager::reports_already_installed(output));
+
+        // An existing install that does have an upgrade goes ahead with it
+        let upgrading = "\
+Found an existing package already installed. Trying to upgrade the installed package...
+Found Git [Git.Git] Version 2.43.0
+Successfully installed
+";
+        assert!(!WingetManager::reports_already_installed(upgrading));
+        assert!(!WingetManager::reports_already_installed("Found Git [Git.Git] Version 2.43.0\nSuccessfully installed\n"));
+    }
+
+    #[test]
+    fn detects_uninstall_of_missing_package() {
+        assert!(WingetManager::reports_not_installed("No installed package found matching input criteria.\n"));
+        assert!(!WingetManager::reports_not_installed(
+            "Found Git [Git.Git]\nStarting package uninstall...\nSuccessfully uninstalled\n"
+        ));
+    }
+
+    const MULTIPLE_MATCHES: &str = "\
+Multiple packages found matching input criteria. Please refine the input.
+Name        Id                  Source
+---------------------------------------
+Python 3.12 Python.Python.3.12  winget
+Python 3.12 9NCVDN91XZQP        msstore
+";
+
+    #[test]
+    fn detects_ambiguous_ids() {
+        assert!(WingetManager::reports_multiple_matches(MULTIPLE_MATCHES));
+        assert!(!WingetManager::reports_multiple_matches("Found Python 3.12 [Python.Python.3.12] Version 3.12.1\n"));
+        assert!(matches!(WingetManager::ambiguous_id("Python.Python.3.12"), PackageError::NotFound(_)));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn ambiguous_ids_are_reported_as_not_found() {
+        let dir = tempfile::tempdir().unwrap();
+        let exe = crate::package_manager::test_support::fake_exe(
+            dir.path(),
+            "winget",
+            &format!(
+                r#"case "$1" in
+  --version) echo "v1.7.10861" ;;
+  list) echo "No installed package found matching input criteria." ;;
+  *)
+    case " $* " in *" --exact "*) ;; *) exit 2 ;; esac
+    printf '%s' '{MULTIPLE_MATCHES}'
+    exit 1 ;;
+esac"#
+            ),
+        );
+        let manager = WingetManager::with_path(exe);
+
+        let install = manager.install("Python.Python.3.12", &InstallOptions::default()).await;
+        assert!(matches!(install, Err(PackageError::NotFound(_))), "{install:?}");
+        let uninstall = manager.uninstall("Python.Python.3.12", false).await;
+        assert!(matches!(uninstall, Err(PackageError::NotFound(_))), "{uninstall:?}");
+        let upgrade = manager.upgrade("Python.Python.3.12").await;
+        assert!(matches!(upgrade, Err(PackageError::NotFound(_))), "{upgrade:?}");
+    }
+
+    /// Raw `winget install` output: the bar is redrawn in place with `\r`
+    /// and colored with ANSI escapes
+    #[cfg(unix)]
+    const INSTALL_TRANSCRIPT: &str = "Found Git [Git.Git] Version 2.43.0\r\n\
+This application is licensed to you by its owner.\r\n\
+Downloading https://github.com/git-for-windows/git/releases/download/v2.43.0.windows.1/Git-2.43.0-64-bit.exe\r\n\
+\r  \x1b[32m██████\x1b[0m▒▒▒▒▒▒▒▒▒▒▒▒▒▒▒▒▒▒▒▒▒▒▒▒  12.0 MB / 58.2 MB\
+\r  \x1b[32m██████████████\x1b[0m▒▒▒▒▒▒▒▒▒▒▒▒▒▒▒▒  29.0 MB / 58.2 MB\
+\r  \x1b[32m██████████████████████████████\x1b[0m  58.2 MB / 58.2 MB\r\n\
+Successfully verified installer hash\r\n\
+Starting package install...\r\n\
+\r   - \r   \\ \r   | \r\
+\r  ██████████████████▒▒▒▒▒▒▒▒▒▒▒▒  60%\r\n\
+Successfully installed\r\n";
+
+    /// `INSTALL_TRANSCRIPT` as the lines a running winget reports
+    #[cfg(unix)]
+    async fn transcript_lines() -> Vec<String> {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("transcript"), INSTALL_TRANSCRIPT).unwrap();
+        let exe = crate::package_manager::test_support::fake_exe(dir.path(), "winget", r#"cat "$(dirname "$0")/transcript""#);
+
+        let mut lines = Vec::new();
+        output_with_lines(&mut TokioCommand::new(exe), |line| lines.push(line.to_string()))
+            .await
+            .unwrap();
+        lines
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn progress_is_read_from_redrawn_bar_lines() {
+        let progress: Vec<LineProgress> = transcript_lines().await.iter().map(|line| parse_progress(line)).collect();
+
+        let phases: Vec<Phase> = progress.iter().filter_map(|p| p.phase).collect();
+        assert_eq!(phases, vec![Phase::Downloading, Phase::Installing, Phase::Installing]);
+
+        let percents: Vec<u32> = progress.iter().filter_map(|p| p.fraction).map(|f| (f * 100.0).round() as u32).collect();
+        assert_eq!(percents, vec![21, 50, 100, 60]);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn rapid_bar_redraws_are_throttled() {
+        let statuses = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let sink = StatusSink::default();
+        let collected = statuses.clone();
+        sink.set(std::sync::Arc::new(move |status: OperationStatus| {
+            collected.lock().unwrap().push((status.message, status.progress));
+        }));
+
+        let mut tracker = sink.track("install", "Git.Git").with_parser(parse_progress);
+        for line in transcript_lines().await {
+            tracker.line(&line);
+        }
+        tracker.finish(true);
+
+        // The redraws arrive faster than updates are emitted, so only the
+        // phase changes get through
+        let statuses = statuses.lock().unwrap();
+        let messages: Vec<(&str, f32)> = statuses.iter().map(|(m, p)| (m.as_str(), *p)).collect();
+        assert_eq!(messages, vec![("Started", 0.0), ("Downloading", 0.0), ("Installing", 0.5), ("Done", 1.0)]);
+    }
+
+    #[test]
+    fn bar_without_counter_uses_filled_share() {
+        let progress = parse_progress("████████▒▒▒▒▒▒▒▒");
+        assert_eq!(progress.fraction, Some(0.5));
+        assert_eq!(progress.phase, None);
+        assert_eq!(parse_progress("Successfully installed"), LineProgress::default());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn configuration_export_completes_or_skips_truncated_ids() {
+        let dir = tempfile::tempdir().unwrap();
+        let exe = crate::package_manager::test_support::fake_exe(
+            dir.path(),
+            "winget",
+            r#"case "$1" in
+  --version) echo "v1.7.10861" ;;
+  list) printf '%s\n' \
+    'Name                 Id                    Version        Source' \
+    '----------------------------------------------------------------' \
+    'Git                  Git.Git               2.43.0         winget' \
+    'Visual Studio Build… Microsoft.VisualStud… 17.8.34330.188 winget' \
+    'Contoso Tool         Contoso.Tool.Extend…  1.0            winget' ;;
+  export)
+    echo '{"Sources":[{"Packages":[
+      {"PackageIdentifier":"Git.Git","Version":"2.43.0"},
+      {"PackageIdentifier":"Microsoft.VisualStudio.2022.BuildTools","Version":"17.8.34330.188"},
+      {"PackageIdentifier":"Contoso.Tool.ExtendedA"},
+      {"PackageIdentifier":"Contoso.Tool.ExtendedB"}]}]}' > "$5" ;;
+esac"#,
+        );
+
+        let yaml = WingetManager::with_path(exe).export_configuration().await.unwrap();
+
+        assert!(yaml.contains("# Skipped, id cut short by winget: Contoso.Tool.Extend…\n"), "{yaml}");
+        assert!(!yaml.contains("id: Contoso"), "{yaml}");
+        assert!(yaml.contains("id: Microsoft.VisualStudio.2022.BuildTools"), "{yaml}");
+        assert!(yaml.contains("id: Git.Git"), "{yaml}");
+    }
+
+    /// Captured from `winget list`, with winget's own truncation
+    const LIST: &str = "\
+Name                                       Id                                          Version         Available     Source
+----------------------------------------------------------------------------------------------------------------------------
+Node.js                                    OpenJS.NodeJS.LTS                           20.11.0         20.11.1       winget
+Microsoft Edge                             Microsoft.Edge                              121.0.2277.83                 winget
+Microsoft Visual C++ 2015-2022 Redistribu… Microsoft.VCRedist.2015+.x64                14.38.33130.0                 winget
+Zoom                                       Zoom.Zoom                                   < 5.17.5                      winget
+Windows Subsystem for Linux                MSIX\\MicrosoftCorporationII.WindowsSubsyst… 2.0.9.0
+";
+
+    #[test]
+    fn list_splits_columns_by_header_offsets() {
+        let installed = WingetManager::parse_list_output(LIST);
+        let rows: Vec<(Option<&str>, &str, &str)> = installed
+            .iter()
+            .map(|p| (p.name.as_deref(), p.id.as_str(), p.version.as_str()))
+            .collect();
+
+        assert_eq!(
+            rows,
+            vec![
+                (Some("Node.js"), "OpenJS.NodeJS.LTS", "20.11.0"),
+                (Some("Microsoft Edge"), "Microsoft.Edge", "121.0.2277.83"),
+                (Some("Microsoft Visual C++ 2015-2022 Redistribu…"), "Microsoft.VCRedist.2015+.x64", "14.38.33130.0"),
+                // winget only knows the version is below 5.17.5
+                (Some("Zoom"), "Zoom.Zoom", "< 5.17.5"),
+                (Some("Windows Subsystem for Linux"), "MSIX\\MicrosoftCorporationII.WindowsSubsyst…", "2.0.9.0"),
+            ]
+        );
+    }
+
+    #[test]
+    fn list_ignores_spinner_redraws_and_footer() {
+        let output = format!("\r   - \r   \\ \r{LIST}\n2 upgrades available.\n");
+        assert_eq!(WingetManager::parse_list_output(&output).len(), 5);
+        assert!(WingetManager::parse_list_output("No installed package found matching input criteria.\n").is_empty());
+    }
+
+    #[test]
+    fn pin_list_without_pin_type_column_leaves_it_empty() {
+        let output = "\
+Name      Id         Version
+------------------------------
+Git       Git.Git    2.43.0
+";
+        let pins = WingetManager::parse_pin_list_output(output);
+        assert_eq!(pins.len(), 1);
+        assert_eq!(pins[0].pin_type, None);
     }
 }
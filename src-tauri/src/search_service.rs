@@ -1,7 +1,33 @@
 // Search Service - Python backend integration
+use flate2::read::GzDecoder;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
-use std::process::Command;
-use std::path::PathBuf;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader as AsyncBufReader};
+use tokio::process::{Child, ChildStdin, ChildStdout, Command};
+use tokio::sync::{watch, Mutex as AsyncMutex, OnceCell};
+
+use crate::aliases::AliasTable;
+use crate::data_cache;
+
+pub const DEFAULT_LIMIT: usize = 20;
+pub const DEFAULT_SUGGEST_LIMIT: usize = 8;
+
+/// With autocorrect on, a query returning fewer results than this is retried
+/// with misspelled words corrected
+const AUTOCORRECT_MIN_RESULTS: usize = 3;
+pub const DEFAULT_THRESHOLD: f32 = 0.0;
+
+/// How long the Python service may run before it's killed
+pub const DEFAULT_SEARCH_TIMEOUT: Duration = Duration::from_secs(30);
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SearchResult {
@@ -10,46 +36,1505 @@ pub struct SearchResult {
     pub summary: String,
     pub score: f32,
     pub source: String,
+    /// Which search path produced this result, for debugging
+    #[serde(default)]
+    pub backend: Option<SearchBackend>,
+    /// For cross-source searches, every `source:id` this app was found as
+    #[serde(default)]
+    pub sources: Vec<String>,
+    /// Index category (e.g. "Developer Tools"), if the index has one
+    #[serde(default)]
+    pub category: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+/// A search box completion: just enough to show and pick a package
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Suggestion {
+    pub id: String,
+    pub title: String,
+    pub source: String,
 }
 
+/// One page of search results and how many matched in total
 #[derive(Debug, Serialize, Deserialize)]
+pub struct SearchResponse {
+    pub results: Vec<SearchResult>,
+    /// Matches above the threshold before the limit was applied
+    pub total_matches: usize,
+    /// Whether `results` was cut short by the limit
+    pub truncated: bool,
+    /// A newer search in the same session replaced this one; `results` is
+    /// empty and the response should be dropped
+    #[serde(default)]
+    pub superseded: bool,
+    /// The corrected query these results are for, when autocorrect replaced
+    /// a misspelled one ("showing results for ...")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub corrected_query: Option<String>,
+}
+
+impl SearchResponse {
+    fn new(results: Vec<SearchResult>, total_matches: usize) -> Self {
+        let total_matches = total_matches.max(results.len());
+        Self {
+            truncated: total_matches > results.len(),
+            results,
+            total_matches,
+            superseded: false,
+            corrected_query: None,
+        }
+    }
+
+    fn superseded() -> Self {
+        Self {
+            superseded: true,
+            ..Self::new(Vec::new(), 0)
+        }
+    }
+}
+
+/// What the Python service prints: results with a total, or a bare list from
+/// older versions of the script
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum PythonOutput {
+    Response { results: Vec<SearchResult>, total_matches: usize },
+    Results(Vec<SearchResult>),
+}
+
+impl From<PythonOutput> for SearchResponse {
+    fn from(output: PythonOutput) -> Self {
+        match output {
+            PythonOutput::Response { results, total_matches } => SearchResponse::new(results, total_matches),
+            PythonOutput::Results(results) => {
+                let total_matches = results.len();
+                SearchResponse::new(results, total_matches)
+            }
+        }
+    }
+}
+
+/// How much of unparseable Python output to keep in `PythonError::InvalidOutput`
+const RAW_OUTPUT_PREVIEW_BYTES: usize = 2048;
+
+/// Fields every result from the Python service must carry
+const REQUIRED_RESULT_FIELDS: &[&str] = &["id", "title", "summary", "score", "source"];
+
+/// Parse and check one response from the Python service
+fn parse_python_output(stdout: &[u8]) -> Result<SearchResponse, PythonError> {
+    let invalid = |message: String| PythonError::InvalidOutput {
+        message,
+        raw: String::from_utf8_lossy(&stdout[..stdout.len().min(RAW_OUTPUT_PREVIEW_BYTES)]).into_owned(),
+    };
+
+    let value: Value = serde_json::from_slice(stdout).map_err(|e| invalid(format!("not JSON: {}", e)))?;
+    if let Some(error) = value.get("error").and_then(Value::as_str) {
+        return Err(PythonError::ServiceError(error.to_string()));
+    }
+
+    let results = match &value {
+        Value::Array(results) => results,
+        Value::Object(response) => response
+            .get("results")
+            .and_then(Value::as_array)
+            .ok_or_else(|| invalid("response has no \"results\" list".to_string()))?,
+        _ => return Err(invalid("expected a JSON object or list".to_string())),
+    };
+
+    for (i, result) in results.iter().enumerate() {
+        if let Some(field) = REQUIRED_RESULT_FIELDS
+            .iter()
+            .find(|&&field| result.get(field).is_none_or(Value::is_null))
+        {
+            return Err(invalid(format!("result {} is missing \"{}\"", i, field)));
+        }
+    }
+
+    serde_json::from_value::<PythonOutput>(value)
+        .map(SearchResponse::from)
+        .map_err(|e| invalid(e.to_string()))
+}
+
+/// Search implementations, in the order they're tried
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchBackend {
+    PythonDaemon,
+    Python,
+    Offline,
+    Fuzzy,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SearchRequest {
     pub query: String,
     pub source: Option<String>,
     pub limit: Option<usize>,
     pub threshold: Option<f32>,
+    /// Pre-computed query embedding, used by the offline search instead of
+    /// deriving one from the cached package vectors
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub query_embedding: Option<Vec<f32>>,
+    /// Only return packages in this category (case-insensitive)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+    /// Searches sharing a session cancel each other: only the newest one
+    /// runs to completion (e.g. one session per search box)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session: Option<String>,
+    /// When the query finds little, retry it with misspelled words replaced
+    /// by the closest words from the package indexes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub autocorrect: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+struct EmbeddingFile {
+    packages: Vec<EmbeddedPackage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct EmbeddedPackage {
+    id: String,
+    embedding: Vec<f32>,
+}
+
+/// Embeddings and index for one source, as loaded from the cache
+struct SourceData {
+    source: String,
+    vectors: Vec<(String, Vec<f32>)>,
+    index: HashMap<String, Value>,
+    /// Lowercase ids and titles, each paired with its package id and sorted
+    /// so a prefix's matches form one contiguous run
+    prefixes: Vec<(String, String)>,
+    /// Distinct lowercase words from ids and titles, sorted, for autocorrect
+    words: Vec<String>,
 }
 
-/// Call Python search service for semantic search
-pub fn semantic_search(request: SearchRequest) -> Result<Vec<SearchResult>, String> {
-    // Get the path to the Python search service
-    let app_dir = std::env::current_dir().map_err(|e| e.to_string())?;
+impl SourceData {
+    fn new(source: &str, vectors: Vec<(String, Vec<f32>)>, index: HashMap<String, Value>) -> Self {
+        let mut prefixes = Vec::with_capacity(index.len() * 2);
+        for (id, info) in &index {
+            prefixes.push((id.to_lowercase(), id.clone()));
+            let title = index_field(info, "title").to_lowercase();
+            if !title.is_empty() && title != id.to_lowercase() {
+                prefixes.push((title, id.clone()));
+            }
+        }
+        prefixes.sort_unstable();
+
+        let mut words: Vec<String> = prefixes
+            .iter()
+            .flat_map(|(key, _)| query_words(key))
+            .map(str::to_string)
+            .collect();
+        words.sort_unstable();
+        words.dedup();
+
+        Self {
+            source: source.to_string(),
+            vectors,
+            index,
+            prefixes,
+            words,
+        }
+    }
+
+    /// Ids and titles starting with `prefix` (already lowercase), in order
+    fn prefix_matches<'a>(&'a self, prefix: &'a str) -> impl Iterator<Item = &'a (String, String)> + 'a {
+        let start = self.prefixes.partition_point(|(key, _)| key.as_str() < prefix);
+        self.prefixes[start..]
+            .iter()
+            .take_while(move |(key, _)| key.starts_with(prefix))
+    }
+}
+
+/// Lazily loaded data for one source; `None` once loaded means it isn't cached
+type SourceCell = Arc<OnceCell<Option<Arc<SourceData>>>>;
+
+/// In-memory embeddings for each source, read and decompressed once on first use
+#[derive(Default)]
+pub struct EmbeddingStore {
+    sources: Mutex<HashMap<String, SourceCell>>,
+    aliases: Mutex<Arc<OnceCell<Arc<AliasTable>>>>,
+    /// Read from here instead of the app's cache directory
+    cache_dir: Option<PathBuf>,
+}
+
+impl EmbeddingStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[cfg(test)]
+    fn with_cache_dir(cache_dir: &Path) -> Self {
+        Self {
+            cache_dir: Some(cache_dir.to_path_buf()),
+            ..Self::default()
+        }
+    }
+
+    fn cache_dir(&self) -> Result<PathBuf, String> {
+        match &self.cache_dir {
+            Some(dir) => Ok(dir.clone()),
+            None => data_cache::get_cache_dir(),
+        }
+    }
+
+    /// Drop everything loaded so far; the next search re-reads the cache files
+    pub fn reload(&self) {
+        self.sources.lock().unwrap().clear();
+        *self.aliases.lock().unwrap() = Arc::default();
+    }
+
+    /// The cross-source alias table, empty if it isn't cached
+    pub async fn aliases(&self) -> Arc<AliasTable> {
+        let cell = self.aliases.lock().unwrap().clone();
+        cell.get_or_init(|| async {
+            let Ok(cache_dir) = self.cache_dir() else {
+                return Arc::default();
+            };
+            tokio::task::spawn_blocking(move || AliasTable::load(&cache_dir))
+                .await
+                .map(Arc::new)
+                .unwrap_or_default()
+        })
+        .await
+        .clone()
+    }
+
+    /// Loaded data for a source, or `None` if its files aren't cached
+    async fn source(&self, source: &str) -> Result<Option<Arc<SourceData>>, String> {
+        let cell = self
+            .sources
+            .lock()
+            .unwrap()
+            .entry(source.to_string())
+            .or_default()
+            .clone();
+
+        let data = cell
+            .get_or_try_init(|| async {
+                let cache_dir = self.cache_dir()?;
+                let name = source.to_string();
+                tokio::task::spawn_blocking(move || load_source(&cache_dir, &name))
+                    .await
+                    .map_err(|e| format!("Failed to load embeddings: {}", e))?
+                    .map(|data| data.map(Arc::new))
+            })
+            .await?
+            .clone();
+
+        if data.is_none() {
+            // Forget the miss so files downloaded later are picked up without a reload
+            let mut sources = self.sources.lock().unwrap();
+            if sources.get(source).is_some_and(|c| Arc::ptr_eq(c, &cell)) {
+                sources.remove(source);
+            }
+        }
+
+        Ok(data)
+    }
+}
+
+/// Latest search generation per session, so a new query can cancel the
+/// ones still running for the same session
+#[derive(Default)]
+pub struct SearchSessions {
+    latest: Mutex<HashMap<String, watch::Sender<u64>>>,
+}
+
+impl SearchSessions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start a new search in `session`, superseding any earlier ones
+    fn begin(&self, session: &str) -> SearchTicket {
+        let mut latest = self.latest.lock().unwrap();
+        let sender = latest
+            .entry(session.to_string())
+            .or_insert_with(|| watch::channel(0).0);
+
+        let generation = *sender.borrow() + 1;
+        sender.send_replace(generation);
+
+        SearchTicket {
+            generation,
+            latest: sender.subscribe(),
+        }
+    }
+}
+
+/// One search's place in its session
+struct SearchTicket {
+    generation: u64,
+    latest: watch::Receiver<u64>,
+}
+
+impl SearchTicket {
+    fn is_superseded(&self) -> bool {
+        *self.latest.borrow() != self.generation
+    }
+
+    /// Resolves once a newer search starts in the same session
+    async fn superseded(&self) {
+        let mut latest = self.latest.clone();
+        if latest.wait_for(|&newest| newest != self.generation).await.is_err() {
+            std::future::pending::<()>().await;
+        }
+    }
+}
+
+/// Errors from running the Python search service
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", content = "message")]
+pub enum PythonError {
+    ScriptNotFound(String),
+    InterpreterNotFound(Vec<String>),
+    Timeout(u64),
+    ExecutionFailed(String),
+    ServiceError(String),
+    /// Output that isn't a valid response. `raw` keeps the start of what the
+    /// service printed (often a traceback) for diagnostics.
+    InvalidOutput { message: String, raw: String },
+    /// The cache directory lacks the index and embedding files to search
+    DataMissing(String),
+    /// A newer search in the same session took over
+    Superseded,
+}
+
+impl fmt::Display for PythonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PythonError::ScriptNotFound(path) => {
+                write!(f, "Python search service not found at {}", path)
+            }
+            PythonError::InterpreterNotFound(tried) => {
+                write!(f, "No working Python interpreter found (tried: {})", tried.join(", "))
+            }
+            PythonError::Timeout(secs) => {
+                write!(f, "Python service timed out after {}s and was stopped", secs)
+            }
+            PythonError::ExecutionFailed(msg) => write!(f, "Failed to execute Python service: {}", msg),
+            PythonError::ServiceError(msg) => write!(f, "Python service error: {}", msg),
+            PythonError::InvalidOutput { message, raw } => {
+                write!(f, "Failed to parse results: {} (output began: {:?})", message, raw)
+            }
+            PythonError::DataMissing(msg) => write!(f, "Search data missing: {}", msg),
+            PythonError::Superseded => write!(f, "Search superseded by a newer one"),
+        }
+    }
+}
+
+impl std::error::Error for PythonError {}
+
+/// Interpreter that answered `--version`, found on first search
+static PYTHON_INTERPRETER: OnceCell<String> = OnceCell::const_new();
+
+/// Get the Python search timeout, overridable with `SAVVY_SEARCH_TIMEOUT_SECS`
+pub fn search_timeout() -> Duration {
+    std::env::var("SAVVY_SEARCH_TIMEOUT_SECS")
+        .ok()
+        .and_then(|secs| secs.trim().parse::<u64>().ok())
+        .map(Duration::from_secs)
+        .unwrap_or(DEFAULT_SEARCH_TIMEOUT)
+}
+
+/// Find a working Python interpreter: `SAVVY_PYTHON`, then `python3`, then `python`
+pub async fn python_interpreter() -> Result<&'static str, PythonError> {
+    PYTHON_INTERPRETER
+        .get_or_try_init(|| async {
+            let mut candidates = Vec::new();
+            if let Ok(path) = std::env::var("SAVVY_PYTHON") {
+                if !path.trim().is_empty() {
+                    candidates.push(path.trim().to_string());
+                }
+            }
+            candidates.push("python3".to_string());
+            candidates.push("python".to_string());
+
+            for candidate in &candidates {
+                let responds = Command::new(candidate)
+                    .arg("--version")
+                    .stdout(Stdio::null())
+                    .stderr(Stdio::null())
+                    .status()
+                    .await
+                    .is_ok_and(|status| status.success());
+                if responds {
+                    return Ok(candidate.clone());
+                }
+            }
+
+            Err(PythonError::InterpreterNotFound(candidates))
+        })
+        .await
+        .map(String::as_str)
+}
+
+/// The interpreter [`python_interpreter`] picked and the version it reports,
+/// e.g. "Python 3.12.1"
+pub async fn python_version() -> Result<(&'static str, String), PythonError> {
+    let interpreter = python_interpreter().await?;
+    let output = Command::new(interpreter)
+        .arg("--version")
+        .output()
+        .await
+        .map_err(|e| PythonError::ExecutionFailed(e.to_string()))?;
+
+    // Python 2 printed its version to stderr
+    let version = [&output.stdout, &output.stderr]
+        .iter()
+        .map(|stream| String::from_utf8_lossy(stream).trim().to_string())
+        .find(|text| !text.is_empty())
+        .unwrap_or_default();
+    Ok((interpreter, version))
+}
+
+/// Get the path to the Python search service
+fn python_script() -> Result<PathBuf, PythonError> {
+    let app_dir = std::env::current_dir()
+        .map_err(|e| PythonError::ExecutionFailed(e.to_string()))?;
     let python_script = app_dir.join("python_service").join("search_service.py");
 
     if !python_script.exists() {
-        return Err(format!("Python search service not found at {:?}", python_script));
+        return Err(PythonError::ScriptNotFound(python_script.display().to_string()));
+    }
+
+    Ok(python_script)
+}
+
+/// The cache directory the Python service should read, after checking it
+/// holds an index and embeddings for at least one of the requested sources
+fn python_data_dir(source: Option<&str>) -> Result<PathBuf, PythonError> {
+    let cache_dir = data_cache::get_cache_dir().map_err(PythonError::DataMissing)?;
+    let sources: Vec<&str> = match source {
+        None | Some("both") => vec!["chocolatey", "winget"],
+        Some(source) => vec![source],
+    };
+
+    let has_file = |name: String| {
+        cache_dir.join(format!("{}.gz", name)).is_file() || cache_dir.join(name).is_file()
+    };
+    let available = sources.iter().any(|source| {
+        let prefix = cache_prefix(source);
+        has_file(format!("{}-index.json", prefix)) && has_file(format!("{}-embeddings.json", prefix))
+    });
+
+    if !available {
+        return Err(PythonError::DataMissing(format!(
+            "no index and embeddings for {} in {}; download the search data first",
+            sources.join(" or "),
+            cache_dir.display()
+        )));
     }
 
+    Ok(cache_dir)
+}
+
+/// Call Python search service for semantic search, killing it after `timeout`
+/// or as soon as `ticket` is superseded
+async fn semantic_search(
+    request: SearchRequest,
+    timeout: Duration,
+    ticket: Option<&SearchTicket>,
+) -> Result<SearchResponse, PythonError> {
+    let cache_dir = python_data_dir(request.source.as_deref())?;
+    let python_script = python_script()?;
+    let python = python_interpreter().await?;
+
     // Serialize request to JSON
     let request_json = serde_json::to_string(&request)
-        .map_err(|e| format!("Failed to serialize request: {}", e))?;
+        .map_err(|e| PythonError::ExecutionFailed(format!("Failed to serialize request: {}", e)))?;
 
-    // Call Python script
-    let output = Command::new("python")
+    // Call Python script; dropping the child on timeout kills it
+    let child = Command::new(python)
         .arg(python_script)
         .arg("search")
         .arg(&request_json)
-        .output()
-        .map_err(|e| format!("Failed to execute Python service: {}", e))?;
+        .env("SAVVY_CACHE_DIR", &cache_dir)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| PythonError::ExecutionFailed(e.to_string()))?;
+
+    let superseded = async {
+        match ticket {
+            Some(ticket) => ticket.superseded().await,
+            None => std::future::pending().await,
+        }
+    };
+
+    let output = tokio::select! {
+        output = tokio::time::timeout(timeout, child.wait_with_output()) => output,
+        _ = superseded => return Err(PythonError::Superseded),
+    }
+    .map_err(|_| PythonError::Timeout(timeout.as_secs()))?
+    .map_err(|e| PythonError::ExecutionFailed(e.to_string()))?;
 
     if !output.status.success() {
         let error = String::from_utf8_lossy(&output.stderr);
-        return Err(format!("Python service error: {}", error));
+        return Err(PythonError::ServiceError(error.to_string()));
+    }
+
+    parse_python_output(&output.stdout)
+}
+
+/// Semantic search through the best available backend: the Python daemon,
+/// then a one-shot Python process, then the offline Rust search.
+///
+/// If the request names a session, starting it supersedes earlier searches in
+/// that session: a one-shot process still running for one is killed, one
+/// still queued for the daemon is skipped, and either returns a response
+/// marked `superseded`. A query the daemon is already working on can't be
+/// interrupted, so it finishes and is then discarded.
+///
+/// With `autocorrect` set, a query that finds fewer than a handful of
+/// results is retried with misspelled words corrected; if that finds more,
+/// those results are returned along with the `corrected_query`.
+pub async fn search(
+    daemon: &PythonSearchDaemon,
+    store: &EmbeddingStore,
+    sessions: &SearchSessions,
+    mut request: SearchRequest,
+) -> Result<SearchResponse, String> {
+    let ticket = request.session.as_deref().map(|session| sessions.begin(session));
+    let autocorrect = request.autocorrect.take().unwrap_or(false);
+
+    let response = run_search(daemon, store, request.clone(), ticket.as_ref()).await?;
+    if !autocorrect || response.superseded || response.results.len() >= AUTOCORRECT_MIN_RESULTS {
+        return Ok(response);
+    }
+
+    let Some(corrected) = correct_query(store, &request.query, request.source.as_deref()).await? else {
+        return Ok(response);
+    };
+    tracing::debug!("Retrying search for {:?} as {:?}", request.query, corrected);
+
+    request.query = corrected.clone();
+    // The embedding was computed for the misspelled query
+    request.query_embedding = None;
+    let retried = run_search(daemon, store, request, ticket.as_ref()).await?;
+    if retried.superseded || retried.results.len() <= response.results.len() {
+        return Ok(response);
+    }
+
+    Ok(SearchResponse {
+        corrected_query: Some(corrected),
+        ..retried
+    })
+}
+
+/// One pass of [`search`] through the backends, without autocorrect
+async fn run_search(
+    daemon: &PythonSearchDaemon,
+    store: &EmbeddingStore,
+    mut request: SearchRequest,
+    ticket: Option<&SearchTicket>,
+) -> Result<SearchResponse, String> {
+    let timeout = search_timeout();
+    let request_source = request.source.clone();
+    let category = request.category.clone();
+
+    // Resolve defaults up front so every backend sees the same values
+    let limit = *request.limit.get_or_insert(DEFAULT_LIMIT);
+    let threshold = *request.threshold.get_or_insert(DEFAULT_THRESHOLD);
+
+    let python_result = match daemon.search(&request, timeout, ticket).await {
+        Ok(response) => Ok((response, SearchBackend::PythonDaemon)),
+        Err(PythonError::Superseded) => return Ok(SearchResponse::superseded()),
+        // No point retrying without a script or interpreter
+        Err(e @ (PythonError::ScriptNotFound(_) | PythonError::InterpreterNotFound(_) | PythonError::DataMissing(_))) => {
+            Err(e)
+        }
+        Err(e) => {
+            tracing::warn!("Python search daemon failed, trying one-shot search: {}", e);
+            semantic_search(request.clone(), timeout, ticket)
+                .await
+                .map(|response| (response, SearchBackend::Python))
+        }
+    };
+
+    let (response, backend) = match python_result {
+        Ok(served) => served,
+        Err(PythonError::Superseded) => return Ok(SearchResponse::superseded()),
+        Err(e) => {
+            tracing::warn!("Python search unavailable, using offline search: {}", e);
+            (offline_search(store, request).await?, SearchBackend::Offline)
+        }
+    };
+
+    let SearchResponse { mut results, mut total_matches, .. } = response;
+
+    // Older Python scripts ignore the category, so filter again here
+    if let Some(category) = category.as_deref() {
+        let before = results.len();
+        results.retain(|result| in_category(result.category.as_deref(), category));
+        total_matches -= (before - results.len()).min(total_matches);
+    }
+
+    tracing::debug!(
+        "Search served by {:?} backend ({} of {} results)",
+        backend,
+        results.len(),
+        total_matches
+    );
+    for result in &mut results {
+        result.backend = Some(backend);
+    }
+    if is_cross_source(request_source.as_deref()) {
+        results = merge_sources(results, store.aliases().await.as_ref());
+    }
+
+    if ticket.is_some_and(SearchTicket::is_superseded) {
+        return Ok(SearchResponse::superseded());
+    }
+
+    // The Python service may not honor these, so enforce them here too
+    Ok(apply_limits(results, total_matches, limit, threshold))
+}
+
+/// Lowercase words of a query or package name, split on anything that isn't
+/// a letter or digit
+fn query_words(text: &str) -> impl Iterator<Item = &str> {
+    text.split(|c: char| !c.is_alphanumeric()).filter(|word| !word.is_empty())
+}
+
+/// Edit distance a word may be from its correction: none for short words,
+/// where almost anything is one edit from something else, then growing with
+/// the word
+fn max_correction_distance(word: &str) -> usize {
+    match word.chars().count() {
+        0..=3 => 0,
+        4..=6 => 1,
+        _ => 2,
+    }
+}
+
+/// The query with each word that isn't in the package indexes replaced by
+/// the closest one that is, or `None` if nothing needed (or could be)
+/// corrected. Distance counts swapped letters as one edit, so "vsocde"
+/// becomes "vscode".
+async fn correct_query(store: &EmbeddingStore, query: &str, source: Option<&str>) -> Result<Option<String>, String> {
+    let sources: Vec<&str> = match source {
+        None | Some("both") => vec!["chocolatey", "winget"],
+        Some(source) => vec![source],
+    };
+
+    let mut loaded = Vec::new();
+    for source in sources {
+        if let Some(data) = store.source(source).await? {
+            loaded.push(data);
+        }
+    }
+    if loaded.is_empty() {
+        return Ok(None);
+    }
+
+    let query = query.trim().to_lowercase();
+    let mut changed = false;
+    let mut corrected = Vec::new();
+    for word in query_words(&query) {
+        let known = loaded.iter().any(|data| data.words.binary_search_by(|w| w.as_str().cmp(word)).is_ok());
+        let max_distance = max_correction_distance(word);
+        if known || max_distance == 0 {
+            corrected.push(word.to_string());
+            continue;
+        }
+
+        let closest = loaded
+            .iter()
+            .flat_map(|data| data.words.iter())
+            .map(|candidate| (strsim::damerau_levenshtein(word, candidate), candidate))
+            .filter(|(distance, _)| *distance <= max_distance)
+            .min_by_key(|(distance, _)| *distance);
+
+        match closest {
+            Some((_, candidate)) => {
+                changed = true;
+                corrected.push(candidate.clone());
+            }
+            None => corrected.push(word.to_string()),
+        }
+    }
+
+    Ok(changed.then(|| corrected.join(" ")))
+}
+
+/// Drop results scoring below `threshold`, then keep the best `limit`.
+///
+/// `total_matches` is the backend's count before its own limit. Merging
+/// cross-source duplicates happens after that count, so for those searches
+/// the total can overstate the distinct apps slightly.
+fn apply_limits(mut results: Vec<SearchResult>, total_matches: usize, limit: usize, threshold: f32) -> SearchResponse {
+    results.retain(|result| result.score >= threshold);
+    results.sort_by(|a, b| b.score.total_cmp(&a.score));
+    let total_matches = total_matches.max(results.len());
+    results.truncate(limit);
+    SearchResponse::new(results, total_matches)
+}
+
+/// Whether a package's category is `wanted`, ignoring case
+fn in_category(category: Option<&str>, wanted: &str) -> bool {
+    category.is_some_and(|c| c.eq_ignore_ascii_case(wanted))
+}
+
+/// Whether a search's `source` covers both package managers
+fn is_cross_source(source: Option<&str>) -> bool {
+    matches!(source, None | Some("both"))
+}
+
+/// Collapse results that are clearly the same app from different sources
+/// (same alias table entry, or same normalized title) into one, ranked by
+/// score. The kept result is the best-scoring one and `sources` lists every
+/// `source:id` it was found as.
+fn merge_sources(mut results: Vec<SearchResult>, aliases: &AliasTable) -> Vec<SearchResult> {
+    results.sort_by(|a, b| b.score.total_cmp(&a.score));
+
+    let mut merged: Vec<SearchResult> = Vec::new();
+    let mut by_key: HashMap<String, usize> = HashMap::new();
+
+    for mut result in results {
+        let entry = format!("{}:{}", result.source, result.id);
+        // Alias keys and titles are kept apart so one can't collide with the other
+        let keys: Vec<String> = aliases
+            .canonical_key(&result.id, &result.source)
+            .map(|key| format!("alias:{}", key))
+            .into_iter()
+            .chain(Some(normalized_title(&result.title)).filter(|name| !name.is_empty()))
+            .collect();
+
+        if let Some(i) = keys.iter().find_map(|key| by_key.get(key).copied()) {
+            let prefix = format!("{}:", result.source);
+            let existing = &mut merged[i];
+            if !existing.sources.iter().any(|s| s.starts_with(&prefix)) {
+                existing.sources.push(entry);
+                continue;
+            }
+        } else {
+            for key in keys {
+                by_key.insert(key, merged.len());
+            }
+        }
+
+        result.sources = vec![entry];
+        merged.push(result);
+    }
+
+    merged
+}
+
+/// Lowercase alphanumerics of a title, so "Google Chrome" and "google-chrome" compare equal
+fn normalized_title(title: &str) -> String {
+    title
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Ids the same app is known by in other sources, as `(source, id)` pairs,
+/// e.g. `("winget", "Google.Chrome")` for `googlechrome`.
+///
+/// The alias table is checked first; sources it doesn't cover fall back to
+/// cached index entries with the same normalized title. An id found in
+/// neither has no equivalents.
+pub async fn equivalent_ids(store: &EmbeddingStore, package_id: &str) -> Result<Vec<(String, String)>, String> {
+    let sources = ["chocolatey", "winget"];
+
+    let table = store.aliases().await;
+    let mut aliases: Vec<(String, String)> = Vec::new();
+    if let Some((own_source, key)) = sources
+        .iter()
+        .find_map(|&source| table.canonical_key(package_id, source).map(|key| (source, key)))
+    {
+        for source in sources.into_iter().filter(|&source| source != own_source) {
+            if let Some(id) = table.resolve(&key, source) {
+                aliases.push((source.to_string(), id));
+            }
+        }
+    }
+
+    let mut loaded = Vec::new();
+    for source in sources {
+        if let Some(data) = store.source(source).await? {
+            loaded.push(data);
+        }
     }
 
-    // Parse response
-    let results: Vec<SearchResult> = serde_json::from_slice(&output.stdout)
-        .map_err(|e| format!("Failed to parse results: {}", e))?;
+    let Some((data, info)) = loaded.iter().find_map(|data| {
+        data.index
+            .iter()
+            .find(|(id, _)| id.eq_ignore_ascii_case(package_id))
+            .map(|(_, info)| (data, info))
+    }) else {
+        return Ok(aliases);
+    };
+
+    let name = normalized_title(&index_field(info, "title"));
+    if !name.is_empty() {
+        for other in loaded.iter().filter(|other| other.source != data.source) {
+            if aliases.iter().any(|(source, _)| *source == other.source) {
+                continue;
+            }
+            if let Some((other_id, _)) = other
+                .index
+                .iter()
+                .find(|(_, other_info)| normalized_title(&index_field(other_info, "title")) == name)
+            {
+                aliases.push((other.source.clone(), other_id.clone()));
+            }
+        }
+    }
+
+    Ok(aliases)
+}
+
+/// Long-running `search_service.py --serve` process, so the embedding model
+/// is loaded once instead of on every query.
+///
+/// Requests and responses are single lines of JSON over stdin/stdout. The
+/// process is started on first use and restarted if it dies.
+#[derive(Default)]
+pub struct PythonSearchDaemon {
+    process: AsyncMutex<Option<DaemonProcess>>,
+}
+
+struct DaemonProcess {
+    child: Child,
+    stdin: ChildStdin,
+    stdout: AsyncBufReader<ChildStdout>,
+}
+
+impl PythonSearchDaemon {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Send a search to the daemon, starting or restarting it as needed.
+    /// A hung daemon is killed after `timeout`. Searches superseded while
+    /// waiting for the daemon are skipped, so fast typing can't queue up a
+    /// backlog of stale queries.
+    async fn search(
+        &self,
+        request: &SearchRequest,
+        timeout: Duration,
+        ticket: Option<&SearchTicket>,
+    ) -> Result<SearchResponse, PythonError> {
+        python_data_dir(request.source.as_deref())?;
+
+        let mut process = self.process.lock().await;
+        if ticket.is_some_and(SearchTicket::is_superseded) {
+            return Err(PythonError::Superseded);
+        }
+
+        // One retry, in case the previous daemon crashed since the last query
+        for attempt in 0..2 {
+            let running = match process.as_mut() {
+                Some(daemon) => matches!(daemon.child.try_wait(), Ok(None)),
+                None => false,
+            };
+            if !running {
+                *process = Some(DaemonProcess::spawn().await?);
+            }
+            let Some(daemon) = process.as_mut() else {
+                continue;
+            };
+
+            match tokio::time::timeout(timeout, daemon.request(request)).await {
+                Ok(Err(PythonError::ExecutionFailed(e))) => {
+                    // Pipe broke: drop (and kill) the process and try a fresh one
+                    *process = None;
+                    if attempt == 1 {
+                        return Err(PythonError::ExecutionFailed(e));
+                    }
+                }
+                Ok(result) => return result,
+                Err(_) => {
+                    *process = None;
+                    return Err(PythonError::Timeout(timeout.as_secs()));
+                }
+            }
+        }
+
+        Err(PythonError::ExecutionFailed("Python search daemon could not be started".to_string()))
+    }
+}
+
+impl DaemonProcess {
+    async fn spawn() -> Result<Self, PythonError> {
+        let cache_dir = data_cache::get_cache_dir().map_err(PythonError::DataMissing)?;
+        let python_script = python_script()?;
+        let python = python_interpreter().await?;
+
+        let mut child = Command::new(python)
+            .arg(python_script)
+            .arg("--serve")
+            .env("SAVVY_CACHE_DIR", &cache_dir)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| PythonError::ExecutionFailed(e.to_string()))?;
+
+        let stdin = child.stdin.take().ok_or_else(|| {
+            PythonError::ExecutionFailed("Python daemon stdin unavailable".to_string())
+        })?;
+        let stdout = child.stdout.take().ok_or_else(|| {
+            PythonError::ExecutionFailed("Python daemon stdout unavailable".to_string())
+        })?;
+
+        Ok(Self {
+            child,
+            stdin,
+            stdout: AsyncBufReader::new(stdout),
+        })
+    }
+
+    async fn request(&mut self, request: &SearchRequest) -> Result<SearchResponse, PythonError> {
+        let mut line = serde_json::to_string(request)
+            .map_err(|e| PythonError::ExecutionFailed(format!("Failed to serialize request: {}", e)))?;
+        line.push('\n');
+
+        self.stdin
+            .write_all(line.as_bytes())
+            .await
+            .map_err(|e| PythonError::ExecutionFailed(e.to_string()))?;
+        self.stdin
+            .flush()
+            .await
+            .map_err(|e| PythonError::ExecutionFailed(e.to_string()))?;
+
+        let mut response = String::new();
+        let read = self
+            .stdout
+            .read_line(&mut response)
+            .await
+            .map_err(|e| PythonError::ExecutionFailed(e.to_string()))?;
+        if read == 0 {
+            return Err(PythonError::ExecutionFailed("Python search daemon exited".to_string()));
+        }
+
+        parse_python_output(response.as_bytes())
+    }
+}
+
+/// Semantic search over the cached embeddings, computed entirely in Rust
+///
+/// There is no embedding model on this side, so unless the request carries a
+/// `query_embedding`, the query vector is the centroid of the packages whose
+/// id, title or tags mention one of the query terms.
+pub async fn offline_search(
+    store: &EmbeddingStore,
+    request: SearchRequest,
+) -> Result<SearchResponse, String> {
+    let limit = request.limit.unwrap_or(DEFAULT_LIMIT);
+    let threshold = request.threshold.unwrap_or(DEFAULT_THRESHOLD);
+
+    let sources: Vec<&str> = match request.source.as_deref() {
+        None | Some("both") => vec!["chocolatey", "winget"],
+        Some(source) => vec![source],
+    };
+
+    let mut loaded = Vec::new();
+    for source in sources {
+        if let Some(data) = store.source(source).await? {
+            loaded.push(data);
+        }
+    }
+    if loaded.iter().all(|data| data.vectors.is_empty()) {
+        return Err("No cached embeddings available for offline search".to_string());
+    }
+
+    let query_embedding = match request.query_embedding {
+        Some(embedding) => embedding,
+        None => match lexical_centroid(&request.query, &loaded) {
+            Some(embedding) => embedding,
+            None => return Ok(SearchResponse::new(Vec::new(), 0)),
+        },
+    };
+
+    let mut results = Vec::new();
+    for data in &loaded {
+        for (id, embedding) in &data.vectors {
+            let score = cosine_similarity(&query_embedding, embedding);
+            if score < threshold {
+                continue;
+            }
+
+            // Same as the Python service: only report packages we have metadata for
+            if let Some(info) = data.index.get(id) {
+                let category = index_category(info);
+                if let Some(wanted) = request.category.as_deref() {
+                    if !in_category(category.as_deref(), wanted) {
+                        continue;
+                    }
+                }
+
+                let title = index_field(info, "title");
+                results.push(SearchResult {
+                    id: id.clone(),
+                    title: if title.is_empty() { id.clone() } else { title },
+                    summary: index_field(info, "summary"),
+                    score,
+                    source: data.source.clone(),
+                    backend: Some(SearchBackend::Offline),
+                    sources: Vec::new(),
+                    category,
+                    tags: index_tags(info),
+                });
+            }
+        }
+    }
+
+    results.sort_by(|a, b| b.score.total_cmp(&a.score));
+    let total_matches = results.len();
+    results.truncate(limit);
+
+    Ok(SearchResponse::new(results, total_matches))
+}
+
+/// Rank index entries by Jaro-Winkler similarity of their id or title to the
+/// query. Searches both sources when `source` is `None`.
+pub async fn fuzzy_search(
+    store: &EmbeddingStore,
+    query: &str,
+    source: Option<&str>,
+    limit: usize,
+) -> Result<Vec<SearchResult>, String> {
+    let query = query.trim().to_lowercase();
+    if query.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let sources: Vec<&str> = match source {
+        None | Some("both") => vec!["chocolatey", "winget"],
+        Some(source) => vec![source],
+    };
+
+    let mut results = Vec::new();
+    for source in sources {
+        let Some(data) = store.source(source).await? else {
+            continue;
+        };
+
+        for (id, info) in &data.index {
+            let title = index_field(info, "title");
+            let score = strsim::jaro_winkler(&query, &id.to_lowercase())
+                .max(strsim::jaro_winkler(&query, &title.to_lowercase()));
+
+            results.push(SearchResult {
+                id: id.clone(),
+                title: if title.is_empty() { id.clone() } else { title },
+                summary: index_field(info, "summary"),
+                score: score as f32,
+                source: data.source.clone(),
+                backend: Some(SearchBackend::Fuzzy),
+                sources: Vec::new(),
+                category: index_category(info),
+                tags: index_tags(info),
+            });
+        }
+    }
+
+    if is_cross_source(source) {
+        results = merge_sources(results, store.aliases().await.as_ref());
+    }
+    results.sort_by(|a, b| b.score.total_cmp(&a.score));
+    results.truncate(limit);
 
     Ok(results)
 }
+
+/// Packages whose id or title starts with `prefix`, ignoring case, for
+/// completing the search box as the user types.
+///
+/// A binary search over the sorted ids and titles built when the index is
+/// loaded, so it stays fast on large indexes. Matches are in alphabetical
+/// order of the matching id or title. Searches both sources when `source`
+/// is `None`.
+pub async fn suggest(
+    store: &EmbeddingStore,
+    prefix: &str,
+    source: Option<&str>,
+    limit: usize,
+) -> Result<Vec<Suggestion>, String> {
+    let prefix = prefix.trim().to_lowercase();
+    if prefix.is_empty() || limit == 0 {
+        return Ok(Vec::new());
+    }
+
+    let sources: Vec<&str> = match source {
+        None | Some("both") => vec!["chocolatey", "winget"],
+        Some(source) => vec![source],
+    };
+
+    let mut matches: Vec<(String, Suggestion)> = Vec::new();
+    for source in sources {
+        let Some(data) = store.source(source).await? else {
+            continue;
+        };
+
+        let mut seen = HashSet::new();
+        for (key, id) in data.prefix_matches(&prefix) {
+            if !seen.insert(id) {
+                continue;
+            }
+            let title = data.index.get(id).map(|info| index_field(info, "title")).unwrap_or_default();
+            matches.push((
+                key.clone(),
+                Suggestion {
+                    id: id.clone(),
+                    title: if title.is_empty() { id.clone() } else { title },
+                    source: data.source.clone(),
+                },
+            ));
+            if seen.len() == limit {
+                break;
+            }
+        }
+    }
+
+    matches.sort_by(|a, b| a.0.cmp(&b.0));
+    matches.truncate(limit);
+
+    Ok(matches.into_iter().map(|(_, suggestion)| suggestion).collect())
+}
+
+/// Load the index and embeddings for a source, or `None` if the index isn't
+/// cached. Without an embeddings file the source has no vectors.
+fn load_source(cache_dir: &Path, source: &str) -> Result<Option<SourceData>, String> {
+    let prefix = cache_prefix(source);
+
+    let embeddings: Option<EmbeddingFile> =
+        read_cache_json(cache_dir, &format!("{}-embeddings.json", prefix))?;
+    let index: Option<HashMap<String, Value>> =
+        read_cache_json(cache_dir, &format!("{}-index.json", prefix))?;
+
+    Ok(index.map(|index| {
+        let vectors = embeddings
+            .map(|embeddings| {
+                embeddings
+                    .packages
+                    .into_iter()
+                    .map(|pkg| (pkg.id, pkg.embedding))
+                    .collect()
+            })
+            .unwrap_or_default();
+        SourceData::new(source, vectors, index)
+    }))
+}
+
+/// File name prefix of a source's cache files; Chocolatey's use the short "choco"
+fn cache_prefix(source: &str) -> &str {
+    match source {
+        "chocolatey" => "choco",
+        other => other,
+    }
+}
+
+/// Read a cached JSON file, preferring the gzipped download over a decompressed copy.
+///
+/// The file is gunzipped and parsed as it's read, through fixed-size
+/// buffers, so neither the compressed bytes nor the decompressed JSON text
+/// (several times larger for embeddings) is ever held whole. What stays in
+/// memory is the parsed value, and for embeddings that's the vectors search
+/// keeps resident anyway, as `f32`s rather than their longer decimal text.
+pub(crate) fn read_cache_json<T: DeserializeOwned>(cache_dir: &Path, name: &str) -> Result<Option<T>, String> {
+    let Some(reader) = open_cache_json(cache_dir, name)? else {
+        return Ok(None);
+    };
+
+    parse_json(reader)
+        .map(Some)
+        .map_err(|e| format!("Failed to parse {}: {}", name, e))
+}
+
+/// The JSON text of a cached file as a stream, gunzipped on the fly
+fn open_cache_json(cache_dir: &Path, name: &str) -> Result<Option<Box<dyn Read>>, String> {
+    let gz_path = cache_dir.join(format!("{}.gz", name));
+    let plain_path = cache_dir.join(name);
+
+    if gz_path.exists() {
+        let file = File::open(&gz_path)
+            .map_err(|e| format!("Failed to open {}: {}", gz_path.display(), e))?;
+        Ok(Some(Box::new(GzDecoder::new(file))))
+    } else if plain_path.exists() {
+        let file = File::open(&plain_path)
+            .map_err(|e| format!("Failed to open {}: {}", plain_path.display(), e))?;
+        Ok(Some(Box::new(file)))
+    } else {
+        Ok(None)
+    }
+}
+
+/// Parse JSON from `reader` through a fixed-size buffer
+fn parse_json<T: DeserializeOwned>(reader: impl Read) -> serde_json::Result<T> {
+    serde_json::from_reader(BufReader::new(reader))
+}
+
+/// Average the embeddings of packages whose id, title or tags contain a query term
+fn lexical_centroid(query: &str, loaded: &[Arc<SourceData>]) -> Option<Vec<f32>> {
+    let terms: Vec<String> = query.split_whitespace().map(str::to_lowercase).collect();
+    if terms.is_empty() {
+        return None;
+    }
+
+    let mut centroid: Vec<f32> = Vec::new();
+    let mut matched = 0usize;
+
+    for data in loaded {
+        for (id, embedding) in &data.vectors {
+            let info = data.index.get(id);
+            let haystack = format!(
+                "{} {} {}",
+                id,
+                info.map(|i| index_field(i, "title")).unwrap_or_default(),
+                info.map(|i| index_field(i, "tags")).unwrap_or_default(),
+            )
+            .to_lowercase();
+
+            if !terms.iter().any(|term| haystack.contains(term.as_str())) {
+                continue;
+            }
+
+            if centroid.is_empty() {
+                centroid = vec![0.0; embedding.len()];
+            }
+            if embedding.len() != centroid.len() {
+                continue;
+            }
+
+            for (sum, value) in centroid.iter_mut().zip(embedding) {
+                *sum += value;
+            }
+            matched += 1;
+        }
+    }
+
+    if matched == 0 {
+        return None;
+    }
+
+    for value in centroid.iter_mut() {
+        *value /= matched as f32;
+    }
+    Some(centroid)
+}
+
+/// Cosine similarity between two vectors, 0.0 if either is empty, zero or mismatched
+fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() || a.is_empty() {
+        return 0.0;
+    }
+
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return 0.0;
+    }
+
+    dot / (norm_a * norm_b)
+}
+
+/// Read a string field from an index entry; tag lists are joined with spaces
+fn index_field(entry: &Value, key: &str) -> String {
+    match entry.get(key) {
+        Some(Value::String(s)) => s.clone(),
+        Some(Value::Array(items)) => items
+            .iter()
+            .filter_map(Value::as_str)
+            .collect::<Vec<_>>()
+            .join(" "),
+        _ => String::new(),
+    }
+}
+
+/// An entry's category, if the index has a non-empty one
+fn index_category(entry: &Value) -> Option<String> {
+    Some(index_field(entry, "category").trim().to_string()).filter(|c| !c.is_empty())
+}
+
+/// An entry's tags: an array in winget indexes, a space- or comma-separated
+/// string in Chocolatey ones
+fn index_tags(entry: &Value) -> Vec<String> {
+    match entry.get("tags") {
+        Some(Value::String(s)) => s
+            .split(|c: char| c.is_whitespace() || c == ',')
+            .filter(|tag| !tag.is_empty())
+            .map(str::to_string)
+            .collect(),
+        Some(Value::Array(items)) => items
+            .iter()
+            .filter_map(Value::as_str)
+            .map(str::to_string)
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Passes reads through, noting the most asked for at once and the total
+    struct RecordingReader<R> {
+        inner: R,
+        largest_read: usize,
+        total: usize,
+    }
+
+    impl<R: Read> Read for RecordingReader<R> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            self.largest_read = self.largest_read.max(buf.len());
+            let read = self.inner.read(buf)?;
+            self.total += read;
+            Ok(read)
+        }
+    }
+
+    fn result(id: &str, score: f32) -> SearchResult {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "title": id,
+            "summary": "",
+            "score": score,
+            "source": "winget",
+        }))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn newer_search_supersedes_only_its_own_session() {
+        let sessions = SearchSessions::new();
+        let first = sessions.begin("main");
+        let other = sessions.begin("sidebar");
+        assert!(!first.is_superseded());
+
+        let waiting = tokio::spawn(async move {
+            first.superseded().await;
+            first.is_superseded()
+        });
+        tokio::task::yield_now().await;
+        assert!(!waiting.is_finished());
+
+        let second = sessions.begin("main");
+        let woke = tokio::time::timeout(Duration::from_secs(5), waiting).await.unwrap().unwrap();
+        assert!(woke);
+        assert!(!second.is_superseded());
+        assert!(!other.is_superseded());
+    }
+
+    #[test]
+    fn limits_drop_low_scores_then_truncate() {
+        let results = vec![result("a", 0.2), result("b", 0.9), result("c", 0.05), result("d", 0.5), result("e", 0.7)];
+
+        let response = apply_limits(results, 5, 2, 0.1);
+        let ids: Vec<&str> = response.results.iter().map(|r| r.id.as_str()).collect();
+        assert_eq!(ids, ["b", "e"]);
+        assert_eq!(response.total_matches, 5);
+        assert!(response.truncated);
+
+        let response = apply_limits(vec![result("a", 0.2), result("c", 0.05)], 2, 10, 0.1);
+        assert_eq!(response.results.len(), 1);
+        assert_eq!(response.total_matches, 2);
+        assert!(response.truncated);
+    }
+
+    #[test]
+    fn default_limits_keep_the_best_twenty() {
+        let results: Vec<SearchResult> = (0..30).map(|i| result(&format!("pkg{i}"), i as f32 / 30.0)).collect();
+
+        let response = apply_limits(results, 0, DEFAULT_LIMIT, DEFAULT_THRESHOLD);
+        assert_eq!(response.results.len(), DEFAULT_LIMIT);
+        assert_eq!(response.results[0].id, "pkg29");
+        assert_eq!(response.total_matches, 30);
+        assert!(response.truncated);
+        assert!(response.results.windows(2).all(|pair| pair[0].score >= pair[1].score));
+    }
+
+    /// Write `value` as the gzipped cache file `name`
+    fn write_gz_json(dir: &Path, name: &str, value: &Value) {
+        use flate2::write::GzEncoder;
+        use flate2::Compression;
+
+        let file = File::create(dir.join(format!("{}.gz", name))).unwrap();
+        let mut gz = GzEncoder::new(file, Compression::fast());
+        serde_json::to_writer(&mut gz, value).unwrap();
+        gz.finish().unwrap();
+    }
+
+    #[tokio::test]
+    async fn sources_are_read_once_until_reload() {
+        let dir = tempfile::tempdir().unwrap();
+        write_gz_json(
+            dir.path(),
+            "choco-index.json",
+            &serde_json::json!({"git": {"title": "Git"}, "nodejs": {"title": "Node.js"}}),
+        );
+        write_gz_json(
+            dir.path(),
+            "choco-embeddings.json",
+            &serde_json::json!({"packages": [
+                {"id": "git", "embedding": [1.0, 0.0]},
+                {"id": "nodejs", "embedding": [0.0, 1.0]}
+            ]}),
+        );
+        let store = EmbeddingStore::with_cache_dir(dir.path());
+
+        let first = store.source("chocolatey").await.unwrap().unwrap();
+        assert_eq!(first.vectors.len(), 2);
+
+        // With the files gone, later queries can only be served from memory
+        std::fs::remove_file(dir.path().join("choco-index.json.gz")).unwrap();
+        std::fs::remove_file(dir.path().join("choco-embeddings.json.gz")).unwrap();
+        for _ in 0..100 {
+            let again = store.source("chocolatey").await.unwrap().unwrap();
+            assert!(Arc::ptr_eq(&first, &again));
+        }
+
+        store.reload();
+        assert!(store.source("chocolatey").await.unwrap().is_none());
+    }
+
+    #[test]
+    fn gzipped_cache_json_is_parsed_without_buffering_it_whole() {
+        use flate2::write::GzEncoder;
+        use flate2::Compression;
+        use std::io::Write;
+
+        // 64 MiB of JSON once decompressed, nearly all of it whitespace
+        const PADDING: usize = 64 << 20;
+        let dir = tempfile::tempdir().unwrap();
+        let file = File::create(dir.path().join("test-embeddings.json.gz")).unwrap();
+        let mut gz = GzEncoder::new(file, Compression::fast());
+        let padding = vec![b' '; 1 << 20];
+        gz.write_all(br#"{"packages": ["#).unwrap();
+        for _ in 0..PADDING >> 20 {
+            gz.write_all(&padding).unwrap();
+        }
+        gz.write_all(br#"{"id": "git", "embedding": [0.5, 0.25]}]}"#).unwrap();
+        gz.finish().unwrap();
+
+        let text = open_cache_json(dir.path(), "test-embeddings.json").unwrap().unwrap();
+        let mut reader = RecordingReader {
+            inner: text,
+            largest_read: 0,
+            total: 0,
+        };
+        let parsed: EmbeddingFile = parse_json(&mut reader).unwrap();
+
+        assert_eq!(parsed.packages.len(), 1);
+        assert_eq!(parsed.packages[0].embedding, [0.5, 0.25]);
+        // All of the text went through, never more than a small buffer at a time
+        assert!(reader.total > PADDING);
+        assert!(reader.largest_read <= 64 << 10, "read {} bytes at once", reader.largest_read);
+    }
+}
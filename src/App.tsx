@@ -293,6 +293,11 @@ function App() {
         threshold: 0.2 // Lower threshold for more results
       });
 
+      // Superseded by a newer query, whose results will arrive instead
+      if (searchResults === null) {
+        return;
+      }
+
       console.log(`[App.handleSearch] Got ${searchResults.length} results from semantic search`);
 
       // Convert search results to Package type
@@ -13,6 +13,8 @@ export interface InstallResult {
   version?: string;
   output: string;
   error?: string;
+  duration_ms: number;
+  forced: boolean;
 }
 
 export interface UninstallResult {
@@ -20,6 +22,7 @@ export interface UninstallResult {
   package_id: string;
   output: string;
   error?: string;
+  duration_ms: number;
 }
 
 export interface UpgradeResult {
@@ -29,6 +32,7 @@ export interface UpgradeResult {
   new_version?: string;
   output: string;
   error?: string;
+  duration_ms: number;
 }
 
 export interface InstalledPackage {
@@ -37,6 +41,35 @@ export interface InstalledPackage {
   source: PackageSource;
 }
 
+export type PackageErrorType =
+  | 'NotFound'
+  | 'CommandFailed'
+  | 'PermissionDenied'
+  | 'AlreadyInstalled'
+  | 'NotInstalled'
+  | 'InvalidInput'
+  | 'RebootRequired'
+  | 'Unknown';
+
+/**
+ * Typed error returned by package commands
+ */
+export interface PackageError {
+  type: PackageErrorType;
+  message: string;
+}
+
+export function isPackageError(error: unknown): error is PackageError {
+  return typeof error === 'object' && error !== null && 'type' in error && 'message' in error;
+}
+
+/**
+ * Human-readable description of an error thrown by a package command
+ */
+export function describeError(error: unknown): string {
+  return isPackageError(error) ? `${error.type}: ${error.message}` : String(error);
+}
+
 export interface PackageOperation {
   package_id: string;
   source: PackageSource;
@@ -88,7 +121,7 @@ class PackageManagerService {
       return result;
     } catch (error) {
       operation.status = 'failed';
-      operation.error = String(error);
+      operation.error = describeError(error);
       this.notifyListeners();
 
       throw error;
@@ -129,7 +162,7 @@ class PackageManagerService {
       return result;
     } catch (error) {
       operation.status = 'failed';
-      operation.error = String(error);
+      operation.error = describeError(error);
       this.notifyListeners();
 
       throw error;
@@ -170,7 +203,7 @@ class PackageManagerService {
       return result;
     } catch (error) {
       operation.status = 'failed';
-      operation.error = String(error);
+      operation.error = describeError(error);
       this.notifyListeners();
 
       throw error;
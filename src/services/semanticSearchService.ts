@@ -19,8 +19,21 @@ interface TauriSearchResult {
   summary: string;
   score: number;
   source: 'chocolatey' | 'winget';
+  backend?: 'python_daemon' | 'python' | 'offline' | 'fuzzy';
+  sources?: string[];
 }
 
+interface TauriSearchResponse {
+  results: TauriSearchResult[];
+  total_matches: number;
+  truncated: boolean;
+  superseded?: boolean;
+  corrected_query?: string;
+}
+
+/** Session shared by every query from the search box, so a new query cancels older ones */
+const SEARCH_SESSION = 'search-box';
+
 class SemanticSearchService {
   private cacheReady = false;
 
@@ -36,8 +49,15 @@ class SemanticSearchService {
 
       if (!isValid) {
         console.log('Cache invalid or missing, downloading data...');
-        const files = await invoke<string[]>('download_cache_data');
-        console.log(`Downloaded ${files.length} files to cache:`, files);
+        const report = await invoke<{
+          succeeded: string[];
+          skipped: string[];
+          failed: { file_name: string; error: string }[];
+        }>('download_cache_data', { force: false });
+        console.log(`Downloaded ${report.succeeded.length} files to cache:`, report.succeeded);
+        if (report.failed.length > 0) {
+          console.warn('Some cache files failed to download:', report.failed);
+        }
       } else {
         console.log('Cache is valid, using existing data');
       }
@@ -52,13 +72,14 @@ class SemanticSearchService {
   }
 
   /**
-   * Search for packages using semantic similarity via Python backend
+   * Search for packages using semantic similarity via Python backend.
+   * Resolves to null when a newer search superseded this one.
    */
   async search(query: string, options: {
     source?: 'chocolatey' | 'winget' | 'both';
     limit?: number;
     threshold?: number;
-  } = {}): Promise<SearchResult[]> {
+  } = {}): Promise<SearchResult[] | null> {
     const {
       source = 'both',
       limit = 20,
@@ -75,16 +96,22 @@ class SemanticSearchService {
 
     try {
       // Call Python backend via Tauri
-      const results = await invoke<TauriSearchResult[]>('semantic_search', {
+      const response = await invoke<TauriSearchResponse>('semantic_search', {
         request: {
           query,
           source,
           limit,
-          threshold
+          threshold,
+          session: SEARCH_SESSION
         }
       });
+      // A newer query from the search box replaced this one
+      if (response.superseded) {
+        return null;
+      }
+      const results = response.results;
 
-      console.log(`[Semantic Search] Python backend returned ${results.length} results`);
+      console.log(`[Semantic Search] ${results[0]?.backend ?? 'Search'} backend returned ${results.length} of ${response.total_matches} results`);
 
       // Convert Tauri results to SearchResult format with full package metadata
       const searchResults: SearchResult[] = results.map(result => {
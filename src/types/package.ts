@@ -3,7 +3,7 @@ export interface Package {
   id: string;
   version: string;
   description: string;
-  source: 'chocolatey' | 'winget';
+  source: 'chocolatey' | 'winget' | 'homebrew';
   installed: boolean;
   category: string;
   hasUpdate?: boolean;